@@ -1,19 +1,35 @@
-use axum::{routing::{post, get}, Router, extract::Path, Json};
+use axum::{routing::{post, get}, Router, extract::{Path, Query}, Json};
+use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::StreamExt;
 use tower_http::cors::{Any, CorsLayer};
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use once_cell::sync::Lazy;
+use trustsystem_scoring::{self as scoring, Scored, ScoringMode};
 
 mod subjective;
 mod services;
 
+use services::disputes::{DisputeError, DisputeStatus};
+use services::jobs::{EnqueueOutcome, Enqueued, JobError, JobStatus, JobView, ProgressUpdate};
+use subjective::{Facets, Normalization, Opinion, TnaConfig, UserScores, DEFAULT_PRIOR_WEIGHT};
+
+/// `SCORING_MODE`: whether lookups are scored in this process.
+static SCORING_MODE: Lazy<ScoringMode> = Lazy::new(ScoringMode::from_env);
+
 #[derive(Deserialize)]
 struct LookupReq { handle: String, force: Option<bool> }
 
 #[derive(Serialize)]
-struct LookupResp { did: String, jobId: String, status: String }
+#[serde(rename_all = "camelCase")]
+struct LookupResp {
+    did: String,
+    #[serde(flatten)]
+    job: ScoreJobResp,
+}
 
 #[tokio::main]
 async fn main() {
@@ -22,94 +38,574 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    if std::env::var("GRAPH_MIGRATE").map_or(true, |v| v != "false") {
+        if let Err(e) = services::graph::migrate().await {
+            tracing::error!("graph schema migration failed: {e:#}");
+        }
+    }
+
+    tracing::info!(mode = ?*SCORING_MODE, "scoring mode");
+    tokio::spawn(services::transport::run_dispatcher());
+
     let cors = CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any);
 
     let app = Router::new()
         .route("/v1/lookup", post(lookup))
+        .route("/v1/user/:id", get(get_user))
         .route("/v1/user/:id/scores", get(get_scores))
-        .route("/v1/trust", post(post_trust))
+        .route("/v1/user/:id/scores/history", get(get_score_history))
+        .route("/v1/user/:id/trust", get(get_viewer_trust))
+        .route("/v1/user/:id/explain", get(get_explain))
+        .route("/v1/trust", post(post_trust).get(list_trust))
+        .route("/v1/jobs/:id", get(get_job))
+        .route("/v1/jobs/:id/events", get(job_events))
+        .route("/v1/disputes", post(post_dispute).get(list_disputes))
+        .route("/v1/disputes/:id", get(get_dispute))
         .route("/internal/jobs/score", post(internal_enqueue))
         .route("/internal/jobs/score/:id", get(internal_status))
         .route("/internal/jobs/next", get(internal_next_job))
+        .route("/internal/jobs", get(internal_list_jobs))
+        .route("/internal/jobs/score/:id/claim", post(internal_claim_job))
+        .route("/internal/jobs/score/:id/heartbeat", post(internal_heartbeat))
+        .route("/internal/jobs/score/:id/progress", post(internal_job_progress))
         .route("/internal/jobs/score/:id/done", post(internal_mark_done))
+        .route("/internal/jobs/score/:id/fail", post(internal_mark_failed))
+        .route("/internal/jobs/score/:id/release", post(internal_release_job))
+        .route("/internal/disputes/:id/review", post(internal_review_dispute))
+        .route("/internal/disputes/:id/accept", post(internal_accept_dispute))
+        .route("/internal/disputes/:id/reject", post(internal_reject_dispute))
         .route("/internal/upsert/scores", post(internal_upsert_scores))
+        .route("/internal/upsert/scored", post(internal_upsert_scored))
+        .route("/internal/upsert/follow", post(internal_upsert_follow))
+        .route("/internal/upsert/endorsement", post(internal_upsert_endorsement))
+        .route("/internal/upsert/interaction", post(internal_upsert_interaction))
         .layer(cors);
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await.unwrap();
     axum::serve(listener, app).await.unwrap();
 }
 
-async fn lookup(Json(req): Json<LookupReq>) -> Json<LookupResp> {
-    let did = services::atproto::resolve_handle_to_did(&req.handle).await.unwrap_or("did:unknown".into());
-    let job_id = Uuid::new_v4().to_string();
-    let _ = services::jobs::enqueue_score_job(&did, &job_id, req.force.unwrap_or(false)).await;
-    // Spawn inline processing task so results appear without separate workers
-    let did_clone = did.clone();
-    let handle_clone = req.handle.clone();
-    let job_id_clone = job_id.clone();
-    tokio::spawn(async move {
-        services::jobs::process_job_inline(did_clone, handle_clone, job_id_clone).await;
-    });
-    Json(LookupResp { did, jobId: job_id, status: "queued".into() })
+async fn lookup(Json(req): Json<LookupReq>) -> impl IntoResponse {
+    // jobs coalesce per DID, so an unresolved handle must not queue under a placeholder
+    let did = match scoring::atproto::resolve_handle_to_did(&req.handle).await {
+        Ok(did) => did,
+        Err(e) => {
+            let error = format!("could not resolve handle {}: {e}", req.handle);
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": error}))).into_response();
+        }
+    };
+    let enqueued = match services::jobs::enqueue_score_job(&did, req.force.unwrap_or(false), services::jobs::LOOKUP_PRIORITY).await {
+        Ok(e) => e,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    };
+    // In inline mode score here so results appear without separate workers;
+    // this also retries a coalesced job that failed and is due again. A job
+    // someone holds a live lease on is left to them
+    let inline = *SCORING_MODE == ScoringMode::Inline;
+    if let Some(job) = enqueued.runnable(chrono::Utc::now().timestamp_millis()).filter(|_| inline) {
+        let did_clone = did.clone();
+        let handle_clone = req.handle.clone();
+        let job_id_clone = job.job_id.clone();
+        tokio::spawn(async move {
+            services::jobs::process_job_inline(did_clone, handle_clone, job_id_clone).await;
+        });
+    }
+    Json(LookupResp { did, job: ScoreJobResp::from(&enqueued) }).into_response()
+}
+
+/// Stored document plus the facets aged to now; `facets` stays raw.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScoresResp {
+    #[serde(flatten)]
+    scores: UserScores,
+    decayed_facets: Facets,
+    decay: serde_json::Value,
+}
+
+async fn get_scores(Path(id): Path<String>) -> Json<ScoresResp> {
+    let scores = services::graph::get_user_scores(&id).await.unwrap_or_else(|_| UserScores::vacuous(&id));
+    let cfg = &*services::decay::DECAY_CONFIG;
+    let now = chrono::Utc::now().timestamp_millis();
+    let decayed_facets = cfg.decayed(&scores, now);
+    let decay = serde_json::json!({"asOf": now, "halfLifeDays": cfg.half_life_days});
+    Json(ScoresResp { scores, decayed_facets, decay })
+}
+
+async fn get_user(Path(id): Path<String>) -> impl IntoResponse {
+    let store = services::graph::store();
+    let res: anyhow::Result<Option<serde_json::Value>> = async {
+        let Some(user) = store.get_user(&id).await? else { return Ok(None) };
+        let following = store.follows_from(&id).await?;
+        let followers = store.followers_of(&id).await?;
+        let endorsements = store.endorsements_of(&id).await?;
+        let interactions = store.interactions_from(&id).await?;
+        let content = store.content_by_author(&id).await?;
+        Ok(Some(serde_json::json!({
+            "user": user, "following": following, "followers": followers,
+            "endorsements": endorsements, "interactions": interactions, "content": content
+        })))
+    }.await;
+    match res {
+        Ok(Some(v)) => Json(v).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "unknown user"}))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryQuery { facet: Option<String>, from: Option<i64>, to: Option<i64>, bucket_ms: Option<i64> }
+
+async fn get_score_history(Path(id): Path<String>, Query(q): Query<HistoryQuery>) -> impl IntoResponse {
+    let facet = q.facet.unwrap_or_else(|| "accuracy".to_string());
+    if !Facets::NAMES.contains(&facet.as_str()) {
+        let err = serde_json::json!({"error": "unknown facet", "facet": facet, "facets": Facets::NAMES});
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+    let (from, to) = (q.from.unwrap_or(0), q.to.unwrap_or(i64::MAX));
+    match services::graph::store().score_history(&id, &facet, from, to).await {
+        Ok(samples) => {
+            let max_points = services::graph::history::HISTORY_CONFIG.max_points;
+            let (points, bucket) = services::graph::history::downsample(samples, q.bucket_ms, max_points);
+            Json(serde_json::json!({
+                "did": id, "facet": facet, "from": from, "to": to, "bucketMs": bucket, "points": points
+            })).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ExplainQuery { facet: Option<String> }
+
+async fn get_explain(Path(id): Path<String>, Query(q): Query<ExplainQuery>) -> impl IntoResponse {
+    let facet = q.facet.unwrap_or_else(|| "accuracy".to_string());
+    if !Facets::NAMES.contains(&facet.as_str()) {
+        let err = serde_json::json!({"error": "unknown facet", "facet": facet, "facets": Facets::NAMES});
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+    let store = services::graph::store();
+    let now = chrono::Utc::now().timestamp_millis();
+    match services::explain::explain(store.as_ref(), &id, &facet, &services::decay::DECAY_CONFIG, now).await {
+        Ok(ex) => Json(ex).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Longest path a client may ask `/v1/user/:id/trust` to follow.
+const MAX_TRUST_HOPS: usize = 6;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ViewerTrustQuery { viewer: String, scope: Option<String>, max_hops: Option<usize> }
+
+async fn get_viewer_trust(Path(id): Path<String>, Query(q): Query<ViewerTrustQuery>) -> impl IntoResponse {
+    if q.viewer == id {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "viewer and target are the same user"}))).into_response();
+    }
+    let scope = q.scope.unwrap_or_else(|| "general".to_string());
+    let defaults = TnaConfig::default();
+    let cfg = TnaConfig {
+        max_depth: q.max_hops.unwrap_or(defaults.max_depth).clamp(1, MAX_TRUST_HOPS),
+        fusion: services::fusion::TRUST_FUSION.operator(&scope),
+        ..defaults
+    };
+    let store = services::graph::store();
+    match services::graph::viewer_trust(store.as_ref(), &q.viewer, &id, &scope, &cfg).await {
+        Ok(derived) => {
+            let (opinion, paths, pruned) = match derived {
+                Some(d) => (d.opinion, d.paths, d.pruned_edges),
+                None => (Opinion::new(0.0, 0.0, 1.0), Vec::new(), Vec::new()),
+            };
+            Json(serde_json::json!({
+                "viewer": q.viewer, "target": id, "scope": scope, "maxHops": cfg.max_depth,
+                "opinion": opinion, "projectedProbability": opinion.projected_probability(),
+                "paths": paths, "prunedEdges": pruned
+            })).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DisputeReq { did: String, facet: String, cid: String, reason: String, filed_by: String }
+
+fn dispute_error(e: DisputeError) -> axum::response::Response {
+    let code = match e {
+        DisputeError::NotFound | DisputeError::EvidenceNotFound => StatusCode::NOT_FOUND,
+        DisputeError::AlreadyDisputed(_) | DisputeError::InvalidTransition { .. } => StatusCode::CONFLICT,
+        DisputeError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (code, Json(serde_json::json!({"error": e.to_string(), "rule": e.rule()}))).into_response()
+}
+
+async fn post_dispute(Json(req): Json<DisputeReq>) -> impl IntoResponse {
+    if req.reason.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "reason is required"}))).into_response();
+    }
+    let store = services::graph::store();
+    let now = chrono::Utc::now().timestamp_millis();
+    match services::disputes::file(store.as_ref(), &req.did, &req.facet, &req.cid, &req.reason, &req.filed_by, now).await {
+        Ok(d) => (StatusCode::CREATED, Json(d)).into_response(),
+        Err(e) => dispute_error(e),
+    }
 }
 
-async fn get_scores(Path(id): Path<String>) -> Json<serde_json::Value> {
-    let val = services::graph::get_user_scores(&id).await.unwrap_or(serde_json::json!({"did": id}));
-    Json(val)
+#[derive(Deserialize)]
+struct DisputeQuery { did: Option<String>, status: Option<DisputeStatus> }
+
+async fn list_disputes(Query(q): Query<DisputeQuery>) -> impl IntoResponse {
+    match services::graph::store().disputes(q.did.as_deref(), q.status).await {
+        Ok(disputes) => Json(serde_json::json!({"disputes": disputes})).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+async fn get_dispute(Path(id): Path<String>) -> impl IntoResponse {
+    match services::graph::store().get_dispute(&id).await {
+        Ok(Some(d)) => Json(d).into_response(),
+        Ok(None) => dispute_error(DisputeError::NotFound),
+        Err(e) => dispute_error(e.into()),
+    }
 }
 
 #[derive(Deserialize)]
-struct ScoreJobReq { did: String, force: Option<bool> }
+struct ReviewReq { reviewer: String, note: Option<String> }
 
+async fn review_dispute(id: &str, to: DisputeStatus, req: ReviewReq) -> axum::response::Response {
+    let store = services::graph::store();
+    let now = chrono::Utc::now().timestamp_millis();
+    match services::disputes::transition(store.as_ref(), id, to, &req.reviewer, req.note, now).await {
+        Ok(d) => Json(d).into_response(),
+        Err(e) => dispute_error(e),
+    }
+}
+
+async fn internal_review_dispute(Path(id): Path<String>, Json(req): Json<ReviewReq>) -> impl IntoResponse {
+    review_dispute(&id, DisputeStatus::UnderReview, req).await
+}
+
+/// Accepting a dispute overturns the classification.
+async fn internal_accept_dispute(Path(id): Path<String>, Json(req): Json<ReviewReq>) -> impl IntoResponse {
+    review_dispute(&id, DisputeStatus::Overturned, req).await
+}
+
+async fn internal_reject_dispute(Path(id): Path<String>, Json(req): Json<ReviewReq>) -> impl IntoResponse {
+    review_dispute(&id, DisputeStatus::Upheld, req).await
+}
+
+#[derive(Deserialize)]
+struct ScoreJobReq { did: String, force: Option<bool>, priority: Option<i32> }
+
+/// `jobId` is the new or reused job; it is absent when fresh scores made
+/// a job unnecessary.
 #[derive(Serialize)]
-struct ScoreJobResp { jobId: String, status: String }
+#[serde(rename_all = "camelCase")]
+struct ScoreJobResp { job_id: Option<String>, status: String, outcome: EnqueueOutcome }
+
+impl From<&Enqueued> for ScoreJobResp {
+    fn from(e: &Enqueued) -> Self {
+        let status = e.job.as_ref().map_or(JobStatus::Done, |j| j.status);
+        Self { job_id: e.job.as_ref().map(|j| j.job_id.clone()), status: status.to_string(), outcome: e.outcome }
+    }
+}
 
-async fn internal_enqueue(Json(req): Json<ScoreJobReq>) -> Json<ScoreJobResp> {
-    let job_id = Uuid::new_v4().to_string();
-    let _ = services::jobs::enqueue_score_job(&req.did, &job_id, req.force.unwrap_or(false)).await;
-    Json(ScoreJobResp { jobId: job_id, status: "queued".into() })
+async fn internal_enqueue(Json(req): Json<ScoreJobReq>) -> impl IntoResponse {
+    match services::jobs::enqueue_score_job(&req.did, req.force.unwrap_or(false), req.priority.unwrap_or(0)).await {
+        Ok(enqueued) => Json(ScoreJobResp::from(&enqueued)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
 }
 
-async fn internal_status(Path(id): Path<String>) -> Json<serde_json::Value> {
-    let status = services::jobs::get_job_status(&id).await.unwrap_or("unknown".into());
-    Json(serde_json::json!({"jobId": id, "status": status}))
+fn job_error(e: JobError) -> axum::response::Response {
+    let code = match e {
+        JobError::NotFound => StatusCode::NOT_FOUND,
+        JobError::WrongState(_) | JobError::NotLeaseHolder | JobError::LeaseExpired => StatusCode::CONFLICT,
+        JobError::InvalidWorker(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        JobError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (code, Json(serde_json::json!({"error": e.to_string(), "rule": e.rule()}))).into_response()
+}
+
+async fn internal_status(Path(id): Path<String>) -> impl IntoResponse {
+    match services::jobs::get_job(&id).await {
+        Some(job) => Json(job).into_response(),
+        None => job_error(JobError::NotFound),
+    }
+}
+
+async fn get_job(Path(id): Path<String>) -> impl IntoResponse {
+    match services::jobs::get_job(&id).await {
+        Some(job) => Json(JobView::from(&job)).into_response(),
+        None => job_error(JobError::NotFound),
+    }
+}
+
+/// Server-sent `job` events carrying the public view until the job is done
+/// or dead-lettered.
+async fn job_events(Path(id): Path<String>) -> impl IntoResponse {
+    let Some(job) = services::jobs::get_job(&id).await else { return job_error(JobError::NotFound) };
+    let events = services::jobs::watch(job).map(|view| Event::default().event("job").json_data(view));
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
 }
 
 #[derive(Deserialize)]
-struct TrustReqOpinion { b: f32, d: f32, u: f32 }
+struct JobListQuery { status: Option<JobStatus> }
+
+async fn internal_list_jobs(Query(q): Query<JobListQuery>) -> impl IntoResponse {
+    match services::jobs::job_store().list(q.status).await {
+        Ok(jobs) => Json(serde_json::json!({"jobs": jobs})).into_response(),
+        Err(e) => job_error(e.into()),
+    }
+}
 
 #[derive(Deserialize)]
-struct TrustReq { fromDid: String, toDid: String, scope: String, opinion: TrustReqOpinion, evidenceRef: Option<String> }
+#[serde(rename_all = "camelCase")]
+struct TrustReq { from_did: String, to_did: String, scope: String, opinion: Opinion, evidence_ref: Option<String>, normalize: Option<bool> }
 
-async fn post_trust(Json(req): Json<TrustReq>) -> Json<serde_json::Value> {
+async fn post_trust(Json(req): Json<TrustReq>) -> impl IntoResponse {
+    let mode = if req.normalize.unwrap_or(false) { Normalization::Rescale } else { Normalization::Strict };
+    let opinion = match Opinion::try_from_parts(
+        req.opinion.b,
+        req.opinion.d,
+        req.opinion.u,
+        req.opinion.a,
+        mode,
+    ) {
+        Ok(o) => o,
+        Err(e) => {
+            let body = serde_json::json!({"error": "invalid opinion", "rule": e.rule(), "detail": e.to_string()});
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+        }
+    };
     let edge = services::graph::TrustEdge {
-        from_did: req.fromDid,
-        to_did: req.toDid,
+        from_did: req.from_did,
+        to_did: req.to_did,
         scope: req.scope,
-        b: req.opinion.b,
-        d: req.opinion.d,
-        u: req.opinion.u,
-        evidence_ref: req.evidenceRef,
+        opinion,
+        evidence_ref: req.evidence_ref,
+        ts: chrono::Utc::now().timestamp_millis(),
     };
-    let _ = services::graph::upsert_trust_edge(edge).await;
-    Json(serde_json::json!({"status": "ok"}))
+    if let Err(e) = services::graph::upsert_trust_edge(edge).await {
+        tracing::error!("upsert trust edge failed: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response();
+    }
+    Json(serde_json::json!({"status": "ok"})).into_response()
 }
 
-async fn internal_upsert_scores(Json(body): Json<serde_json::Value>) -> Json<serde_json::Value> {
-    let did = body.get("did").and_then(|v| v.as_str()).unwrap_or("did:unknown").to_string();
-    let _ = services::graph::upsert_user_scores(&did, body).await;
-    Json(serde_json::json!({"status": "ok", "did": did}))
+async fn list_trust(Query(q): Query<services::graph::TrustEdgeQuery>) -> impl IntoResponse {
+    match services::graph::store().trust_edges(&q).await {
+        Ok(edges) => Json(serde_json::json!({"edges": edges})).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
 }
 
-async fn internal_next_job() -> impl IntoResponse {
-    if let Some((job_id, did)) = services::jobs::pop_job().await {
-        return (StatusCode::OK, Json(serde_json::json!({"jobId": job_id, "did": did}))).into_response();
+async fn internal_upsert_scores(body: Result<Json<UserScores>, JsonRejection>) -> impl IntoResponse {
+    let scores = match body {
+        Ok(Json(scores)) => scores,
+        Err(e) => {
+            let err = serde_json::json!({"error": "invalid scores", "rule": "schema", "detail": e.body_text()});
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+        }
+    };
+    if let Err(e) = scores.validate() {
+        let err = serde_json::json!({"error": "invalid scores", "rule": e.rule(), "detail": e.to_string()});
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+    }
+    let did = scores.did.clone();
+    for (facet, rec) in scores.facets.iter() {
+        if let Err(e) = rec.check_consistency(DEFAULT_PRIOR_WEIGHT) {
+            tracing::warn!(%did, %facet, "inconsistent facet: {e}");
+        }
     }
-    StatusCode::NO_CONTENT.into_response()
+    if let Err(e) = services::graph::upsert_user_scores(scores).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response();
+    }
+    Json(serde_json::json!({"status": "ok", "did": did})).into_response()
 }
 
-async fn internal_mark_done(Path(id): Path<String>) -> impl IntoResponse {
-    services::jobs::mark_done(&id).await;
-    StatusCode::NO_CONTENT
+/// A worker's scoring run: posts, evidence and the score document.
+async fn internal_upsert_scored(body: Result<Json<Scored>, JsonRejection>) -> impl IntoResponse {
+    let scored = match body {
+        Ok(Json(scored)) => scored,
+        Err(e) => {
+            let err = serde_json::json!({"error": "invalid scores", "rule": "schema", "detail": e.body_text()});
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+        }
+    };
+    if let Err(e) = scored.scores.validate() {
+        let err = serde_json::json!({"error": "invalid scores", "rule": e.rule(), "detail": e.to_string()});
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+    }
+    let did = scored.scores.did.clone();
+    if let Err(e) = services::graph::apply_scored(scored).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response();
+    }
+    Json(serde_json::json!({"status": "ok", "did": did})).into_response()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FollowReq { from_did: String, to_did: String, ts: Option<i64> }
+
+async fn internal_upsert_follow(Json(req): Json<FollowReq>) -> impl IntoResponse {
+    let store = services::graph::store();
+    let ts = req.ts.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let res = async {
+        store.upsert_user(&req.from_did, None).await?;
+        store.upsert_user(&req.to_did, None).await?;
+        store.upsert_follow(&req.from_did, &req.to_did, ts).await
+    }.await;
+    match res {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EndorseReq { from_did: String, to_did: String, domain: String, strength: f64, ts: Option<i64> }
+
+async fn internal_upsert_endorsement(Json(req): Json<EndorseReq>) -> impl IntoResponse {
+    let store = services::graph::store();
+    let edge = services::graph::EndorseEdge {
+        from_did: req.from_did,
+        to_did: req.to_did,
+        domain: req.domain,
+        strength: req.strength,
+        ts: req.ts.unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+    };
+    let res = async {
+        store.upsert_user(&edge.from_did, None).await?;
+        store.upsert_user(&edge.to_did, None).await?;
+        store.upsert_endorsement(edge).await
+    }.await;
+    match res {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InteractReq { from_did: String, to_did: String, weight: f64, ts: Option<i64> }
+
+async fn internal_upsert_interaction(Json(req): Json<InteractReq>) -> impl IntoResponse {
+    let store = services::graph::store();
+    let edge = services::graph::InteractEdge {
+        from_did: req.from_did,
+        to_did: req.to_did,
+        weight: req.weight,
+        ts: req.ts.unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+    };
+    let res = async {
+        store.upsert_user(&edge.from_did, None).await?;
+        store.upsert_user(&edge.to_did, None).await?;
+        store.upsert_interaction(edge).await
+    }.await;
+    match res {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct NextJobQuery { worker: Option<String> }
+
+/// Leases the next job; the worker must heartbeat before `leaseExpiresAt`.
+async fn internal_next_job(Query(q): Query<NextJobQuery>) -> impl IntoResponse {
+    let worker = match services::jobs::external_worker(q.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    match services::jobs::claim_job(&worker).await {
+        Some(job) => (StatusCode::OK, Json(job)).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct LeaseReq { worker: Option<String>, error: Option<String> }
+
+/// Leases one job by id, for workers handed it by a pushing transport.
+async fn internal_claim_job(Path(id): Path<String>, body: Option<Json<LeaseReq>>) -> impl IntoResponse {
+    let Json(req) = body.unwrap_or_default();
+    let worker = match services::jobs::external_worker(req.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    match services::jobs::lease_job(&id, &worker).await {
+        Ok(job) => Json(job).into_response(),
+        Err(e) => job_error(e),
+    }
+}
+
+async fn internal_heartbeat(Path(id): Path<String>, body: Option<Json<LeaseReq>>) -> impl IntoResponse {
+    let Json(req) = body.unwrap_or_default();
+    let worker = match services::jobs::external_worker(req.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    match services::jobs::heartbeat(&id, &worker).await {
+        Ok(job) => Json(job).into_response(),
+        Err(e) => job_error(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct ProgressReq {
+    worker: Option<String>,
+    #[serde(flatten)]
+    progress: ProgressUpdate,
+}
+
+async fn internal_job_progress(Path(id): Path<String>, Json(req): Json<ProgressReq>) -> impl IntoResponse {
+    let worker = match services::jobs::external_worker(req.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    match services::jobs::report_progress(&id, &worker, req.progress).await {
+        Ok(job) => Json(JobView::from(&job)).into_response(),
+        Err(e) => job_error(e),
+    }
+}
+
+async fn internal_mark_done(Path(id): Path<String>, body: Option<Json<LeaseReq>>) -> impl IntoResponse {
+    let Json(req) = body.unwrap_or_default();
+    let worker = match services::jobs::external_worker(req.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    match services::jobs::complete_job(&id, &worker).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => job_error(e),
+    }
+}
+
+async fn internal_release_job(Path(id): Path<String>, body: Option<Json<LeaseReq>>) -> impl IntoResponse {
+    let Json(req) = body.unwrap_or_default();
+    let worker = match services::jobs::external_worker(req.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    match services::jobs::release_job(&id, &worker).await {
+        Ok(job) => Json(job).into_response(),
+        Err(e) => job_error(e),
+    }
+}
+
+async fn internal_mark_failed(Path(id): Path<String>, body: Option<Json<LeaseReq>>) -> impl IntoResponse {
+    let Json(req) = body.unwrap_or_default();
+    let worker = match services::jobs::external_worker(req.worker) {
+        Ok(w) => w,
+        Err(e) => return job_error(e),
+    };
+    let error = req.error.unwrap_or_else(|| "unspecified".to_string());
+    match services::jobs::fail_job(&id, &worker, &error).await {
+        Ok(job) => Json(job).into_response(),
+        Err(e) => job_error(e),
+    }
 }
 
 
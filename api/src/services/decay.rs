@@ -0,0 +1,67 @@
+//! Read-time ageing of stored scores.
+
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use trustsystem_core::{Facets, UserScores};
+
+use crate::services::fusion::FACET_FUSION;
+
+/// Per-facet half-lives, in days. Facets without one are served undecayed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecayConfig {
+    pub half_life_days: BTreeMap<String, f64>,
+}
+
+impl Default for DecayConfig {
+    fn default() -> Self {
+        Self { half_life_days: BTreeMap::from([("accuracy".to_string(), 180.0), ("civility".to_string(), 90.0)]) }
+    }
+}
+
+impl DecayConfig {
+    /// Parses `facet=days` pairs separated by commas, e.g.
+    /// `accuracy=180,civility=90`. Listed facets override the defaults; a
+    /// value of 0 disables decay for that facet.
+    pub fn parse(spec: &str) -> Self {
+        let mut cfg = Self::default();
+        for (facet, days) in spec.split(',').filter_map(|pair| pair.split_once('=')) {
+            match days.trim().parse::<f64>() {
+                Ok(days) if days > 0.0 => {
+                    cfg.half_life_days.insert(facet.trim().to_string(), days);
+                }
+                Ok(_) => {
+                    cfg.half_life_days.remove(facet.trim());
+                }
+                Err(_) => tracing::warn!(%facet, %days, "ignoring invalid half-life"),
+            }
+        }
+        cfg
+    }
+
+    /// Reads `SCORE_HALF_LIFE_DAYS`.
+    pub fn from_env() -> Self {
+        std::env::var("SCORE_HALF_LIFE_DAYS").map(|s| Self::parse(&s)).unwrap_or_default()
+    }
+
+    pub fn decayed(&self, scores: &UserScores, now_ms: i64) -> Facets {
+        scores.decayed_facets(now_ms, |facet| self.half_life_days.get(facet).copied(), &FACET_FUSION)
+    }
+}
+
+pub static DECAY_CONFIG: Lazy<DecayConfig> = Lazy::new(DecayConfig::from_env);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_parses_overrides() {
+        let cfg = DecayConfig::parse("accuracy=30, civility=0,bogus");
+        assert_eq!(cfg.half_life_days.get("accuracy"), Some(&30.0));
+        assert_eq!(cfg.half_life_days.get("civility"), None);
+        assert_eq!(DecayConfig::parse("").half_life_days.len(), 2);
+    }
+}
@@ -0,0 +1,387 @@
+//! Disputes against individual evidence records.
+//!
+//! A scored user files a dispute against one `(did, facet, cid)` record.
+//! Reviewers move it from `open` to `under_review` and then either uphold
+//! the classification or overturn it. Overturning reweights the record
+//! (to 0 by default, `DISPUTE_OVERTURN_WEIGHT`) and rescores the facet.
+//! Every transition is appended to the dispute's audit trail.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use trustsystem_core::{BetaEvidence, EvidenceRecord, OpinionRecord};
+use trustsystem_scoring::Scored;
+
+use crate::services::fusion::FACET_FUSION;
+use crate::services::graph::{self, GraphStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeStatus {
+    Open,
+    UnderReview,
+    /// The classification stands.
+    Upheld,
+    /// The classification was wrong; the record was reweighted.
+    Overturned,
+}
+
+impl DisputeStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DisputeStatus::Open => "open",
+            DisputeStatus::UnderReview => "under_review",
+            DisputeStatus::Upheld => "upheld",
+            DisputeStatus::Overturned => "overturned",
+        }
+    }
+
+    pub fn is_terminal(self) -> bool {
+        matches!(self, DisputeStatus::Upheld | DisputeStatus::Overturned)
+    }
+
+    /// Allowed moves: open → under_review, and open/under_review → a
+    /// terminal state.
+    pub fn can_transition(self, to: DisputeStatus) -> bool {
+        match (self, to) {
+            (DisputeStatus::Open, DisputeStatus::UnderReview) => true,
+            (from, to) => !from.is_terminal() && to.is_terminal(),
+        }
+    }
+}
+
+impl fmt::Display for DisputeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub ts: i64,
+    pub actor: String,
+    pub from: Option<DisputeStatus>,
+    pub to: DisputeStatus,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dispute {
+    pub id: String,
+    /// Subject of the disputed evidence.
+    pub did: String,
+    pub facet: String,
+    pub cid: String,
+    pub reason: String,
+    pub filed_by: String,
+    pub status: DisputeStatus,
+    /// Record weight before and after an overturn.
+    pub original_weight: f64,
+    pub resolved_weight: Option<f64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub audit: Vec<AuditEntry>,
+}
+
+#[derive(Debug)]
+pub enum DisputeError {
+    NotFound,
+    EvidenceNotFound,
+    /// Another dispute on the same record is still open.
+    AlreadyDisputed(String),
+    InvalidTransition { from: DisputeStatus, to: DisputeStatus },
+    Store(anyhow::Error),
+}
+
+impl DisputeError {
+    /// Machine-readable name for API error bodies.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            DisputeError::NotFound => "not_found",
+            DisputeError::EvidenceNotFound => "evidence_not_found",
+            DisputeError::AlreadyDisputed(_) => "already_disputed",
+            DisputeError::InvalidTransition { .. } => "invalid_transition",
+            DisputeError::Store(_) => "store",
+        }
+    }
+}
+
+impl fmt::Display for DisputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisputeError::NotFound => f.write_str("unknown dispute"),
+            DisputeError::EvidenceNotFound => f.write_str("no such evidence record"),
+            DisputeError::AlreadyDisputed(id) => write!(f, "record already has an unresolved dispute ({id})"),
+            DisputeError::InvalidTransition { from, to } => write!(f, "cannot move a dispute from {from} to {to}"),
+            DisputeError::Store(e) => write!(f, "{e:#}"),
+        }
+    }
+}
+
+impl std::error::Error for DisputeError {}
+
+impl From<anyhow::Error> for DisputeError {
+    fn from(e: anyhow::Error) -> Self { DisputeError::Store(e) }
+}
+
+/// Weight given to an overturned record; `DISPUTE_OVERTURN_WEIGHT`,
+/// default 0 (the record no longer counts).
+fn overturn_weight() -> f64 {
+    std::env::var("DISPUTE_OVERTURN_WEIGHT").ok().and_then(|v| v.parse().ok()).filter(|w: &f64| *w >= 0.0).unwrap_or(0.0)
+}
+
+async fn find_record(store: &dyn GraphStore, did: &str, facet: &str, cid: &str) -> Result<EvidenceRecord, DisputeError> {
+    let records = store.evidence_for(did, Some(facet)).await?;
+    records.into_iter().find(|r| r.cid == cid).ok_or(DisputeError::EvidenceNotFound)
+}
+
+pub async fn file(
+    store: &dyn GraphStore,
+    did: &str,
+    facet: &str,
+    cid: &str,
+    reason: &str,
+    filed_by: &str,
+    now_ms: i64,
+) -> Result<Dispute, DisputeError> {
+    let record = find_record(store, did, facet, cid).await?;
+    let pending = store.disputes(Some(did), None).await?;
+    if let Some(d) = pending.iter().find(|d| d.facet == facet && d.cid == cid && !d.status.is_terminal()) {
+        return Err(DisputeError::AlreadyDisputed(d.id.clone()));
+    }
+    let dispute = Dispute {
+        id: uuid::Uuid::new_v4().to_string(),
+        did: did.to_string(),
+        facet: facet.to_string(),
+        cid: cid.to_string(),
+        reason: reason.to_string(),
+        filed_by: filed_by.to_string(),
+        status: DisputeStatus::Open,
+        original_weight: record.weight,
+        resolved_weight: None,
+        created_at: now_ms,
+        updated_at: now_ms,
+        audit: vec![AuditEntry { ts: now_ms, actor: filed_by.to_string(), from: None, to: DisputeStatus::Open, note: Some(reason.to_string()) }],
+    };
+    store.upsert_dispute(dispute.clone()).await?;
+    Ok(dispute)
+}
+
+/// Moves dispute `id` to `to`, recording `actor` and `note`. Overturning
+/// reweights the disputed record and rescores its facet.
+pub async fn transition(
+    store: &dyn GraphStore,
+    id: &str,
+    to: DisputeStatus,
+    actor: &str,
+    note: Option<String>,
+    now_ms: i64,
+) -> Result<Dispute, DisputeError> {
+    let mut dispute = store.get_dispute(id).await?.ok_or(DisputeError::NotFound)?;
+    let from = dispute.status;
+    if !from.can_transition(to) {
+        return Err(DisputeError::InvalidTransition { from, to });
+    }
+    if to == DisputeStatus::Overturned {
+        let mut record = find_record(store, &dispute.did, &dispute.facet, &dispute.cid).await?;
+        record.weight = overturn_weight();
+        dispute.resolved_weight = Some(record.weight);
+        store.upsert_evidence(record).await?;
+        rescore_facet(store, &dispute.did, &dispute.facet, now_ms).await?;
+    }
+    dispute.status = to;
+    dispute.updated_at = now_ms;
+    dispute.audit.push(AuditEntry { ts: now_ms, actor: actor.to_string(), from: Some(from), to, note });
+    store.upsert_dispute(dispute.clone()).await?;
+    Ok(dispute)
+}
+
+/// Carries overturned disputes over to a fresh scoring run: records the
+/// run emits again at full weight get their resolved weight back, and the
+/// facets they feed are fused again from the reweighted records.
+pub async fn keep_overturned(store: &dyn GraphStore, scored: &mut Scored) -> anyhow::Result<()> {
+    let overturned = store.disputes(Some(&scored.scores.did), Some(DisputeStatus::Overturned)).await?;
+    let mut facets = Vec::new();
+    for record in &mut scored.evidence {
+        let resolved = overturned
+            .iter()
+            .filter(|d| d.facet == record.facet && d.cid == record.cid)
+            .max_by_key(|d| d.updated_at)
+            .and_then(|d| d.resolved_weight);
+        if let Some(weight) = resolved.filter(|w| *w != record.weight) {
+            record.weight = weight;
+            if !facets.contains(&record.facet) {
+                facets.push(record.facet.clone());
+            }
+        }
+    }
+    for facet in facets {
+        let records = scored.evidence.iter().filter(|r| r.facet == facet);
+        let Some(rec) = scored.scores.facets.get_mut(&facet) else { continue };
+        *rec = fuse_facet(&facet, rec.opinion.a, records.map(|r| (r.classifier.as_str(), r.evidence())));
+    }
+    scored.scores.observations = scored.evidence.iter().filter_map(EvidenceRecord::observation).collect();
+    Ok(())
+}
+
+/// `facet` fused from `(source, evidence)` items with the facet's
+/// operator, at base rate `a`.
+fn fuse_facet<'a>(facet: &str, a: f64, items: impl IntoIterator<Item = (&'a str, BetaEvidence)>) -> OpinionRecord {
+    let items = items.into_iter().map(|(source, ev)| (source, ev.with_base_rate(a)));
+    FACET_FUSION.fuse_evidence(facet, items).unwrap_or_else(|| BetaEvidence::default().with_base_rate(a)).into()
+}
+
+/// Rebuilds `facet` from its evidence records, fused per classifier with
+/// the facet's operator as at scoring time. Counts stored without a
+/// backing record are carried over as a source of their own, and the
+/// facet keeps its base rate.
+async fn rescore_facet(store: &dyn GraphStore, did: &str, facet: &str, now_ms: i64) -> anyhow::Result<()> {
+    let Some(mut scores) = store.get_scores(did).await? else { return Ok(()) };
+    let records = store.evidence_for(did, Some(facet)).await?;
+    let (old_alpha, old_beta) = scores
+        .observations
+        .iter()
+        .filter(|o| o.facet == facet)
+        .fold((0.0, 0.0), |(a, b), o| (a + o.alpha, b + o.beta));
+
+    let Some(rec) = scores.facets.get_mut(facet) else { return Ok(()) };
+    let unattributed =
+        BetaEvidence::new((rec.alpha.unwrap_or(0.0) - old_alpha).max(0.0), (rec.beta.unwrap_or(0.0) - old_beta).max(0.0));
+    let items = records
+        .iter()
+        .map(|r| (r.classifier.as_str(), r.evidence()))
+        .chain((unattributed.alpha + unattributed.beta > 0.0).then_some(("", unattributed)));
+    *rec = fuse_facet(facet, rec.opinion.a, items);
+    scores.observations.retain(|o| o.facet != facet);
+    scores.observations.extend(records.iter().filter_map(EvidenceRecord::observation));
+    scores.updated_at = now_ms;
+    graph::upsert_user_scores_in(store, scores).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::graph::MemoryGraphStore;
+    use trustsystem_core::{UserScores, DEFAULT_PRIOR_WEIGHT};
+
+    fn record(cid: &str, label: &str) -> EvidenceRecord {
+        EvidenceRecord {
+            did: "did:a".into(),
+            facet: "accuracy".into(),
+            cid: cid.into(),
+            uri: format!("at://did:a/app.bsky.feed.post/{cid}"),
+            domain: "politics".into(),
+            classifier: "gemini-claim-v1".into(),
+            label: label.into(),
+            weight: 1.0,
+            evidence_refs: vec![],
+            ts: 1,
+        }
+    }
+
+    #[test]
+    fn t_transitions() {
+        use DisputeStatus::*;
+        assert!(Open.can_transition(UnderReview));
+        assert!(Open.can_transition(Overturned));
+        assert!(UnderReview.can_transition(Upheld));
+        assert!(!UnderReview.can_transition(Open));
+        assert!(!Upheld.can_transition(Overturned));
+        assert!(!Overturned.can_transition(UnderReview));
+    }
+
+    #[tokio::test]
+    async fn t_overturning_rescores_the_facet() {
+        let store = MemoryGraphStore::default();
+        let t = chrono::Utc::now().timestamp_millis();
+        let records = [record("1", "inaccurate"), record("2", "accurate")];
+        let mut scores = UserScores::vacuous("did:a");
+        scores.observations = records.iter().filter_map(EvidenceRecord::observation).collect();
+        // one extra positive count with no record behind it
+        scores.facets.accuracy = OpinionRecord::from_evidence(2.0, 1.0, DEFAULT_PRIOR_WEIGHT);
+        store.upsert_scores(scores).await.unwrap();
+        for r in records {
+            store.upsert_evidence(r).await.unwrap();
+        }
+
+        let d = file(&store, "did:a", "accuracy", "1", "this was satire", "did:a", t + 10).await.unwrap();
+        assert!(matches!(
+            file(&store, "did:a", "accuracy", "1", "again", "did:a", t + 11).await,
+            Err(DisputeError::AlreadyDisputed(_))
+        ));
+        assert!(matches!(file(&store, "did:a", "accuracy", "9", "", "did:a", t + 11).await, Err(DisputeError::EvidenceNotFound)));
+
+        transition(&store, &d.id, DisputeStatus::UnderReview, "mod:1", None, t + 20).await.unwrap();
+        let done = transition(&store, &d.id, DisputeStatus::Overturned, "mod:1", Some("satire".into()), t + 30).await.unwrap();
+        assert_eq!(done.audit.iter().map(|a| a.to).collect::<Vec<_>>(), [DisputeStatus::Open, DisputeStatus::UnderReview, DisputeStatus::Overturned]);
+        assert_eq!(done.resolved_weight, Some(0.0));
+
+        let rescored = store.get_scores("did:a").await.unwrap().unwrap();
+        assert_eq!((rescored.facets.accuracy.alpha, rescored.facets.accuracy.beta), (Some(2.0), Some(0.0)));
+        assert_eq!(rescored.updated_at, t + 30);
+        assert_eq!(store.score_history("did:a", "accuracy", t, t + 100).await.unwrap().len(), 1);
+
+        assert!(matches!(
+            transition(&store, &d.id, DisputeStatus::Upheld, "mod:2", None, t + 40).await,
+            Err(DisputeError::InvalidTransition { .. })
+        ));
+        // resolved, so the record can be disputed again
+        assert!(file(&store, "did:a", "accuracy", "1", "new info", "did:a", t + 50).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn t_overturned_weights_survive_rescoring() {
+        let store = MemoryGraphStore::default();
+        let t = chrono::Utc::now().timestamp_millis();
+        // what every scoring run of the same two posts emits
+        let run = || {
+            let evidence = vec![record("1", "inaccurate"), record("2", "accurate")];
+            let mut scores = UserScores::vacuous("did:a");
+            scores.observations = evidence.iter().filter_map(EvidenceRecord::observation).collect();
+            scores.facets.accuracy = OpinionRecord::from_evidence(1.0, 1.0, DEFAULT_PRIOR_WEIGHT);
+            Scored { scores, evidence, content: vec![] }
+        };
+        graph::apply_scored_in(&store, run()).await.unwrap();
+        let d = file(&store, "did:a", "accuracy", "1", "this was satire", "did:a", t).await.unwrap();
+        transition(&store, &d.id, DisputeStatus::Overturned, "mod:1", None, t + 10).await.unwrap();
+
+        graph::apply_scored_in(&store, run()).await.unwrap();
+        let scores = store.get_scores("did:a").await.unwrap().unwrap();
+        assert_eq!((scores.facets.accuracy.alpha, scores.facets.accuracy.beta), (Some(1.0), Some(0.0)));
+        assert!(scores.observations.iter().all(|o| o.beta == 0.0));
+        assert_eq!(find_record(&store, "did:a", "accuracy", "1").await.unwrap().weight, 0.0);
+    }
+
+    #[tokio::test]
+    async fn t_overturning_fuses_the_facet_per_classifier() {
+        let store = MemoryGraphStore::default();
+        let t = chrono::Utc::now().timestamp_millis();
+        let civility = |cid: &str, classifier: &str, label: &str| EvidenceRecord {
+            facet: "civility".into(),
+            classifier: classifier.into(),
+            ..record(cid, label)
+        };
+        let records = [civility("1", "perspective-v1", "uncivil"), civility("2", "perspective-v1", "civil"), civility("3", "detoxify-v1", "civil")];
+        let mut scores = UserScores::vacuous("did:a");
+        scores.observations = records.iter().filter_map(EvidenceRecord::observation).collect();
+        scores.facets.civility = FACET_FUSION
+            .fuse_evidence("civility", records.iter().map(|r| (r.classifier.as_str(), r.evidence().with_base_rate(0.3))))
+            .unwrap()
+            .into();
+        store.upsert_scores(scores).await.unwrap();
+        for r in records {
+            store.upsert_evidence(r).await.unwrap();
+        }
+
+        let d = file(&store, "did:a", "civility", "1", "quoting someone", "did:a", t + 10).await.unwrap();
+        transition(&store, &d.id, DisputeStatus::Overturned, "mod:1", None, t + 20).await.unwrap();
+
+        // both classifiers now saw one civil post: averaged, not summed to two
+        let civ = store.get_scores("did:a").await.unwrap().unwrap().facets.civility;
+        assert!((civ.alpha.unwrap() - 1.0).abs() < 1e-9, "{civ:?}");
+        assert!(civ.beta.unwrap().abs() < 1e-9, "{civ:?}");
+        assert!((civ.opinion.a - 0.3).abs() < 1e-9);
+    }
+}
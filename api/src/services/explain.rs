@@ -0,0 +1,120 @@
+//! Breakdown of a facet opinion into the evidence records behind it.
+
+use anyhow::Result;
+use serde::Serialize;
+use trustsystem_core::{decay_evidence, BetaEvidence, EvidenceRecord, Facets, OpinionRecord, UserScores};
+
+use crate::services::decay::DecayConfig;
+use crate::services::graph::GraphStore;
+
+const DAY_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// One record and what it contributes to the facet today.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainedItem {
+    #[serde(flatten)]
+    pub record: EvidenceRecord,
+    pub alpha: f64,
+    pub beta: f64,
+    /// Counts after read-time decay.
+    pub decayed_alpha: f64,
+    pub decayed_beta: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Explanation {
+    pub did: String,
+    pub facet: String,
+    pub as_of: i64,
+    pub half_life_days: Option<f64>,
+    /// Stored and decayed facet opinions, as served by `/scores`.
+    pub opinion: OpinionRecord,
+    pub decayed: OpinionRecord,
+    /// Newest first; records that count neither way are included.
+    pub items: Vec<ExplainedItem>,
+    /// Stored counts not accounted for by any record (e.g. scores written
+    /// before per-post evidence was kept).
+    pub unattributed_alpha: f64,
+    pub unattributed_beta: f64,
+}
+
+pub async fn explain(store: &dyn GraphStore, did: &str, facet: &str, cfg: &DecayConfig, now_ms: i64) -> Result<Explanation> {
+    let scores = store.get_scores(did).await?.unwrap_or_else(|| UserScores::vacuous(did));
+    let opinion = scores.facets.get(facet).copied().unwrap_or(Facets::vacuous().accuracy);
+    let decayed = cfg.decayed(&scores, now_ms).get(facet).copied().unwrap_or(opinion);
+    let half_life = cfg.half_life_days.get(facet).copied();
+
+    let items: Vec<ExplainedItem> = store
+        .evidence_for(did, Some(facet))
+        .await?
+        .into_iter()
+        .map(|record| {
+            let (alpha, beta) = record.counts();
+            let evidence = BetaEvidence::new(alpha, beta).with_base_rate(opinion.opinion.a);
+            let aged = match half_life {
+                Some(h) => decay_evidence(evidence, ((now_ms - record.ts) as f64 / DAY_MS).max(0.0), h),
+                None => evidence,
+            };
+            ExplainedItem { record, alpha, beta, decayed_alpha: aged.alpha, decayed_beta: aged.beta }
+        })
+        .collect();
+
+    let (alpha, beta) = items.iter().fold((0.0, 0.0), |(a, b), i| (a + i.alpha, b + i.beta));
+    Ok(Explanation {
+        did: did.to_string(),
+        facet: facet.to_string(),
+        as_of: now_ms,
+        half_life_days: half_life,
+        opinion,
+        decayed,
+        items,
+        unattributed_alpha: (opinion.alpha.unwrap_or(0.0) - alpha).max(0.0),
+        unattributed_beta: (opinion.beta.unwrap_or(0.0) - beta).max(0.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::graph::MemoryGraphStore;
+    use trustsystem_core::DEFAULT_PRIOR_WEIGHT;
+
+    fn record(cid: &str, label: &str, ts: i64) -> EvidenceRecord {
+        EvidenceRecord {
+            did: "did:a".into(),
+            facet: "accuracy".into(),
+            cid: cid.into(),
+            uri: format!("at://did:a/app.bsky.feed.post/{cid}"),
+            domain: "politics".into(),
+            classifier: "gemini-claim-v1".into(),
+            label: label.into(),
+            weight: 1.0,
+            evidence_refs: vec![],
+            ts,
+        }
+    }
+
+    #[tokio::test]
+    async fn t_items_add_up_to_the_stored_opinion() {
+        let store = MemoryGraphStore::default();
+        let now = 400 * DAY_MS as i64;
+        for r in [record("1", "accurate", now), record("2", "inaccurate", 0), record("3", "contested", now)] {
+            store.upsert_evidence(r).await.unwrap();
+        }
+        let rec = OpinionRecord::from_evidence(2.0, 1.0, DEFAULT_PRIOR_WEIGHT);
+        let scores = UserScores { updated_at: now, facets: Facets { accuracy: rec, civility: rec }, ..UserScores::vacuous("did:a") };
+        store.upsert_scores(scores).await.unwrap();
+
+        let cfg = DecayConfig::parse("accuracy=100");
+        let ex = explain(&store, "did:a", "accuracy", &cfg, now).await.unwrap();
+        assert_eq!(ex.items.len(), 3);
+        assert_eq!(ex.items.last().unwrap().record.cid, "2");
+        assert_eq!((ex.unattributed_alpha, ex.unattributed_beta), (1.0, 0.0));
+        let old = ex.items.iter().find(|i| i.record.cid == "2").unwrap();
+        assert!(old.decayed_beta < 0.1);
+        let fresh = ex.items.iter().find(|i| i.record.cid == "1").unwrap();
+        assert!((fresh.decayed_alpha - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,23 @@
+//! Which fusion operator each facet uses, shared by scoring and read-time
+//! decay, and which one each trust scope uses for derived trust.
+
+use once_cell::sync::Lazy;
+use trustsystem_core::{FacetFusion, TnaConfig};
+
+/// Read once from `FACET_FUSION`; see [`trustsystem_scoring::facet_fusion_from_env`].
+pub static FACET_FUSION: Lazy<FacetFusion> = Lazy::new(trustsystem_scoring::facet_fusion_from_env);
+
+/// Read once from `TRUST_FUSION`; see [`trust_fusion_from_env`].
+pub static TRUST_FUSION: Lazy<FacetFusion> = Lazy::new(trust_fusion_from_env);
+
+/// How parallel trust paths fuse in each scope: [`TnaConfig`]'s operator,
+/// overridden per scope by `TRUST_FUSION`, e.g. `medicine=averaging`.
+/// Scopes don't inherit anything from `FACET_FUSION`.
+pub fn trust_fusion_from_env() -> FacetFusion {
+    let default = FacetFusion::new(TnaConfig::default().fusion);
+    let spec = std::env::var("TRUST_FUSION").unwrap_or_default();
+    default.clone().with_spec(&spec).unwrap_or_else(|e| {
+        tracing::warn!(%spec, error = %e, "ignoring TRUST_FUSION");
+        default
+    })
+}
@@ -0,0 +1,657 @@
+//! JanusGraph / Gremlin Server backend speaking the Gremlin WebSocket
+//! protocol with GraphSON 3 serialization.
+//!
+//! Labels and property keys follow `graph/schema.groovy`. Requests are
+//! sessionless script evaluations with bindings, so each one commits on
+//! its own. The integration tests run against any Gremlin Server, e.g. a
+//! local TinkerGraph one:
+//!
+//! ```text
+//! docker run --rm -p 8182:8182 tinkerpop/gremlin-server
+//! GREMLIN_TEST_URL=ws://localhost:8182/gremlin cargo test -p trustsystem-api -- --ignored gremlin
+//! ```
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Map, Value};
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, Semaphore};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use trustsystem_core::{EvidenceRecord, Opinion, UserScores, DEFAULT_BASE_RATE};
+use uuid::Uuid;
+
+use crate::services::disputes::{Dispute, DisputeStatus};
+
+use super::migrations::gremlin_script;
+use super::{Content, EndorseEdge, Migration, ScoreSample, FollowEdge, GraphStore, InteractEdge, TrustEdge, TrustEdgeQuery, User};
+
+const MIME: &str = "application/vnd.gremlin-v3.0+json";
+
+type Conn = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Pooled Gremlin Server client. At most `pool_size` requests are in
+/// flight; connections are opened lazily and reused, and dropped after
+/// any error.
+pub struct GremlinClient {
+    url: String,
+    idle: Mutex<Vec<Conn>>,
+    permits: Semaphore,
+    timeout: Duration,
+}
+
+impl GremlinClient {
+    pub fn new(url: &str, pool_size: usize, timeout: Duration) -> Self {
+        Self {
+            url: normalize_url(url),
+            idle: Mutex::new(Vec::new()),
+            permits: Semaphore::new(pool_size.max(1)),
+            timeout,
+        }
+    }
+
+    /// Evaluates `gremlin` with `bindings` and returns the result items
+    /// decoded from GraphSON into plain JSON.
+    pub async fn submit(&self, gremlin: &str, bindings: Value) -> Result<Vec<Value>> {
+        let _permit = self.permits.acquire().await?;
+        let pooled = self.idle.lock().await.pop();
+        let mut conn = match pooled {
+            Some(c) => c,
+            None => {
+                let (c, _) = tokio_tungstenite::connect_async(self.url.as_str())
+                    .await
+                    .with_context(|| format!("connect to gremlin server at {}", self.url))?;
+                c
+            }
+        };
+        let res = tokio::time::timeout(self.timeout, roundtrip(&mut conn, gremlin, bindings))
+            .await
+            .map_err(|_| anyhow!("gremlin request timed out"))?;
+        if res.is_ok() {
+            self.idle.lock().await.push(conn);
+        }
+        res
+    }
+}
+
+async fn roundtrip(conn: &mut Conn, gremlin: &str, bindings: Value) -> Result<Vec<Value>> {
+    let request_id = Uuid::new_v4().to_string();
+    let msg = json!({
+        "requestId": {"@type": "g:UUID", "@value": request_id},
+        "op": "eval",
+        "processor": "",
+        "args": {"gremlin": gremlin, "bindings": bindings, "language": "gremlin-groovy"}
+    });
+    let mut payload = vec![MIME.len() as u8];
+    payload.extend_from_slice(MIME.as_bytes());
+    payload.extend(serde_json::to_vec(&msg)?);
+    conn.send(Message::Binary(payload)).await?;
+
+    let mut out = Vec::new();
+    while let Some(frame) = conn.next().await {
+        let bytes = match frame? {
+            Message::Text(t) => t.into_bytes(),
+            Message::Binary(b) => b,
+            Message::Close(_) => bail!("gremlin server closed the connection"),
+            _ => continue,
+        };
+        let resp = from_graphson(serde_json::from_slice(&bytes)?);
+        if resp["requestId"].as_str() != Some(request_id.as_str()) {
+            continue;
+        }
+        let code = resp["status"]["code"].as_u64().unwrap_or(0);
+        match code {
+            200 | 206 => match resp["result"]["data"].clone() {
+                Value::Array(items) => out.extend(items),
+                Value::Null => {}
+                other => out.push(other),
+            },
+            204 => {}
+            _ => bail!("gremlin error {}: {}", code, resp["status"]["message"].as_str().unwrap_or("")),
+        }
+        if code != 206 {
+            return Ok(out);
+        }
+    }
+    bail!("gremlin connection ended before the response completed")
+}
+
+/// Accepts `GRAPH_HOST` in the forms used by our deployments
+/// (`http://janusgraph:8182`, `janusgraph:8182`, `ws://…/gremlin`).
+fn normalize_url(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    let url = if let Some(rest) = host.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else if let Some(rest) = host.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if host.starts_with("ws://") || host.starts_with("wss://") {
+        host.to_string()
+    } else {
+        format!("ws://{host}")
+    };
+    let has_path = url.split("://").nth(1).is_some_and(|rest| rest.contains('/'));
+    if has_path { url } else { format!("{url}/gremlin") }
+}
+
+/// Strips GraphSON 3 type wrappers. Maps become JSON objects with
+/// stringified keys.
+pub fn from_graphson(v: Value) -> Value {
+    match v {
+        Value::Object(mut map) if map.contains_key("@type") && map.contains_key("@value") => {
+            let ty = map.get("@type").and_then(Value::as_str).unwrap_or_default().to_string();
+            let value = map.remove("@value").unwrap_or(Value::Null);
+            match (ty.as_str(), value) {
+                ("g:List" | "g:Set", Value::Array(items)) => Value::Array(items.into_iter().map(from_graphson).collect()),
+                ("g:Map", Value::Array(items)) => {
+                    let mut out = Map::new();
+                    let mut it = items.into_iter();
+                    while let (Some(k), Some(v)) = (it.next(), it.next()) {
+                        let key = match from_graphson(k) {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        out.insert(key, from_graphson(v));
+                    }
+                    Value::Object(out)
+                }
+                ("g:BulkSet", Value::Array(items)) => {
+                    let mut out = Vec::new();
+                    let mut it = items.into_iter();
+                    while let (Some(v), Some(n)) = (it.next(), it.next()) {
+                        let v = from_graphson(v);
+                        let n = from_graphson(n).as_u64().unwrap_or(1);
+                        out.extend(std::iter::repeat_n(v, n as usize));
+                    }
+                    Value::Array(out)
+                }
+                (_, value) => from_graphson(value),
+            }
+        }
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, from_graphson(v))).collect()),
+        Value::Array(items) => Value::Array(items.into_iter().map(from_graphson).collect()),
+        other => other,
+    }
+}
+
+fn long(v: i64) -> Value { json!({"@type": "g:Int64", "@value": v}) }
+
+fn float(v: f64) -> Value { json!({"@type": "g:Float", "@value": v as f32}) }
+
+fn str_field(v: &Value, key: &str) -> String {
+    v[key].as_str().unwrap_or_default().to_string()
+}
+
+fn opt_str_field(v: &Value, key: &str) -> Option<String> {
+    v[key].as_str().filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+// Projections shared by the read queries; missing optional properties
+// come back as '' / 0 and are mapped to None by the decoders.
+const USER_PROJECTION: &str = ".project('did','handle','createdAt').by('did').by(coalesce(values('handle'),constant(''))).by(coalesce(values('createdAt'),constant(0L)))";
+const TRUST_PROJECTION: &str = ".project('fromDid','toDid','scope','b','d','u','a','evidenceRef','ts').by(outV().values('did')).by(inV().values('did')).by('scope').by('b').by('d').by('u').by(coalesce(values('a'),constant(0.5d))).by(coalesce(values('evidenceRef'),constant(''))).by(coalesce(values('ts'),constant(0L)))";
+const FOLLOW_PROJECTION: &str = ".project('fromDid','toDid','ts').by(outV().values('did')).by(inV().values('did')).by(coalesce(values('ts'),constant(0L)))";
+const ENDORSE_PROJECTION: &str = ".project('fromDid','toDid','domain','strength','ts').by(outV().values('did')).by(inV().values('did')).by('domain').by('strength').by(coalesce(values('ts'),constant(0L)))";
+const INTERACT_PROJECTION: &str = ".project('fromDid','toDid','weight','ts').by(outV().values('did')).by(inV().values('did')).by('weight').by(coalesce(values('ts'),constant(0L)))";
+const CONTENT_PROJECTION: &str = ".project('cid','uri','authorDid','domain','classification','createdAt').by('cid').by(coalesce(values('uri'),constant(''))).by('authorDid').by(coalesce(values('domain'),constant(''))).by(coalesce(values('classification'),constant(''))).by(coalesce(values('createdAt'),constant(0L)))";
+
+const EVIDENCE_PROJECTION: &str = ".project('did','facet','cid','uri','domain','classifier','label','weight','evidenceRefs','ts').by(outV().values('did')).by('facet').by(inV().values('cid')).by(inV().coalesce(values('uri'),constant(''))).by(coalesce(values('domain'),constant(''))).by('classifier').by('evidenceLabel').by('weight').by(coalesce(values('evidenceRefs'),constant('[]'))).by('ts')";
+
+fn evidence_from(v: &Value) -> Result<EvidenceRecord> {
+    Ok(EvidenceRecord {
+        did: str_field(v, "did"),
+        facet: str_field(v, "facet"),
+        cid: str_field(v, "cid"),
+        uri: str_field(v, "uri"),
+        domain: str_field(v, "domain"),
+        classifier: str_field(v, "classifier"),
+        label: str_field(v, "label"),
+        weight: v["weight"].as_f64().unwrap_or(0.0),
+        evidence_refs: serde_json::from_str(v["evidenceRefs"].as_str().unwrap_or("[]"))?,
+        ts: v["ts"].as_i64().unwrap_or(0),
+    })
+}
+
+fn user_from(v: &Value) -> User {
+    User { did: str_field(v, "did"), handle: opt_str_field(v, "handle"), created_at: v["createdAt"].as_i64().unwrap_or(0) }
+}
+
+fn trust_from(v: &Value) -> TrustEdge {
+    TrustEdge {
+        from_did: str_field(v, "fromDid"),
+        to_did: str_field(v, "toDid"),
+        scope: str_field(v, "scope"),
+        opinion: Opinion::with_base_rate(
+            v["b"].as_f64().unwrap_or(0.0),
+            v["d"].as_f64().unwrap_or(0.0),
+            v["u"].as_f64().unwrap_or(1.0),
+            v["a"].as_f64().unwrap_or(DEFAULT_BASE_RATE),
+        ),
+        evidence_ref: opt_str_field(v, "evidenceRef"),
+        ts: v["ts"].as_i64().unwrap_or(0),
+    }
+}
+
+fn follow_from(v: &Value) -> FollowEdge {
+    FollowEdge { from_did: str_field(v, "fromDid"), to_did: str_field(v, "toDid"), ts: v["ts"].as_i64().unwrap_or(0) }
+}
+
+fn endorse_from(v: &Value) -> EndorseEdge {
+    EndorseEdge {
+        from_did: str_field(v, "fromDid"),
+        to_did: str_field(v, "toDid"),
+        domain: str_field(v, "domain"),
+        strength: v["strength"].as_f64().unwrap_or(0.0),
+        ts: v["ts"].as_i64().unwrap_or(0),
+    }
+}
+
+fn interact_from(v: &Value) -> InteractEdge {
+    InteractEdge {
+        from_did: str_field(v, "fromDid"),
+        to_did: str_field(v, "toDid"),
+        weight: v["weight"].as_f64().unwrap_or(0.0),
+        ts: v["ts"].as_i64().unwrap_or(0),
+    }
+}
+
+fn content_from(v: &Value) -> Content {
+    Content {
+        cid: str_field(v, "cid"),
+        uri: str_field(v, "uri"),
+        author_did: str_field(v, "authorDid"),
+        domain: opt_str_field(v, "domain"),
+        classification: opt_str_field(v, "classification"),
+        created_at: v["createdAt"].as_i64().unwrap_or(0),
+    }
+}
+
+/// Graph store backed by JanusGraph (or any Gremlin Server).
+pub struct GremlinGraphStore {
+    client: GremlinClient,
+}
+
+impl GremlinGraphStore {
+    pub fn new(client: GremlinClient) -> Self { Self { client } }
+
+    /// Configured from `GRAPH_HOST`, `GRAPH_POOL_SIZE` (default 8) and
+    /// `GRAPH_TIMEOUT_MS` (default 10000).
+    pub fn from_env() -> Self {
+        let host = std::env::var("GRAPH_HOST").unwrap_or_else(|_| "ws://localhost:8182/gremlin".to_string());
+        let pool = std::env::var("GRAPH_POOL_SIZE").ok().and_then(|v| v.parse().ok()).unwrap_or(8);
+        let timeout = std::env::var("GRAPH_TIMEOUT_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(10_000);
+        Self::new(GremlinClient::new(&host, pool, Duration::from_millis(timeout)))
+    }
+
+    /// Runs an edge upsert anchored at `src` → current vertex and fails if
+    /// either endpoint is missing.
+    async fn upsert_edge(&self, script: String, bindings: Value, what: &str) -> Result<()> {
+        let res = self.client.submit(&script, bindings).await?;
+        if res.is_empty() {
+            bail!("{what} not stored: unknown user");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GraphStore for GremlinGraphStore {
+    fn backend(&self) -> &'static str { "gremlin" }
+
+    async fn schema_versions(&self) -> Result<Vec<u32>> {
+        let res = self.client.submit("g.V().hasLabel('schemaMigration').values('version')", json!({})).await?;
+        let mut versions: Vec<u32> = res.iter().filter_map(Value::as_u64).map(|v| v as u32).collect();
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        if !migration.steps.is_empty() {
+            self.client.submit(&gremlin_script(migration.steps), json!({})).await?;
+        }
+        let script = "g.addV('schemaMigration').property('version',version).property('name',name).property('appliedAt',now)";
+        let bindings = json!({
+            "version": {"@type": "g:Int32", "@value": migration.version},
+            "name": migration.name,
+            "now": long(chrono::Utc::now().timestamp_millis()),
+        });
+        self.client.submit(script, bindings).await?;
+        Ok(())
+    }
+
+    async fn upsert_user(&self, did: &str, handle: Option<&str>) -> Result<User> {
+        let mut script = String::from(
+            "g.V().has('user','did',did).fold().coalesce(unfold(), addV('user').property('did',did).property('createdAt',now))",
+        );
+        if handle.is_some() {
+            script.push_str(".property('handle',handle)");
+        }
+        script.push_str(USER_PROJECTION);
+        let bindings = json!({"did": did, "handle": handle.unwrap_or_default(), "now": long(chrono::Utc::now().timestamp_millis())});
+        let res = self.client.submit(&script, bindings).await?;
+        res.first().map(user_from).ok_or_else(|| anyhow!("user upsert returned nothing"))
+    }
+
+    async fn get_user(&self, did: &str) -> Result<Option<User>> {
+        let script = format!("g.V().has('user','did',did){USER_PROJECTION}");
+        Ok(self.client.submit(&script, json!({"did": did})).await?.first().map(user_from))
+    }
+
+    async fn upsert_trust_edge(&self, edge: TrustEdge) -> Result<()> {
+        let mut script = String::from(
+            "g.V().has('user','did',fromDid).as('src').V().has('user','did',toDid)\
+             .coalesce(__.inE('trusts').has('scope',scope).where(outV().as('src')), __.addE('trusts').from('src').property('scope',scope))\
+             .property('b',b).property('d',d).property('u',u).property('a',a).property('ts',ts)",
+        );
+        if edge.evidence_ref.is_some() {
+            script.push_str(".property('evidenceRef',evidenceRef)");
+        }
+        let bindings = json!({
+            "fromDid": edge.from_did, "toDid": edge.to_did, "scope": edge.scope,
+            "b": float(edge.opinion.b), "d": float(edge.opinion.d), "u": float(edge.opinion.u), "a": float(edge.opinion.a),
+            "ts": long(edge.ts), "evidenceRef": edge.evidence_ref.unwrap_or_default(),
+        });
+        self.upsert_edge(script, bindings, "trust edge").await
+    }
+
+    async fn trust_edges(&self, query: &TrustEdgeQuery) -> Result<Vec<TrustEdge>> {
+        let mut script = match (&query.from_did, &query.to_did) {
+            (Some(_), _) => String::from("g.V().has('user','did',fromDid).outE('trusts')"),
+            (None, Some(_)) => String::from("g.V().has('user','did',toDid).inE('trusts')"),
+            (None, None) => String::from("g.E().hasLabel('trusts')"),
+        };
+        if query.scope.is_some() {
+            script.push_str(".has('scope',scope)");
+        }
+        if query.from_did.is_some() && query.to_did.is_some() {
+            script.push_str(".where(inV().has('did',toDid))");
+        }
+        script.push_str(TRUST_PROJECTION);
+        let bindings = json!({
+            "fromDid": query.from_did.clone().unwrap_or_default(),
+            "toDid": query.to_did.clone().unwrap_or_default(),
+            "scope": query.scope.clone().unwrap_or_default(),
+        });
+        Ok(self.client.submit(&script, bindings).await?.iter().map(trust_from).collect())
+    }
+
+    async fn upsert_follow(&self, from_did: &str, to_did: &str, ts: i64) -> Result<()> {
+        let script = "g.V().has('user','did',fromDid).as('src').V().has('user','did',toDid)\
+             .coalesce(__.inE('follows').where(outV().as('src')), __.addE('follows').from('src'))\
+             .property('ts',ts)";
+        let bindings = json!({"fromDid": from_did, "toDid": to_did, "ts": long(ts)});
+        self.upsert_edge(script.to_string(), bindings, "follow").await
+    }
+
+    async fn follows_from(&self, did: &str) -> Result<Vec<FollowEdge>> {
+        let script = format!("g.V().has('user','did',did).outE('follows'){FOLLOW_PROJECTION}");
+        Ok(self.client.submit(&script, json!({"did": did})).await?.iter().map(follow_from).collect())
+    }
+
+    async fn followers_of(&self, did: &str) -> Result<Vec<FollowEdge>> {
+        let script = format!("g.V().has('user','did',did).inE('follows'){FOLLOW_PROJECTION}");
+        Ok(self.client.submit(&script, json!({"did": did})).await?.iter().map(follow_from).collect())
+    }
+
+    async fn upsert_endorsement(&self, edge: EndorseEdge) -> Result<()> {
+        let script = "g.V().has('user','did',fromDid).as('src').V().has('user','did',toDid)\
+             .coalesce(__.inE('endorses').has('domain',domain).where(outV().as('src')), __.addE('endorses').from('src').property('domain',domain))\
+             .property('strength',strength).property('ts',ts)";
+        let bindings = json!({
+            "fromDid": edge.from_did, "toDid": edge.to_did, "domain": edge.domain,
+            "strength": float(edge.strength), "ts": long(edge.ts),
+        });
+        self.upsert_edge(script.to_string(), bindings, "endorsement").await
+    }
+
+    async fn endorsements_of(&self, did: &str) -> Result<Vec<EndorseEdge>> {
+        let script = format!("g.V().has('user','did',did).inE('endorses'){ENDORSE_PROJECTION}");
+        Ok(self.client.submit(&script, json!({"did": did})).await?.iter().map(endorse_from).collect())
+    }
+
+    async fn upsert_interaction(&self, edge: InteractEdge) -> Result<()> {
+        let script = "g.V().has('user','did',fromDid).as('src').V().has('user','did',toDid)\
+             .coalesce(__.inE('interacts').where(outV().as('src')), __.addE('interacts').from('src'))\
+             .property('weight',weight).property('ts',ts)";
+        let bindings = json!({"fromDid": edge.from_did, "toDid": edge.to_did, "weight": float(edge.weight), "ts": long(edge.ts)});
+        self.upsert_edge(script.to_string(), bindings, "interaction").await
+    }
+
+    async fn interactions_from(&self, did: &str) -> Result<Vec<InteractEdge>> {
+        let script = format!("g.V().has('user','did',did).outE('interacts'){INTERACT_PROJECTION}");
+        Ok(self.client.submit(&script, json!({"did": did})).await?.iter().map(interact_from).collect())
+    }
+
+    async fn upsert_content(&self, content: Content) -> Result<()> {
+        let mut script = String::from(
+            "g.V().has('content','cid',cid).fold().coalesce(unfold(), addV('content').property('cid',cid))\
+             .property('uri',uri).property('authorDid',authorDid).property('createdAt',createdAt)",
+        );
+        if content.domain.is_some() {
+            script.push_str(".property('domain',domain)");
+        }
+        if content.classification.is_some() {
+            script.push_str(".property('classification',classification)");
+        }
+        let bindings = json!({
+            "cid": content.cid, "uri": content.uri, "authorDid": content.author_did,
+            "createdAt": long(content.created_at),
+            "domain": content.domain.unwrap_or_default(),
+            "classification": content.classification.unwrap_or_default(),
+        });
+        self.client.submit(&script, bindings).await?;
+        Ok(())
+    }
+
+    async fn content_by_author(&self, did: &str) -> Result<Vec<Content>> {
+        let script = format!("g.V().has('content','authorDid',did){CONTENT_PROJECTION}");
+        Ok(self.client.submit(&script, json!({"did": did})).await?.iter().map(content_from).collect())
+    }
+
+    async fn upsert_scores(&self, scores: UserScores) -> Result<()> {
+        let script = "g.V().has('user','did',did).fold().coalesce(unfold(), addV('user').property('did',did).property('createdAt',now))\
+             .property('scores',scores)";
+        let bindings = json!({
+            "did": scores.did, "now": long(chrono::Utc::now().timestamp_millis()),
+            "scores": serde_json::to_string(&scores)?,
+        });
+        self.client.submit(script, bindings).await?;
+        Ok(())
+    }
+
+    async fn get_scores(&self, did: &str) -> Result<Option<UserScores>> {
+        let res = self.client.submit("g.V().has('user','did',did).values('scores')", json!({"did": did})).await?;
+        match res.first().and_then(Value::as_str) {
+            Some(s) => Ok(Some(serde_json::from_str(s)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn append_score_sample(&self, did: &str, facet: &str, sample: ScoreSample) -> Result<()> {
+        let script = "g.V().has('user','did',did).as('u')\
+             .addV('scoreSample').property('facet',facet).property('ts',ts).property('sample',sample)\
+             .addE('scoreHistory').from('u')";
+        let bindings = json!({"did": did, "facet": facet, "ts": long(sample.ts), "sample": serde_json::to_string(&sample)?});
+        self.upsert_edge(script.to_string(), bindings, "score sample").await
+    }
+
+    async fn score_history(&self, did: &str, facet: &str, from: i64, to: i64) -> Result<Vec<ScoreSample>> {
+        let script = "g.V().has('user','did',did).out('scoreHistory').has('facet',facet)\
+             .has('ts',gte(from)).has('ts',lte(to)).order().by('ts').values('sample')";
+        let bindings = json!({"did": did, "facet": facet, "from": long(from), "to": long(to)});
+        let res = self.client.submit(script, bindings).await?;
+        res.iter()
+            .filter_map(Value::as_str)
+            .map(|s| Ok(serde_json::from_str(s)?))
+            .collect()
+    }
+
+    async fn upsert_evidence(&self, record: EvidenceRecord) -> Result<()> {
+        let script = "g.V().has('user','did',did).as('u')\
+             .V().has('content','cid',cid).fold().coalesce(unfold(), addV('content').property('cid',cid).property('authorDid',did).property('createdAt',ts))\
+             .property('uri',uri).as('c')\
+             .coalesce(__.inE('evidence').has('facet',facet).where(outV().as('u')), __.addE('evidence').from('u').property('facet',facet))\
+             .property('domain',domain).property('classifier',classifier).property('evidenceLabel',label)\
+             .property('weight',weight).property('evidenceRefs',evidenceRefs).property('ts',ts)";
+        let bindings = json!({
+            "did": record.did, "facet": record.facet, "cid": record.cid, "uri": record.uri, "domain": record.domain,
+            "classifier": record.classifier, "label": record.label, "weight": float(record.weight),
+            "evidenceRefs": serde_json::to_string(&record.evidence_refs)?, "ts": long(record.ts),
+        });
+        self.upsert_edge(script.to_string(), bindings, "evidence").await
+    }
+
+    async fn evidence_for(&self, did: &str, facet: Option<&str>) -> Result<Vec<EvidenceRecord>> {
+        let mut script = String::from("g.V().has('user','did',did).outE('evidence')");
+        if facet.is_some() {
+            script.push_str(".has('facet',facet)");
+        }
+        script.push_str(".order().by('ts',desc)");
+        script.push_str(EVIDENCE_PROJECTION);
+        let bindings = json!({"did": did, "facet": facet.unwrap_or_default()});
+        self.client.submit(&script, bindings).await?.iter().map(evidence_from).collect()
+    }
+
+    async fn prune_score_history(&self, did: &str, before: i64) -> Result<()> {
+        let script = "g.V().has('user','did',did).out('scoreHistory').has('ts',lt(before)).drop()";
+        self.client.submit(script, json!({"did": did, "before": long(before)})).await?;
+        Ok(())
+    }
+
+    async fn upsert_dispute(&self, dispute: Dispute) -> Result<()> {
+        let script = "g.V().has('dispute','disputeId',id).fold()\
+             .coalesce(unfold(), addV('dispute').property('disputeId',id).property('subjectDid',did).property('createdAt',createdAt))\
+             .property('disputeStatus',status).property('dispute',doc)";
+        let bindings = json!({
+            "id": dispute.id, "did": dispute.did, "createdAt": long(dispute.created_at),
+            "status": dispute.status.as_str(), "doc": serde_json::to_string(&dispute)?,
+        });
+        self.client.submit(script, bindings).await?;
+        Ok(())
+    }
+
+    async fn get_dispute(&self, id: &str) -> Result<Option<Dispute>> {
+        let res = self.client.submit("g.V().has('dispute','disputeId',id).values('dispute')", json!({"id": id})).await?;
+        match res.first().and_then(Value::as_str) {
+            Some(s) => Ok(Some(serde_json::from_str(s)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn disputes(&self, did: Option<&str>, status: Option<DisputeStatus>) -> Result<Vec<Dispute>> {
+        let mut script = String::from("g.V().hasLabel('dispute')");
+        if did.is_some() {
+            script.push_str(".has('subjectDid',did)");
+        }
+        if status.is_some() {
+            script.push_str(".has('disputeStatus',status)");
+        }
+        script.push_str(".order().by('createdAt').values('dispute')");
+        let bindings = json!({"did": did.unwrap_or_default(), "status": status.map(DisputeStatus::as_str).unwrap_or_default()});
+        let res = self.client.submit(&script, bindings).await?;
+        res.iter()
+            .filter_map(Value::as_str)
+            .map(|s| Ok(serde_json::from_str(s)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_graphson_is_unwrapped() {
+        let raw = json!({
+            "@type": "g:List",
+            "@value": [{
+                "@type": "g:Map",
+                "@value": ["did", "did:a", "createdAt", {"@type": "g:Int64", "@value": 42}, "b", {"@type": "g:Float", "@value": 0.5}]
+            }]
+        });
+        assert_eq!(from_graphson(raw), json!([{"did": "did:a", "createdAt": 42, "b": 0.5}]));
+        let bulk = json!({"@type": "g:BulkSet", "@value": ["x", {"@type": "g:Int64", "@value": 2}]});
+        assert_eq!(from_graphson(bulk), json!(["x", "x"]));
+    }
+
+    #[test]
+    fn t_graph_host_forms() {
+        assert_eq!(normalize_url("http://janusgraph:8182"), "ws://janusgraph:8182/gremlin");
+        assert_eq!(normalize_url("janusgraph:8182/"), "ws://janusgraph:8182/gremlin");
+        assert_eq!(normalize_url("wss://g.example.com/gremlin"), "wss://g.example.com/gremlin");
+    }
+
+    fn test_store() -> Option<GremlinGraphStore> {
+        let url = std::env::var("GREMLIN_TEST_URL").ok()?;
+        Some(GremlinGraphStore::new(GremlinClient::new(&url, 2, Duration::from_secs(10))))
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Gremlin Server; set GREMLIN_TEST_URL"]
+    async fn t_gremlin_round_trip() {
+        let Some(store) = test_store() else { return };
+        let run = Uuid::new_v4().simple().to_string();
+        let (a, b) = (format!("did:test:{run}:a"), format!("did:test:{run}:b"));
+
+        let user = store.upsert_user(&a, Some("a.test")).await.unwrap();
+        assert_eq!(user.handle.as_deref(), Some("a.test"));
+        store.upsert_user(&b, None).await.unwrap();
+        assert_eq!(store.upsert_user(&a, None).await.unwrap().created_at, user.created_at);
+
+        let edge = TrustEdge {
+            from_did: a.clone(),
+            to_did: b.clone(),
+            scope: "general".into(),
+            opinion: Opinion::new(0.5, 0.25, 0.25),
+            evidence_ref: Some("at://post".into()),
+            ts: 7,
+        };
+        store.upsert_trust_edge(edge.clone()).await.unwrap();
+        store.upsert_trust_edge(TrustEdge { opinion: Opinion::new(0.75, 0.0, 0.25), ..edge.clone() }).await.unwrap();
+        let q = TrustEdgeQuery { from_did: Some(a.clone()), ..Default::default() };
+        let edges = store.trust_edges(&q).await.unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].opinion.b, 0.75);
+        assert_eq!(edges[0].evidence_ref.as_deref(), Some("at://post"));
+        let q = TrustEdgeQuery { to_did: Some(b.clone()), scope: Some("general".into()), ..Default::default() };
+        assert_eq!(store.trust_edges(&q).await.unwrap().len(), 1);
+
+        store.upsert_follow(&a, &b, 1).await.unwrap();
+        store.upsert_follow(&a, &b, 2).await.unwrap();
+        assert_eq!(store.followers_of(&b).await.unwrap(), vec![FollowEdge { from_did: a.clone(), to_did: b.clone(), ts: 2 }]);
+
+        let endorse = EndorseEdge { from_did: a.clone(), to_did: b.clone(), domain: "medicine".into(), strength: 0.5, ts: 3 };
+        store.upsert_endorsement(endorse.clone()).await.unwrap();
+        assert_eq!(store.endorsements_of(&b).await.unwrap(), vec![endorse]);
+        let interact = InteractEdge { from_did: a.clone(), to_did: b.clone(), weight: 2.0, ts: 4 };
+        store.upsert_interaction(interact.clone()).await.unwrap();
+        assert_eq!(store.interactions_from(&a).await.unwrap(), vec![interact]);
+
+        let post = Content {
+            cid: format!("cid-{run}"),
+            uri: "at://post".into(),
+            author_did: a.clone(),
+            domain: Some("politics".into()),
+            classification: None,
+            created_at: 5,
+        };
+        store.upsert_content(post.clone()).await.unwrap();
+        assert_eq!(store.content_by_author(&a).await.unwrap(), vec![post]);
+
+        store.upsert_scores(UserScores { bot_prob: 0.5, ..UserScores::vacuous(&a) }).await.unwrap();
+        assert_eq!(store.get_scores(&a).await.unwrap().unwrap().bot_prob, 0.5);
+        assert!(store.upsert_follow(&a, "did:test:missing", 1).await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Gremlin Server; set GREMLIN_TEST_URL"]
+    async fn t_gremlin_migrations_are_recorded() {
+        let Some(store) = test_store() else { return };
+        crate::services::graph::migrations::migrate(&store).await.unwrap();
+        let versions = store.schema_versions().await.unwrap();
+        assert!(crate::services::graph::migrations::MIGRATIONS.iter().all(|m| versions.contains(&m.version)));
+        assert!(crate::services::graph::migrations::migrate(&store).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+//! Per-DID, per-facet score history. Every score write appends one sample
+//! per facet; samples older than the retention window are pruned on write
+//! and long ranges are downsampled on read.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use trustsystem_core::OpinionRecord;
+
+/// One facet opinion as written at `ts` (epoch millis).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreSample {
+    pub ts: i64,
+    #[serde(flatten)]
+    pub record: OpinionRecord,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryConfig {
+    /// Samples older than this are dropped; `None` keeps everything.
+    pub retention_ms: Option<i64>,
+    /// Reads returning more samples than this are downsampled.
+    pub max_points: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { retention_ms: Some(365 * DAY_MS), max_points: 500 }
+    }
+}
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+impl HistoryConfig {
+    /// Reads `SCORE_HISTORY_RETENTION_DAYS` (0 keeps history forever) and
+    /// `SCORE_HISTORY_MAX_POINTS`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let retention_ms = match std::env::var("SCORE_HISTORY_RETENTION_DAYS").ok().and_then(|v| v.parse::<i64>().ok()) {
+            Some(0) => None,
+            Some(days) => Some(days * DAY_MS),
+            None => defaults.retention_ms,
+        };
+        let max_points = std::env::var("SCORE_HISTORY_MAX_POINTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(defaults.max_points);
+        Self { retention_ms, max_points }
+    }
+}
+
+pub static HISTORY_CONFIG: Lazy<HistoryConfig> = Lazy::new(HistoryConfig::from_env);
+
+/// Keeps the latest sample in each `bucket_ms` window. Without an explicit
+/// bucket, picks the smallest one that brings `samples` (sorted by `ts`)
+/// under `max_points`. Returns the samples and the bucket used.
+pub fn downsample(samples: Vec<ScoreSample>, bucket_ms: Option<i64>, max_points: usize) -> (Vec<ScoreSample>, Option<i64>) {
+    let bucket = match bucket_ms {
+        Some(b) if b > 0 => b,
+        _ if samples.len() <= max_points => return (samples, None),
+        _ => {
+            let span = samples.last().map_or(0, |l| l.ts) - samples.first().map_or(0, |f| f.ts);
+            (span / max_points.max(1) as i64 + 1).max(1)
+        }
+    };
+    let mut out: Vec<ScoreSample> = Vec::new();
+    for s in samples {
+        match out.last_mut() {
+            Some(last) if last.ts.div_euclid(bucket) == s.ts.div_euclid(bucket) => *last = s,
+            _ => out.push(s),
+        }
+    }
+    if out.len() > max_points {
+        out.drain(..out.len() - max_points);
+    }
+    (out, Some(bucket))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ts: i64, alpha: f64) -> ScoreSample {
+        ScoreSample { ts, record: OpinionRecord::from_evidence(alpha, 0.0, 2.0) }
+    }
+
+    #[test]
+    fn t_keeps_last_sample_per_bucket() {
+        let samples = vec![sample(0, 1.0), sample(5, 2.0), sample(10, 3.0), sample(25, 4.0)];
+        let (out, bucket) = downsample(samples.clone(), Some(10), 100);
+        assert_eq!(bucket, Some(10));
+        assert_eq!(out.iter().map(|s| s.ts).collect::<Vec<_>>(), vec![5, 10, 25]);
+
+        let (out, bucket) = downsample(samples.clone(), None, 100);
+        assert_eq!((out.len(), bucket), (4, None));
+
+        let (out, bucket) = downsample(samples, None, 2);
+        assert!(out.len() <= 2);
+        assert_eq!(out.last().unwrap().ts, 25);
+        assert!(bucket.is_some());
+    }
+
+    #[test]
+    fn t_sample_wire_format_is_flat() {
+        let v = serde_json::to_value(sample(7, 3.0)).unwrap();
+        assert_eq!(v["ts"], 7);
+        assert_eq!(v["alpha"], 3.0);
+        assert!(v["b"].is_number());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::RwLock;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use trustsystem_core::{EvidenceRecord, UserScores};
+
+use crate::services::disputes::{Dispute, DisputeStatus};
+
+use super::{Content, Migration, ScoreSample, EndorseEdge, FollowEdge, GraphStore, InteractEdge, TrustEdge, TrustEdgeQuery, User};
+
+type EdgeKey = (String, String, String); // (from, to, scope)
+
+/// In-process graph store. Trust edges are indexed by source DID, target
+/// DID and scope; everything is lost on restart.
+#[derive(Default)]
+pub struct MemoryGraphStore {
+    inner: RwLock<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    users: HashMap<String, User>,
+    trusts: HashMap<EdgeKey, TrustEdge>,
+    trusts_by_from: HashMap<String, BTreeSet<EdgeKey>>,
+    trusts_by_to: HashMap<String, BTreeSet<EdgeKey>>,
+    trusts_by_scope: HashMap<String, BTreeSet<EdgeKey>>,
+    follows: HashMap<(String, String), FollowEdge>,
+    endorsements: HashMap<EdgeKey, EndorseEdge>, // (from, to, domain)
+    interactions: HashMap<(String, String), InteractEdge>,
+    content: HashMap<String, Content>,
+    content_by_author: HashMap<String, BTreeSet<String>>,
+    scores: HashMap<String, UserScores>,
+    schema_versions: BTreeSet<u32>,
+    history: HashMap<(String, String), Vec<ScoreSample>>, // (did, facet), sorted by ts
+    evidence: HashMap<String, HashMap<(String, String), EvidenceRecord>>, // did -> (facet, cid)
+    disputes: HashMap<String, Dispute>,
+}
+
+impl MemoryGraphStore {
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Inner>> {
+        self.inner.read().map_err(|_| anyhow!("graph store lock poisoned"))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Inner>> {
+        self.inner.write().map_err(|_| anyhow!("graph store lock poisoned"))
+    }
+}
+
+#[async_trait]
+impl GraphStore for MemoryGraphStore {
+    fn backend(&self) -> &'static str { "memory" }
+
+    async fn schema_versions(&self) -> Result<Vec<u32>> {
+        Ok(self.read()?.schema_versions.iter().copied().collect())
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        self.write()?.schema_versions.insert(migration.version);
+        Ok(())
+    }
+
+    async fn upsert_user(&self, did: &str, handle: Option<&str>) -> Result<User> {
+        let mut inner = self.write()?;
+        let user = inner.users.entry(did.to_string()).or_insert_with(|| User {
+            did: did.to_string(),
+            handle: None,
+            created_at: chrono::Utc::now().timestamp_millis(),
+        });
+        if let Some(h) = handle {
+            user.handle = Some(h.to_string());
+        }
+        Ok(user.clone())
+    }
+
+    async fn get_user(&self, did: &str) -> Result<Option<User>> {
+        Ok(self.read()?.users.get(did).cloned())
+    }
+
+    async fn upsert_trust_edge(&self, edge: TrustEdge) -> Result<()> {
+        let mut inner = self.write()?;
+        let key = (edge.from_did.clone(), edge.to_did.clone(), edge.scope.clone());
+        inner.trusts_by_from.entry(edge.from_did.clone()).or_default().insert(key.clone());
+        inner.trusts_by_to.entry(edge.to_did.clone()).or_default().insert(key.clone());
+        inner.trusts_by_scope.entry(edge.scope.clone()).or_default().insert(key.clone());
+        inner.trusts.insert(key, edge);
+        Ok(())
+    }
+
+    async fn trust_edges(&self, query: &TrustEdgeQuery) -> Result<Vec<TrustEdge>> {
+        let inner = self.read()?;
+        // start from the narrowest index available
+        let keys: Vec<&EdgeKey> = match (&query.from_did, &query.to_did, &query.scope) {
+            (Some(from), _, _) => inner.trusts_by_from.get(from).into_iter().flatten().collect(),
+            (None, Some(to), _) => inner.trusts_by_to.get(to).into_iter().flatten().collect(),
+            (None, None, Some(scope)) => inner.trusts_by_scope.get(scope).into_iter().flatten().collect(),
+            (None, None, None) => {
+                let mut all: Vec<&EdgeKey> = inner.trusts.keys().collect();
+                all.sort();
+                all
+            }
+        };
+        Ok(keys
+            .into_iter()
+            .filter(|(from, to, scope)| {
+                query.from_did.as_ref().is_none_or(|f| f == from)
+                    && query.to_did.as_ref().is_none_or(|t| t == to)
+                    && query.scope.as_ref().is_none_or(|s| s == scope)
+            })
+            .filter_map(|k| inner.trusts.get(k).cloned())
+            .collect())
+    }
+
+    async fn upsert_follow(&self, from_did: &str, to_did: &str, ts: i64) -> Result<()> {
+        let edge = FollowEdge { from_did: from_did.to_string(), to_did: to_did.to_string(), ts };
+        self.write()?.follows.insert((from_did.to_string(), to_did.to_string()), edge);
+        Ok(())
+    }
+
+    async fn follows_from(&self, did: &str) -> Result<Vec<FollowEdge>> {
+        let mut out: Vec<FollowEdge> = self.read()?.follows.values().filter(|f| f.from_did == did).cloned().collect();
+        out.sort_by(|a, b| a.to_did.cmp(&b.to_did));
+        Ok(out)
+    }
+
+    async fn followers_of(&self, did: &str) -> Result<Vec<FollowEdge>> {
+        let mut out: Vec<FollowEdge> = self.read()?.follows.values().filter(|f| f.to_did == did).cloned().collect();
+        out.sort_by(|a, b| a.from_did.cmp(&b.from_did));
+        Ok(out)
+    }
+
+    async fn upsert_endorsement(&self, edge: EndorseEdge) -> Result<()> {
+        let key = (edge.from_did.clone(), edge.to_did.clone(), edge.domain.clone());
+        self.write()?.endorsements.insert(key, edge);
+        Ok(())
+    }
+
+    async fn endorsements_of(&self, did: &str) -> Result<Vec<EndorseEdge>> {
+        let mut out: Vec<EndorseEdge> = self.read()?.endorsements.values().filter(|e| e.to_did == did).cloned().collect();
+        out.sort_by(|a, b| (&a.from_did, &a.domain).cmp(&(&b.from_did, &b.domain)));
+        Ok(out)
+    }
+
+    async fn upsert_interaction(&self, edge: InteractEdge) -> Result<()> {
+        self.write()?.interactions.insert((edge.from_did.clone(), edge.to_did.clone()), edge);
+        Ok(())
+    }
+
+    async fn interactions_from(&self, did: &str) -> Result<Vec<InteractEdge>> {
+        let mut out: Vec<InteractEdge> = self.read()?.interactions.values().filter(|e| e.from_did == did).cloned().collect();
+        out.sort_by(|a, b| a.to_did.cmp(&b.to_did));
+        Ok(out)
+    }
+
+    async fn upsert_content(&self, content: Content) -> Result<()> {
+        let mut inner = self.write()?;
+        inner.content_by_author.entry(content.author_did.clone()).or_default().insert(content.cid.clone());
+        inner.content.insert(content.cid.clone(), content);
+        Ok(())
+    }
+
+    async fn content_by_author(&self, did: &str) -> Result<Vec<Content>> {
+        let inner = self.read()?;
+        Ok(inner
+            .content_by_author
+            .get(did)
+            .into_iter()
+            .flatten()
+            .filter_map(|cid| inner.content.get(cid).cloned())
+            .collect())
+    }
+
+    async fn upsert_scores(&self, scores: UserScores) -> Result<()> {
+        self.write()?.scores.insert(scores.did.clone(), scores);
+        Ok(())
+    }
+
+    async fn get_scores(&self, did: &str) -> Result<Option<UserScores>> {
+        Ok(self.read()?.scores.get(did).cloned())
+    }
+
+    async fn append_score_sample(&self, did: &str, facet: &str, sample: ScoreSample) -> Result<()> {
+        let mut inner = self.write()?;
+        let samples = inner.history.entry((did.to_string(), facet.to_string())).or_default();
+        let at = samples.partition_point(|s| s.ts <= sample.ts);
+        samples.insert(at, sample);
+        Ok(())
+    }
+
+    async fn score_history(&self, did: &str, facet: &str, from: i64, to: i64) -> Result<Vec<ScoreSample>> {
+        let inner = self.read()?;
+        let Some(samples) = inner.history.get(&(did.to_string(), facet.to_string())) else { return Ok(Vec::new()) };
+        Ok(samples.iter().filter(|s| (from..=to).contains(&s.ts)).copied().collect())
+    }
+
+    async fn upsert_evidence(&self, record: EvidenceRecord) -> Result<()> {
+        let key = (record.facet.clone(), record.cid.clone());
+        self.write()?.evidence.entry(record.did.clone()).or_default().insert(key, record);
+        Ok(())
+    }
+
+    async fn evidence_for(&self, did: &str, facet: Option<&str>) -> Result<Vec<EvidenceRecord>> {
+        let inner = self.read()?;
+        let mut out: Vec<EvidenceRecord> = inner
+            .evidence
+            .get(did)
+            .into_iter()
+            .flat_map(|m| m.values())
+            .filter(|r| facet.is_none_or(|f| f == r.facet))
+            .cloned()
+            .collect();
+        out.sort_by(|a, b| b.ts.cmp(&a.ts).then_with(|| a.cid.cmp(&b.cid)));
+        Ok(out)
+    }
+
+    async fn upsert_dispute(&self, dispute: Dispute) -> Result<()> {
+        self.write()?.disputes.insert(dispute.id.clone(), dispute);
+        Ok(())
+    }
+
+    async fn get_dispute(&self, id: &str) -> Result<Option<Dispute>> {
+        Ok(self.read()?.disputes.get(id).cloned())
+    }
+
+    async fn disputes(&self, did: Option<&str>, status: Option<DisputeStatus>) -> Result<Vec<Dispute>> {
+        let inner = self.read()?;
+        let mut out: Vec<Dispute> = inner
+            .disputes
+            .values()
+            .filter(|d| did.is_none_or(|did| did == d.did) && status.is_none_or(|s| s == d.status))
+            .cloned()
+            .collect();
+        out.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(out)
+    }
+
+    async fn prune_score_history(&self, did: &str, before: i64) -> Result<()> {
+        let mut inner = self.write()?;
+        for ((d, _), samples) in inner.history.iter_mut() {
+            if d == did {
+                samples.retain(|s| s.ts >= before);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trustsystem_core::Opinion;
+
+    fn edge(from: &str, to: &str, scope: &str, b: f64) -> TrustEdge {
+        TrustEdge {
+            from_did: from.into(),
+            to_did: to.into(),
+            scope: scope.into(),
+            opinion: Opinion::new(b, 0.0, 1.0 - b),
+            evidence_ref: None,
+            ts: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn t_trust_edges_are_indexed_and_replaced() {
+        let store = MemoryGraphStore::default();
+        store.upsert_trust_edge(edge("did:a", "did:b", "general", 0.5)).await.unwrap();
+        store.upsert_trust_edge(edge("did:a", "did:c", "medicine", 0.6)).await.unwrap();
+        store.upsert_trust_edge(edge("did:c", "did:b", "general", 0.7)).await.unwrap();
+        store.upsert_trust_edge(edge("did:a", "did:b", "general", 0.9)).await.unwrap();
+
+        let from_a = store.trust_edges(&TrustEdgeQuery { from_did: Some("did:a".into()), ..Default::default() }).await.unwrap();
+        assert_eq!(from_a.len(), 2);
+        assert_eq!(from_a[0].opinion.b, 0.9);
+
+        let to_b = store.trust_edges(&TrustEdgeQuery { to_did: Some("did:b".into()), ..Default::default() }).await.unwrap();
+        assert_eq!(to_b.len(), 2);
+
+        let q = TrustEdgeQuery { from_did: Some("did:a".into()), scope: Some("medicine".into()), ..Default::default() };
+        let scoped = store.trust_edges(&q).await.unwrap();
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].to_did, "did:c");
+
+        assert_eq!(store.trust_edges(&TrustEdgeQuery::default()).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn t_users_content_and_follows() {
+        let store = MemoryGraphStore::default();
+        let u = store.upsert_user("did:a", None).await.unwrap();
+        assert_eq!(u.handle, None);
+        let u2 = store.upsert_user("did:a", Some("a.bsky.social")).await.unwrap();
+        assert_eq!(u2.handle.as_deref(), Some("a.bsky.social"));
+        assert_eq!(u2.created_at, u.created_at);
+
+        store.upsert_follow("did:a", "did:b", 1).await.unwrap();
+        store.upsert_follow("did:a", "did:b", 2).await.unwrap();
+        assert_eq!(store.follows_from("did:a").await.unwrap().len(), 1);
+        assert_eq!(store.followers_of("did:b").await.unwrap()[0].ts, 2);
+
+        let post = Content {
+            cid: "bafy1".into(),
+            uri: "at://did:a/app.bsky.feed.post/1".into(),
+            author_did: "did:a".into(),
+            domain: Some("politics".into()),
+            classification: Some("accurate".into()),
+            created_at: 0,
+        };
+        store.upsert_content(post.clone()).await.unwrap();
+        assert_eq!(store.content_by_author("did:a").await.unwrap(), vec![post]);
+    }
+}
@@ -0,0 +1,398 @@
+//! Versioned graph schema migrations.
+//!
+//! Every backend records the versions it has applied and only runs the
+//! pending ones, in order. A migration carries both a backend-neutral list
+//! of [`SchemaStep`]s (rendered to JanusGraph management calls, each
+//! guarded by a `contains*` check so re-running is harmless) and the DDL
+//! the SQLite store needs for the same change. Append new migrations to
+//! [`MIGRATIONS`]; never edit one that has shipped.
+
+use anyhow::Result;
+
+use super::GraphStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    String,
+    Long,
+    Integer,
+    Float,
+}
+
+impl DataType {
+    fn groovy(self) -> &'static str {
+        match self {
+            DataType::String => "String.class",
+            DataType::Long => "Long.class",
+            DataType::Integer => "Integer.class",
+            DataType::Float => "Float.class",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaStep {
+    PropertyKey { name: &'static str, data_type: DataType, single: bool },
+    VertexLabel(&'static str),
+    EdgeLabel(&'static str),
+    /// Exact-match vertex index.
+    CompositeIndex { name: &'static str, keys: &'static [&'static str], unique: bool },
+    /// Full-text/range vertex index on the `search` index backend.
+    MixedIndex { name: &'static str, keys: &'static [&'static str] },
+    /// Vertex-centric index ordering `label` edges by `key`, newest first.
+    EdgeIndex { name: &'static str, label: &'static str, key: &'static str },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub steps: &'static [SchemaStep],
+    /// DDL for the SQLite store; empty when the change has no relational
+    /// counterpart.
+    pub sql: &'static str,
+}
+
+use DataType::{Float, Integer, Long, String as Str};
+use SchemaStep::*;
+
+const fn key(name: &'static str, data_type: DataType) -> SchemaStep {
+    PropertyKey { name, data_type, single: false }
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "graph",
+        steps: &[
+            PropertyKey { name: "did", data_type: Str, single: true },
+            key("handle", Str),
+            key("createdAt", Long),
+            key("alpha", Long),
+            key("beta", Long),
+            key("b", Float),
+            key("d", Float),
+            key("u", Float),
+            key("scope", Str),
+            key("domain", Str),
+            key("offenseScore", Float),
+            key("botProb", Float),
+            key("strength", Float),
+            key("weight", Float),
+            key("ts", Long),
+            key("evidenceRef", Str),
+            PropertyKey { name: "cid", data_type: Str, single: true },
+            key("authorDid", Str),
+            key("classification", Str),
+            key("exp_map", Str),
+            VertexLabel("user"),
+            VertexLabel("content"),
+            VertexLabel("community"),
+            EdgeLabel("follows"),
+            EdgeLabel("trusts"),
+            EdgeLabel("endorses"),
+            EdgeLabel("interacts"),
+            CompositeIndex { name: "userByDid", keys: &["did"], unique: true },
+            CompositeIndex { name: "contentByAuthor", keys: &["authorDid"], unique: false },
+            CompositeIndex { name: "contentByCid", keys: &["cid"], unique: true },
+            MixedIndex { name: "userSearch", keys: &["handle"] },
+            MixedIndex { name: "contentSearch", keys: &["domain", "classification"] },
+            // bookkeeping for this module
+            VertexLabel("schemaMigration"),
+            PropertyKey { name: "version", data_type: Integer, single: true },
+            key("name", Str),
+            key("appliedAt", Long),
+        ],
+        sql: "
+            CREATE TABLE users (
+                did TEXT PRIMARY KEY,
+                handle TEXT,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE trusts (
+                from_did TEXT NOT NULL,
+                to_did TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                b REAL NOT NULL, d REAL NOT NULL, u REAL NOT NULL, a REAL NOT NULL,
+                evidence_ref TEXT,
+                ts INTEGER NOT NULL,
+                PRIMARY KEY (from_did, to_did, scope)
+            );
+            CREATE INDEX trusts_by_to ON trusts (to_did);
+            CREATE INDEX trusts_by_scope ON trusts (scope);
+            CREATE TABLE follows (
+                from_did TEXT NOT NULL,
+                to_did TEXT NOT NULL,
+                ts INTEGER NOT NULL,
+                PRIMARY KEY (from_did, to_did)
+            );
+            CREATE INDEX follows_by_to ON follows (to_did);
+            CREATE TABLE endorsements (
+                from_did TEXT NOT NULL,
+                to_did TEXT NOT NULL,
+                domain TEXT NOT NULL,
+                strength REAL NOT NULL,
+                ts INTEGER NOT NULL,
+                PRIMARY KEY (from_did, to_did, domain)
+            );
+            CREATE INDEX endorsements_by_to ON endorsements (to_did);
+            CREATE TABLE interactions (
+                from_did TEXT NOT NULL,
+                to_did TEXT NOT NULL,
+                weight REAL NOT NULL,
+                ts INTEGER NOT NULL,
+                PRIMARY KEY (from_did, to_did)
+            );
+            CREATE TABLE content (
+                cid TEXT PRIMARY KEY,
+                uri TEXT NOT NULL,
+                author_did TEXT NOT NULL,
+                domain TEXT,
+                classification TEXT,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX content_by_author ON content (author_did);
+            CREATE TABLE scores (
+                did TEXT PRIMARY KEY,
+                doc TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 2,
+        name: "jobs",
+        steps: &[],
+        sql: "
+            CREATE TABLE jobs (
+                job_id TEXT PRIMARY KEY,
+                did TEXT NOT NULL,
+                status TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE INDEX jobs_by_status ON jobs (status, created_at);
+        ",
+    },
+    Migration {
+        version: 3,
+        name: "trust_ts_index",
+        steps: &[
+            // written by the Gremlin store since the base rate/URI/score
+            // document landed but never declared
+            key("a", Float),
+            key("uri", Str),
+            key("scores", Str),
+            EdgeIndex { name: "trustsByTs", label: "trusts", key: "ts" },
+        ],
+        sql: "CREATE INDEX trusts_by_ts ON trusts (ts);",
+    },
+    Migration {
+        version: 4,
+        name: "score_history",
+        steps: &[
+            // samples hang off the user vertex: user -scoreHistory-> scoreSample
+            VertexLabel("scoreSample"),
+            EdgeLabel("scoreHistory"),
+            key("facet", Str),
+            key("sample", Str),
+        ],
+        sql: "
+            CREATE TABLE score_history (
+                did TEXT NOT NULL,
+                facet TEXT NOT NULL,
+                ts INTEGER NOT NULL,
+                sample TEXT NOT NULL
+            );
+            CREATE INDEX score_history_by_did ON score_history (did, facet, ts);
+        ",
+    },
+    Migration {
+        version: 5,
+        name: "evidence",
+        steps: &[
+            // user -evidence-> content, one edge per facet
+            EdgeLabel("evidence"),
+            key("classifier", Str),
+            key("evidenceLabel", Str),
+            key("evidenceRefs", Str),
+        ],
+        sql: "
+            CREATE TABLE evidence (
+                did TEXT NOT NULL,
+                facet TEXT NOT NULL,
+                cid TEXT NOT NULL,
+                uri TEXT NOT NULL,
+                domain TEXT NOT NULL,
+                classifier TEXT NOT NULL,
+                label TEXT NOT NULL,
+                weight REAL NOT NULL,
+                evidence_refs TEXT NOT NULL,
+                ts INTEGER NOT NULL,
+                PRIMARY KEY (did, facet, cid)
+            );
+        ",
+    },
+    Migration {
+        version: 6,
+        name: "disputes",
+        steps: &[
+            // one vertex per dispute; the full document lives in `dispute`
+            VertexLabel("dispute"),
+            PropertyKey { name: "disputeId", data_type: Str, single: true },
+            key("subjectDid", Str),
+            key("disputeStatus", Str),
+            key("dispute", Str),
+            CompositeIndex { name: "disputeById", keys: &["disputeId"], unique: true },
+            CompositeIndex { name: "disputeBySubject", keys: &["subjectDid"], unique: false },
+        ],
+        sql: "
+            CREATE TABLE disputes (
+                id TEXT PRIMARY KEY,
+                did TEXT NOT NULL,
+                status TEXT NOT NULL,
+                doc TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX disputes_by_did ON disputes (did, created_at);
+            CREATE INDEX disputes_by_status ON disputes (status, created_at);
+        ",
+    },
+    Migration {
+        version: 7,
+        name: "job_leases",
+        steps: &[],
+        sql: "
+            ALTER TABLE jobs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE jobs ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE jobs ADD COLUMN max_attempts INTEGER NOT NULL DEFAULT 5;
+            ALTER TABLE jobs ADD COLUMN run_at INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE jobs ADD COLUMN lease_owner TEXT;
+            ALTER TABLE jobs ADD COLUMN lease_expires_at INTEGER;
+            ALTER TABLE jobs ADD COLUMN last_error TEXT;
+            CREATE INDEX jobs_by_priority ON jobs (status, priority DESC, created_at);
+        ",
+    },
+    Migration {
+        version: 8,
+        name: "job_progress",
+        steps: &[],
+        sql: "
+            ALTER TABLE jobs ADD COLUMN progress TEXT NOT NULL DEFAULT '{}';
+            ALTER TABLE jobs ADD COLUMN started_at INTEGER;
+            ALTER TABLE jobs ADD COLUMN finished_at INTEGER;
+        ",
+    },
+    Migration {
+        version: 9,
+        name: "jobs_by_did",
+        steps: &[],
+        sql: "CREATE INDEX jobs_by_did ON jobs (did, created_at);",
+    },
+];
+
+/// Versions in [`MIGRATIONS`] that `applied` doesn't contain, in order.
+pub fn pending(applied: &[u32]) -> impl Iterator<Item = &'static Migration> + '_ {
+    MIGRATIONS.iter().filter(move |m| !applied.contains(&m.version))
+}
+
+/// Brings `store` up to the latest schema and returns the versions applied.
+pub async fn migrate(store: &dyn GraphStore) -> Result<Vec<u32>> {
+    let applied = store.schema_versions().await?;
+    let mut ran = Vec::new();
+    for m in pending(&applied) {
+        store.apply_migration(m).await?;
+        tracing::info!(backend = store.backend(), version = m.version, name = m.name, "applied graph migration");
+        ran.push(m.version);
+    }
+    Ok(ran)
+}
+
+fn quoted(keys: &[&str]) -> String {
+    keys.iter().map(|k| format!(".addKey(mgmt.getPropertyKey('{k}'))")).collect()
+}
+
+/// Renders `steps` as one JanusGraph management transaction. Schemaless
+/// graphs (e.g. TinkerGraph) have no management API and skip it.
+pub fn gremlin_script(steps: &[SchemaStep]) -> String {
+    let mut s = String::from(
+        "if (!graph.metaClass.respondsTo(graph, 'openManagement')) { return 'schemaless' }\n\
+         mgmt = graph.openManagement()\n\
+         reindex = []\n\
+         try {\n",
+    );
+    for step in steps {
+        let line = match *step {
+            PropertyKey { name, data_type, single } => format!(
+                "if (!mgmt.containsPropertyKey('{name}')) mgmt.makePropertyKey('{name}').dataType({}){}.make()",
+                data_type.groovy(),
+                if single { ".cardinality(Cardinality.SINGLE)" } else { "" },
+            ),
+            VertexLabel(name) => format!("if (!mgmt.containsVertexLabel('{name}')) mgmt.makeVertexLabel('{name}').make()"),
+            EdgeLabel(name) => {
+                format!("if (!mgmt.containsEdgeLabel('{name}')) mgmt.makeEdgeLabel('{name}').multiplicity(Multiplicity.MULTI).make()")
+            }
+            CompositeIndex { name, keys, unique } => format!(
+                "if (!mgmt.containsGraphIndex('{name}')) mgmt.buildIndex('{name}', Vertex.class){}{}.buildCompositeIndex()",
+                quoted(keys),
+                if unique { ".unique()" } else { "" },
+            ),
+            MixedIndex { name, keys } => format!(
+                "if (!mgmt.containsGraphIndex('{name}')) mgmt.buildIndex('{name}', Vertex.class){}.buildMixedIndex('search')",
+                quoted(keys),
+            ),
+            // existing edges are only covered after a reindex
+            EdgeIndex { name, label, key } => format!(
+                "if (!mgmt.containsRelationIndex(mgmt.getEdgeLabel('{label}'), '{name}')) {{ \
+                 mgmt.buildEdgeIndex(mgmt.getEdgeLabel('{label}'), '{name}', Direction.BOTH, Order.desc, mgmt.getPropertyKey('{key}')); \
+                 reindex << ['{name}', '{label}'] }}"
+            ),
+        };
+        s.push_str("  ");
+        s.push_str(&line);
+        s.push('\n');
+    }
+    s.push_str(
+        "  mgmt.commit()\n\
+         } catch (e) { mgmt.rollback(); throw e }\n\
+         reindex.each { idx ->\n\
+         \x20 ManagementSystem.awaitRelationIndexStatus(graph, idx[0], idx[1]).call()\n\
+         \x20 m = graph.openManagement()\n\
+         \x20 m.updateIndex(m.getRelationIndex(m.getEdgeLabel(idx[1]), idx[0]), SchemaAction.REINDEX).get()\n\
+         \x20 m.commit()\n\
+         }\n\
+         'ok'",
+    );
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::graph::MemoryGraphStore;
+
+    #[test]
+    fn t_versions_are_increasing() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(pending(&[1, 3]).next().map(|m| m.version), Some(2));
+    }
+
+    #[test]
+    fn t_every_gremlin_step_is_guarded() {
+        for m in MIGRATIONS {
+            let script = gremlin_script(m.steps);
+            let lines = script.lines().filter(|l| l.contains(".make") || l.contains(".build"));
+            assert!(lines.clone().all(|l| l.trim_start().starts_with("if (!mgmt.contains")), "{}", m.name);
+            assert_eq!(lines.count(), m.steps.len());
+        }
+        assert!(gremlin_script(MIGRATIONS[2].steps).contains("buildEdgeIndex(mgmt.getEdgeLabel('trusts'), 'trustsByTs'"));
+    }
+
+    #[tokio::test]
+    async fn t_migrate_is_idempotent() {
+        let store = MemoryGraphStore::default();
+        assert_eq!(migrate(&store).await.unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert!(migrate(&store).await.unwrap().is_empty());
+        assert_eq!(store.schema_versions().await.unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use trustsystem_core::{derive_trust, DerivedTrust, EvidenceRecord, Opinion, TnaConfig, TrustGraph, UserScores};
+use trustsystem_scoring::Scored;
+
+use crate::services::disputes::{self, Dispute, DisputeStatus};
+
+mod gremlin;
+pub mod history;
+mod memory;
+pub mod migrations;
+
+pub use gremlin::GremlinGraphStore;
+pub use history::ScoreSample;
+pub use memory::MemoryGraphStore;
+pub use migrations::Migration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub did: String,
+    pub handle: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustEdge {
+    pub from_did: String,
+    pub to_did: String,
+    pub scope: String,
+    pub opinion: Opinion,
+    pub evidence_ref: Option<String>,
+    /// Last write, epoch millis.
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowEdge {
+    pub from_did: String,
+    pub to_did: String,
+    pub ts: i64,
+}
+
+/// Domain endorsement, e.g. a physician vouching for another's
+/// `medicine` expertise. Keyed by (from, to, domain).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndorseEdge {
+    pub from_did: String,
+    pub to_did: String,
+    pub domain: String,
+    pub strength: f64,
+    pub ts: i64,
+}
+
+/// Aggregated reply/repost/like activity from one user to another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractEdge {
+    pub from_did: String,
+    pub to_did: String,
+    pub weight: f64,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    pub cid: String,
+    pub uri: String,
+    pub author_did: String,
+    pub domain: Option<String>,
+    pub classification: Option<String>,
+    pub created_at: i64,
+}
+
+/// Filter for trust edge queries; unset fields match anything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustEdgeQuery {
+    pub from_did: Option<String>,
+    pub to_did: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// Storage for the trust graph: users, content, trusts/follows/endorses/
+/// interacts edges and per-user score documents. Upserts are keyed the way
+/// the JanusGraph schema indexes them: users by DID, content by CID, trust
+/// edges by (from, to, scope), endorsements by (from, to, domain) and the
+/// other edges by (from, to).
+#[async_trait]
+pub trait GraphStore: Send + Sync {
+    /// Short backend name for logs.
+    fn backend(&self) -> &'static str;
+
+    /// Schema versions already applied, ascending.
+    async fn schema_versions(&self) -> Result<Vec<u32>>;
+    /// Applies one migration and records its version.
+    async fn apply_migration(&self, migration: &Migration) -> Result<()>;
+
+    /// Creates the user if missing; a given handle replaces the stored one.
+    async fn upsert_user(&self, did: &str, handle: Option<&str>) -> Result<User>;
+    async fn get_user(&self, did: &str) -> Result<Option<User>>;
+
+    async fn upsert_trust_edge(&self, edge: TrustEdge) -> Result<()>;
+    async fn trust_edges(&self, query: &TrustEdgeQuery) -> Result<Vec<TrustEdge>>;
+
+    async fn upsert_follow(&self, from_did: &str, to_did: &str, ts: i64) -> Result<()>;
+    async fn follows_from(&self, did: &str) -> Result<Vec<FollowEdge>>;
+    async fn followers_of(&self, did: &str) -> Result<Vec<FollowEdge>>;
+
+    async fn upsert_endorsement(&self, edge: EndorseEdge) -> Result<()>;
+    /// Endorsements received by `did`.
+    async fn endorsements_of(&self, did: &str) -> Result<Vec<EndorseEdge>>;
+
+    async fn upsert_interaction(&self, edge: InteractEdge) -> Result<()>;
+    async fn interactions_from(&self, did: &str) -> Result<Vec<InteractEdge>>;
+
+    async fn upsert_content(&self, content: Content) -> Result<()>;
+    async fn content_by_author(&self, did: &str) -> Result<Vec<Content>>;
+
+    /// Replaces the score document stored for `scores.did`.
+    async fn upsert_scores(&self, scores: UserScores) -> Result<()>;
+    async fn get_scores(&self, did: &str) -> Result<Option<UserScores>>;
+
+    async fn append_score_sample(&self, did: &str, facet: &str, sample: ScoreSample) -> Result<()>;
+    /// Samples with `from <= ts <= to`, oldest first.
+    async fn score_history(&self, did: &str, facet: &str, from: i64, to: i64) -> Result<Vec<ScoreSample>>;
+    /// Drops every sample of `did` older than `before`.
+    async fn prune_score_history(&self, did: &str, before: i64) -> Result<()>;
+
+    /// Replaces the record for `(did, facet, cid)`.
+    async fn upsert_evidence(&self, record: EvidenceRecord) -> Result<()>;
+    /// Records about `did`, newest first, optionally for one facet.
+    async fn evidence_for(&self, did: &str, facet: Option<&str>) -> Result<Vec<EvidenceRecord>>;
+
+    /// Replaces the dispute with `dispute.id`.
+    async fn upsert_dispute(&self, dispute: Dispute) -> Result<()>;
+    async fn get_dispute(&self, id: &str) -> Result<Option<Dispute>>;
+    /// Disputes filtered by subject and status, oldest first.
+    async fn disputes(&self, did: Option<&str>, status: Option<DisputeStatus>) -> Result<Vec<Dispute>>;
+}
+
+/// Selected by `GRAPH_BACKEND`: `gremlin` (alias `janusgraph`) talks to
+/// `GRAPH_HOST`, `sqlite` uses the embedded file at `SQLITE_PATH`;
+/// anything else keeps the in-memory store.
+static STORE: Lazy<Arc<dyn GraphStore>> = Lazy::new(|| {
+    let store: Arc<dyn GraphStore> = match std::env::var("GRAPH_BACKEND").unwrap_or_default().as_str() {
+        "gremlin" | "janusgraph" => Arc::new(GremlinGraphStore::from_env()),
+        "sqlite" => crate::services::sqlite::shared(),
+        _ => Arc::new(MemoryGraphStore::default()),
+    };
+    tracing::info!(backend = store.backend(), "graph store ready");
+    store
+});
+
+pub fn store() -> Arc<dyn GraphStore> {
+    STORE.clone()
+}
+
+/// Runs pending schema migrations against the configured store.
+pub async fn migrate() -> Result<Vec<u32>> {
+    migrations::migrate(STORE.as_ref()).await
+}
+
+pub async fn get_user_scores(did_or_handle: &str) -> Result<UserScores> {
+    if let Some(v) = store().get_scores(did_or_handle).await? { return Ok(v); }
+    Ok(UserScores::vacuous(did_or_handle))
+}
+
+/// Stores the edge, creating both endpoint users if needed.
+pub async fn upsert_trust_edge(edge: TrustEdge) -> Result<()> {
+    let store = store();
+    store.upsert_user(&edge.from_did, None).await?;
+    store.upsert_user(&edge.to_did, None).await?;
+    store.upsert_trust_edge(edge).await
+}
+
+/// Stores the document and appends one history sample per facet.
+pub async fn upsert_user_scores(scores: UserScores) -> Result<()> {
+    upsert_user_scores_in(store().as_ref(), scores).await
+}
+
+/// [`upsert_user_scores`] against an explicit store.
+pub async fn upsert_user_scores_in(store: &dyn GraphStore, scores: UserScores) -> Result<()> {
+    let (did, ts, facets) = (scores.did.clone(), scores.updated_at, scores.facets);
+    store.upsert_scores(scores).await?;
+    for (facet, record) in facets.iter() {
+        store.append_score_sample(&did, facet, ScoreSample { ts, record: *record }).await?;
+    }
+    if let Some(retention) = history::HISTORY_CONFIG.retention_ms {
+        store.prune_score_history(&did, chrono::Utc::now().timestamp_millis() - retention).await?;
+    }
+    Ok(())
+}
+
+/// Stores one scoring run: classified posts, their evidence records and
+/// the score document. Records with an overturned dispute keep their
+/// resolved weight.
+pub async fn apply_scored(scored: Scored) -> Result<()> {
+    apply_scored_in(store().as_ref(), scored).await
+}
+
+/// [`apply_scored`] against an explicit store.
+pub async fn apply_scored_in(store: &dyn GraphStore, mut scored: Scored) -> Result<()> {
+    disputes::keep_overturned(store, &mut scored).await?;
+    let did = scored.scores.did.clone();
+    for post in scored.content {
+        let content = Content {
+            cid: post.cid,
+            uri: post.uri,
+            author_did: post.author_did,
+            domain: Some(post.domain),
+            classification: Some(post.classification),
+            created_at: post.created_at,
+        };
+        if let Err(e) = store.upsert_content(content).await {
+            tracing::warn!(%did, error = %e, "content upsert failed");
+        }
+    }
+    for record in scored.evidence {
+        if let Err(e) = store.upsert_evidence(record).await {
+            tracing::warn!(%did, error = %e, "evidence upsert failed");
+        }
+    }
+    upsert_user_scores_in(store, scored.scores).await
+}
+
+/// Upper bound on users whose outgoing edges are loaded for one
+/// viewer-relative query.
+const MAX_TRUST_FRONTIER: usize = 2_000;
+
+/// `viewer`'s derived opinion of `target` over stored `trusts` edges in
+/// `scope`, or `None` when no path of at most `cfg.max_depth` hops exists.
+/// Edges are loaded breadth-first from the viewer, so only the
+/// neighbourhood reachable within the hop limit is read.
+pub async fn viewer_trust(
+    store: &dyn GraphStore,
+    viewer: &str,
+    target: &str,
+    scope: &str,
+    cfg: &TnaConfig,
+) -> Result<Option<DerivedTrust>> {
+    let mut graph = TrustGraph::new();
+    let mut seen: BTreeSet<String> = BTreeSet::from([viewer.to_string()]);
+    let mut frontier = vec![viewer.to_string()];
+    // nodes at depth max_depth - 1 only matter through edges into the target
+    for depth in 0..cfg.max_depth {
+        let mut next = Vec::new();
+        for from in &frontier {
+            let query = TrustEdgeQuery {
+                from_did: Some(from.clone()),
+                to_did: (depth + 1 == cfg.max_depth).then(|| target.to_string()),
+                scope: Some(scope.to_string()),
+            };
+            for edge in store.trust_edges(&query).await? {
+                if edge.to_did != target && seen.len() < MAX_TRUST_FRONTIER && seen.insert(edge.to_did.clone()) {
+                    next.push(edge.to_did.clone());
+                }
+                graph.add_edge(edge.from_did, edge.to_did, edge.opinion);
+            }
+        }
+        frontier = next;
+    }
+    Ok(derive_trust(&graph, viewer, target, cfg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn edge(from: &str, to: &str, scope: &str, b: f64) -> TrustEdge {
+        TrustEdge {
+            from_did: from.into(),
+            to_did: to.into(),
+            scope: scope.into(),
+            opinion: Opinion::new(b, 0.0, 1.0 - b),
+            evidence_ref: None,
+            ts: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn t_viewer_trust_follows_scoped_paths() {
+        let store = MemoryGraphStore::default();
+        for e in [
+            edge("did:v", "did:a", "general", 0.8),
+            edge("did:a", "did:t", "general", 0.6),
+            edge("did:v", "did:b", "general", 0.5),
+            edge("did:b", "did:c", "general", 0.9),
+            edge("did:c", "did:t", "general", 0.9),
+            edge("did:v", "did:t", "medicine", 0.9),
+        ] {
+            store.upsert_trust_edge(e).await.unwrap();
+        }
+
+        let cfg = TnaConfig { max_depth: 2, ..Default::default() };
+        let two_hops = viewer_trust(&store, "did:v", "did:t", "general", &cfg).await.unwrap().unwrap();
+        assert_eq!(two_hops.paths.len(), 1);
+        assert_eq!(two_hops.paths[0].nodes, ["did:v", "did:a", "did:t"]);
+
+        let cfg = TnaConfig { max_depth: 3, ..Default::default() };
+        let three_hops = viewer_trust(&store, "did:v", "did:t", "general", &cfg).await.unwrap().unwrap();
+        assert_eq!(three_hops.paths.len(), 2);
+        assert!(three_hops.opinion.u < two_hops.opinion.u);
+
+        let direct = viewer_trust(&store, "did:v", "did:t", "medicine", &cfg).await.unwrap().unwrap();
+        assert_relative_eq!(direct.opinion.b, 0.9, epsilon = 1e-9);
+        assert!(viewer_trust(&store, "did:t", "did:v", "general", &cfg).await.unwrap().is_none());
+    }
+}
@@ -1,105 +1,959 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures_util::stream::{self, Stream};
 use once_cell::sync::Lazy;
-use dashmap::DashMap;
-use serde_json::json;
-use trustsystem_core as core;
-use crate::services::{atproto, gemini};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use trustsystem_scoring as scoring;
+use crate::services::fusion::FACET_FUSION;
+use crate::services::{graph, sqlite, transport};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Processing,
+    Done,
+    /// The last attempt failed; runnable again once `runAt` passes.
+    Failed,
+    /// Out of attempts; left for an operator.
+    DeadLetter,
+    /// Replaced by a newer job for the same DID.
+    Superseded,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Processing => "processing",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+            JobStatus::DeadLetter => "dead_letter",
+            JobStatus::Superseded => "superseded",
+        }
+    }
 
-static JOBS: Lazy<DashMap<String, String>> = Lazy::new(|| DashMap::new());
-static QUEUE: Lazy<DashMap<String, String>> = Lazy::new(|| DashMap::new()); // jobId -> did
+    /// Nothing further will happen to the job.
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::DeadLetter | JobStatus::Superseded)
+    }
 
-pub async fn enqueue_score_job(_did: &str, _job_id: &str, _force: bool) -> Result<()> {
-    // TODO: produce to Kafka topic score.jobs
-    JOBS.insert(_job_id.to_string(), "queued".into());
-    QUEUE.insert(_job_id.to_string(), _did.to_string());
-    Ok(())
+    pub fn parse(s: &str) -> Option<Self> {
+        [JobStatus::Queued, JobStatus::Processing, JobStatus::Done, JobStatus::Failed, JobStatus::DeadLetter, JobStatus::Superseded]
+            .into_iter()
+            .find(|st| st.as_str() == s)
+    }
 }
 
-pub async fn get_job_status(job_id: &str) -> Option<String> {
-    JOBS.get(job_id).map(|v| v.clone())
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Lease and retry settings: `JOB_LEASE_MS` (default 60s),
+/// `JOB_MAX_ATTEMPTS` (5), and exponential backoff from `JOB_BACKOFF_MS`
+/// (5s) capped at `JOB_BACKOFF_MAX_MS` (10 min). Scores younger than
+/// `JOB_FRESHNESS_MS` (6h) are reused unless forced, and a DID gets at most
+/// one new job per `JOB_COOLDOWN_MS` (60s), forced or not.
+#[derive(Debug, Clone)]
+pub struct JobConfig {
+    pub lease_ms: i64,
+    pub max_attempts: u32,
+    pub backoff_ms: i64,
+    pub backoff_max_ms: i64,
+    pub freshness_ms: i64,
+    pub cooldown_ms: i64,
 }
 
-pub async fn pop_job() -> Option<(String, String)> {
-    if let Some(entry) = QUEUE.iter().next() {
-        let job_id = entry.key().clone();
-        let did = entry.value().clone();
-        QUEUE.remove(&job_id);
-        JOBS.insert(job_id.clone(), "processing".into());
-        return Some((job_id, did));
+impl Default for JobConfig {
+    fn default() -> Self {
+        Self {
+            lease_ms: 60_000,
+            max_attempts: 5,
+            backoff_ms: 5_000,
+            backoff_max_ms: 600_000,
+            freshness_ms: 6 * 60 * 60 * 1000,
+            cooldown_ms: 60_000,
+        }
     }
-    None
 }
 
-pub async fn mark_done(job_id: &str) {
-    JOBS.insert(job_id.to_string(), "done".into());
+impl JobConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        let d = Self::default();
+        Self {
+            lease_ms: var("JOB_LEASE_MS").filter(|v| *v > 0).unwrap_or(d.lease_ms),
+            max_attempts: var("JOB_MAX_ATTEMPTS").filter(|v| *v > 0).unwrap_or(d.max_attempts),
+            backoff_ms: var("JOB_BACKOFF_MS").filter(|v| *v >= 0).unwrap_or(d.backoff_ms),
+            backoff_max_ms: var("JOB_BACKOFF_MAX_MS").filter(|v| *v >= 0).unwrap_or(d.backoff_max_ms),
+            freshness_ms: var("JOB_FRESHNESS_MS").filter(|v| *v >= 0).unwrap_or(d.freshness_ms),
+            cooldown_ms: var("JOB_COOLDOWN_MS").filter(|v| *v >= 0).unwrap_or(d.cooldown_ms),
+        }
+    }
+
+    /// Delay before retrying after the `attempts`-th failure.
+    pub fn backoff(&self, attempts: u32) -> i64 {
+        let factor = 1i64.checked_shl(attempts.saturating_sub(1)).unwrap_or(i64::MAX);
+        self.backoff_ms.saturating_mul(factor).min(self.backoff_max_ms)
+    }
 }
 
-pub async fn process_job_inline(did: String, handle: String, job_id: String) {
-    // MVP real-ish flow: fetch posts and derive basic alpha/beta counts
-    let mut posts = atproto::fetch_recent_posts(&did, 25).await.unwrap_or_default();
-    if posts.is_empty() {
-        // Fallback to using handle when DID resolution failed
-        posts = atproto::fetch_recent_posts(&handle, 25).await.unwrap_or_default();
-    }
-    let mut alpha_acc = 0.0f64;
-    let mut beta_acc = 0.0f64;
-    let mut alpha_civ = 0.0f64;
-    let mut beta_civ = 0.0f64;
-    let mut evidence: Vec<serde_json::Value> = Vec::new();
-
-    // Lightweight claim heuristic to widen coverage
-    fn looks_like_claim(text: &str) -> bool {
-        let t = text.to_lowercase();
-        if t.len() < 40 { return false; }
-        let has_digit = t.chars().any(|c| c.is_ascii_digit());
-        let has_link = t.contains("http://") || t.contains("https://");
-        let cues = [" is ", " are ", " was ", " were ", " will ", " has ", " have ", "%", " million", " billion", " according to ", " reports ", " says "];
-        let cue_hit = cues.iter().any(|p| t.contains(p));
-        let words = t.split_whitespace().count();
-        words >= 8 && (has_digit || has_link || cue_hit)
-    }
-
-    let mut claim_calls = 0usize;
-    for (idx, p) in posts.iter().take(100).enumerate() {
-        let text = p.record.as_ref().and_then(|r| r.text.clone()).unwrap_or_default();
-        if text.is_empty() { continue; }
-        let force_call = idx < 10; // always inspect first 10 posts
-        if (force_call || looks_like_claim(&text)) && claim_calls < 25 {
-            claim_calls += 1;
-            let r = gemini::analyze_claim(&text, "politics").await.unwrap_or(gemini::GeminiResponse{classification:"neutral".into(), evidenceRefs:vec![]});
-            match r.classification.as_str() {
-                "accurate" => alpha_acc += 1.0,
-                "inaccurate" => beta_acc += 1.0,
-                "contested" => evidence.push(serde_json::json!({"cid": p.cid, "domain":"politics", "classification":"contested", "evidenceRefs": r.evidenceRefs})),
-                _ => {}
+pub static JOB_CONFIG: Lazy<JobConfig> = Lazy::new(JobConfig::from_env);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub job_id: String,
+    pub did: String,
+    pub status: JobStatus,
+    /// Higher runs first; FIFO within a priority.
+    pub priority: i32,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Not runnable before this (backoff after a failure).
+    pub run_at: i64,
+    pub lease_owner: Option<String>,
+    pub lease_expires_at: Option<i64>,
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub progress: JobProgress,
+    /// First lease and terminal transition (done or dead-lettered).
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    #[serde(default)]
+    pub finished_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// What the current attempt has got through. Counts restart with each
+/// attempt; `errors` accumulates across attempts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JobProgress {
+    pub posts_fetched: u32,
+    pub posts_classified: u32,
+    pub facets_computed: u32,
+    pub errors: Vec<String>,
+}
+
+/// Partial progress report; absent counts are left as they are.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressUpdate {
+    pub posts_fetched: Option<u32>,
+    pub posts_classified: Option<u32>,
+    pub facets_computed: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// Most errors kept on a job; older ones are dropped first.
+const MAX_JOB_ERRORS: usize = 20;
+
+impl JobProgress {
+    fn push_error(&mut self, error: &str) {
+        if self.errors.len() == MAX_JOB_ERRORS {
+            self.errors.remove(0);
+        }
+        self.errors.push(error.to_string());
+    }
+}
+
+#[derive(Debug)]
+pub enum JobError {
+    NotFound,
+    /// The job isn't in a state that allows the operation.
+    WrongState(JobStatus),
+    /// Another worker holds (or took over) the lease.
+    NotLeaseHolder,
+    /// The caller's lease ran out; the job may be retried elsewhere.
+    LeaseExpired,
+    /// A worker id is missing or reserved for the API itself.
+    InvalidWorker(String),
+    Store(anyhow::Error),
+}
+
+impl JobError {
+    /// Machine-readable name for API error bodies.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            JobError::NotFound => "not_found",
+            JobError::WrongState(_) => "wrong_state",
+            JobError::NotLeaseHolder => "not_lease_holder",
+            JobError::LeaseExpired => "lease_expired",
+            JobError::InvalidWorker(_) => "invalid_worker",
+            JobError::Store(_) => "store",
+        }
+    }
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::NotFound => f.write_str("unknown job"),
+            JobError::WrongState(status) => write!(f, "job is {status}"),
+            JobError::NotLeaseHolder => f.write_str("lease is held by another worker"),
+            JobError::LeaseExpired => f.write_str("lease expired"),
+            JobError::InvalidWorker(why) => f.write_str(why),
+            JobError::Store(e) => write!(f, "{e:#}"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+impl From<anyhow::Error> for JobError {
+    fn from(e: anyhow::Error) -> Self { JobError::Store(e) }
+}
+
+impl Job {
+    pub fn new(job_id: &str, did: &str, priority: i32, cfg: &JobConfig, now: i64) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            did: did.to_string(),
+            status: JobStatus::Queued,
+            priority,
+            attempts: 0,
+            max_attempts: cfg.max_attempts,
+            run_at: now,
+            lease_owner: None,
+            lease_expires_at: None,
+            last_error: None,
+            progress: JobProgress::default(),
+            started_at: None,
+            finished_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Whether [`Job::try_lease`] would touch it at `now`: waiting and due,
+    /// or processing under an expired lease.
+    pub fn claimable(&self, now: i64) -> bool {
+        match self.status {
+            JobStatus::Queued | JobStatus::Failed => self.run_at <= now,
+            JobStatus::Processing => self.lease_expires_at.is_none_or(|t| t <= now),
+            JobStatus::Done | JobStatus::DeadLetter | JobStatus::Superseded => false,
+        }
+    }
+
+    /// Leases a due job to `worker` and counts the attempt. An expired
+    /// lease is instead recorded as a failed attempt and `false` returned.
+    pub fn try_lease(&mut self, worker: &str, now: i64, cfg: &JobConfig) -> bool {
+        if !self.claimable(now) {
+            return false;
+        }
+        if self.status == JobStatus::Processing {
+            let _ = self.expire_lease(now, cfg);
+            return false;
+        }
+        self.status = JobStatus::Processing;
+        self.attempts += 1;
+        self.progress = JobProgress { errors: std::mem::take(&mut self.progress.errors), ..JobProgress::default() };
+        self.started_at.get_or_insert(now);
+        self.lease_owner = Some(worker.to_string());
+        self.lease_expires_at = Some(now + cfg.lease_ms);
+        self.updated_at = now;
+        true
+    }
+
+    /// Leases this particular job, as a pushed delivery does; unlike
+    /// [`Job::try_lease`] it never takes over a processing job.
+    pub fn lease(&mut self, worker: &str, now: i64, cfg: &JobConfig) -> Result<(), JobError> {
+        if self.status == JobStatus::Processing || !self.try_lease(worker, now, cfg) {
+            return Err(JobError::WrongState(self.status));
+        }
+        Ok(())
+    }
+
+    /// Records a lapsed lease as a failed attempt.
+    pub fn expire_lease(&mut self, now: i64, cfg: &JobConfig) -> Result<(), JobError> {
+        if self.status != JobStatus::Processing || !self.claimable(now) {
+            return Err(JobError::WrongState(self.status));
+        }
+        let owner = self.lease_owner.clone().unwrap_or_default();
+        self.record_failure(&format!("lease expired (worker {owner})"), now, cfg);
+        Ok(())
+    }
+
+    /// `worker` holds a live lease on the job.
+    fn check_lease(&self, worker: &str, now: i64) -> Result<(), JobError> {
+        if self.status != JobStatus::Processing {
+            return Err(JobError::WrongState(self.status));
+        }
+        if self.lease_owner.as_deref() != Some(worker) {
+            return Err(JobError::NotLeaseHolder);
+        }
+        if self.lease_lapsed(now) {
+            return Err(JobError::LeaseExpired);
+        }
+        Ok(())
+    }
+
+    pub fn heartbeat(&mut self, worker: &str, now: i64, cfg: &JobConfig) -> Result<(), JobError> {
+        self.check_lease(worker, now)?;
+        self.lease_expires_at = Some(now + cfg.lease_ms);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    pub fn complete(&mut self, worker: &str, now: i64) -> Result<(), JobError> {
+        self.check_lease(worker, now)?;
+        self.status = JobStatus::Done;
+        self.finished_at = Some(now);
+        self.lease_owner = None;
+        self.lease_expires_at = None;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    pub fn record_progress(&mut self, worker: &str, update: &ProgressUpdate, now: i64) -> Result<(), JobError> {
+        self.check_lease(worker, now)?;
+        let p = &mut self.progress;
+        p.posts_fetched = update.posts_fetched.unwrap_or(p.posts_fetched);
+        p.posts_classified = update.posts_classified.unwrap_or(p.posts_classified);
+        p.facets_computed = update.facets_computed.unwrap_or(p.facets_computed);
+        if let Some(e) = &update.error {
+            p.push_error(e);
+        }
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Hands the job back unfinished, e.g. from a worker shutting down: it
+    /// is queued again straight away and the attempt isn't counted.
+    pub fn release(&mut self, worker: &str, now: i64) -> Result<(), JobError> {
+        self.check_lease(worker, now)?;
+        self.status = JobStatus::Queued;
+        self.attempts = self.attempts.saturating_sub(1);
+        self.run_at = now;
+        self.lease_owner = None;
+        self.lease_expires_at = None;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    pub fn fail(&mut self, worker: &str, error: &str, now: i64, cfg: &JobConfig) -> Result<(), JobError> {
+        self.check_lease(worker, now)?;
+        self.record_failure(error, now, cfg);
+        Ok(())
+    }
+
+    fn record_failure(&mut self, error: &str, now: i64, cfg: &JobConfig) {
+        self.status = if self.attempts >= self.max_attempts { JobStatus::DeadLetter } else { JobStatus::Failed };
+        self.run_at = now + cfg.backoff(self.attempts);
+        self.last_error = Some(error.to_string());
+        self.progress.push_error(error);
+        if self.status == JobStatus::DeadLetter {
+            self.finished_at = Some(now);
+        }
+        self.lease_owner = None;
+        self.lease_expires_at = None;
+        self.updated_at = now;
+    }
+}
+
+/// Public view of a job, without lease bookkeeping.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobView {
+    pub job_id: String,
+    pub did: String,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub progress: JobProgress,
+    pub error: Option<String>,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+    /// Where the scores are, once the job is done.
+    pub result: Option<String>,
+}
+
+impl From<&Job> for JobView {
+    fn from(job: &Job) -> Self {
+        Self {
+            job_id: job.job_id.clone(),
+            did: job.did.clone(),
+            status: job.status,
+            attempts: job.attempts,
+            progress: job.progress.clone(),
+            error: job.last_error.clone(),
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+            result: (job.status == JobStatus::Done).then(|| format!("/v1/user/{}/scores", job.did)),
+        }
+    }
+}
+
+/// How a score request was handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnqueueOutcome {
+    /// A new job was queued.
+    Created,
+    /// The DID already had a job in flight; that job is returned.
+    Coalesced,
+    /// A new job was queued in place of a stuck one (a lapsed lease, or a
+    /// failed job when forced), which is superseded.
+    Replaced,
+    /// Stored scores are within the freshness window; nothing was queued.
+    Fresh,
+    /// The DID's last job was created within the cooldown; it is returned.
+    CoolingDown,
+}
+
+#[derive(Debug, Clone)]
+pub struct Enqueued {
+    pub job: Option<Job>,
+    pub outcome: EnqueueOutcome,
+}
+
+impl Enqueued {
+    /// The job, if it is due and nobody holds it, so the caller may run it.
+    pub fn runnable(&self, now: i64) -> Option<&Job> {
+        self.job.as_ref().filter(|j| j.claimable(now))
+    }
+}
+
+impl Job {
+    /// Queued, waiting to retry, or running under a live lease.
+    pub fn in_flight(&self, now: i64) -> bool {
+        match self.status {
+            JobStatus::Queued | JobStatus::Failed => true,
+            JobStatus::Processing => !self.claimable(now),
+            _ => false,
+        }
+    }
+
+    /// Processing under a lease nobody renewed.
+    fn lease_lapsed(&self, now: i64) -> bool {
+        self.status == JobStatus::Processing && self.claimable(now)
+    }
+
+    pub fn supersede(&mut self, by: &str, now: i64) {
+        self.status = JobStatus::Superseded;
+        self.last_error = Some(format!("superseded by job {by}"));
+        self.finished_at = Some(now);
+        self.lease_owner = None;
+        self.lease_expires_at = None;
+        self.updated_at = now;
+    }
+}
+
+/// Decides what to do with `new` given the newest job already stored for
+/// its DID. A coalesced job inherits the higher of the two priorities. A
+/// stuck job (lapsed lease, or failed and `force`d) is replaced by `new`
+/// and returned superseded as the second job, to be stored as well.
+pub fn coalesce(latest: Option<Job>, new: Job, force: bool, cfg: &JobConfig) -> (Job, EnqueueOutcome, Option<Job>) {
+    let now = new.created_at;
+    match latest {
+        Some(mut job) if job.lease_lapsed(now) || (force && job.status == JobStatus::Failed) => {
+            job.supersede(&new.job_id, now);
+            (new, EnqueueOutcome::Replaced, Some(job))
+        }
+        Some(mut job) if job.in_flight(now) => {
+            if job.priority < new.priority && job.status != JobStatus::Processing {
+                job.priority = new.priority;
+                job.updated_at = now;
+            }
+            (job, EnqueueOutcome::Coalesced, None)
+        }
+        Some(job) if now - job.created_at < cfg.cooldown_ms => (job, EnqueueOutcome::CoolingDown, None),
+        _ => (new, EnqueueOutcome::Created, None),
+    }
+}
+
+/// Claim order: priority, then age.
+pub fn queue_order(a: &Job, b: &Job) -> std::cmp::Ordering {
+    b.priority.cmp(&a.priority).then(a.created_at.cmp(&b.created_at)).then_with(|| a.job_id.cmp(&b.job_id))
+}
+
+/// Edit applied to one job under the store's lock.
+pub type JobUpdate = Box<dyn FnOnce(&mut Job) -> Result<(), JobError> + Send>;
+
+/// Where score jobs live. Stores only order and persist; state changes go
+/// through the [`Job`] methods so every backend behaves the same.
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// Atomically applies [`coalesce`] against the DID's newest job and
+    /// stores the result, including any job it superseded.
+    async fn enqueue(&self, job: Job, force: bool, cfg: &JobConfig) -> Result<(Job, EnqueueOutcome)>;
+    async fn get(&self, job_id: &str) -> Result<Option<Job>>;
+    /// Jobs in claim order, optionally with one status.
+    async fn list(&self, status: Option<JobStatus>) -> Result<Vec<Job>>;
+    /// Leases the first claimable job to `worker`, expiring stale leases on
+    /// the way.
+    async fn claim(&self, worker: &str, now: i64, cfg: &JobConfig) -> Result<Option<Job>>;
+    /// Applies `f` atomically and stores the result if it succeeds.
+    async fn update(&self, job_id: &str, f: JobUpdate) -> Result<Job, JobError>;
+}
+
+/// Process-local queue; jobs are lost on restart.
+#[derive(Default)]
+pub struct MemoryJobStore {
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl MemoryJobStore {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Job>>> {
+        self.jobs.lock().map_err(|_| anyhow!("job store lock poisoned"))
+    }
+}
+
+#[async_trait]
+impl JobStore for MemoryJobStore {
+    async fn enqueue(&self, job: Job, force: bool, cfg: &JobConfig) -> Result<(Job, EnqueueOutcome)> {
+        let mut jobs = self.lock()?;
+        let latest = jobs.values().filter(|j| j.did == job.did).max_by_key(|j| (j.created_at, j.job_id.clone())).cloned();
+        let (job, outcome, superseded) = coalesce(latest, job, force, cfg);
+        if let Some(old) = superseded {
+            jobs.insert(old.job_id.clone(), old);
+        }
+        jobs.insert(job.job_id.clone(), job.clone());
+        Ok((job, outcome))
+    }
+
+    async fn get(&self, job_id: &str) -> Result<Option<Job>> {
+        Ok(self.lock()?.get(job_id).cloned())
+    }
+
+    async fn list(&self, status: Option<JobStatus>) -> Result<Vec<Job>> {
+        let mut out: Vec<Job> = self.lock()?.values().filter(|j| status.is_none_or(|s| s == j.status)).cloned().collect();
+        out.sort_by(queue_order);
+        Ok(out)
+    }
+
+    async fn claim(&self, worker: &str, now: i64, cfg: &JobConfig) -> Result<Option<Job>> {
+        let mut jobs = self.lock()?;
+        let mut due: Vec<&mut Job> = jobs.values_mut().filter(|j| j.claimable(now)).collect();
+        due.sort_by(|a, b| queue_order(a, b));
+        for job in due {
+            if job.try_lease(worker, now, cfg) {
+                return Ok(Some(job.clone()));
             }
         }
-        // simplistic civility heuristic (only increment on posts > 5 chars)
-        if text.len() > 5 {
-            if text.to_lowercase().contains("idiot") { beta_civ += 1.0; } else { alpha_civ += 1.0; }
-        }
-    }
-
-    let o_acc = core::evidence_to_opinion(alpha_acc, beta_acc, 2.0);
-    let o_civ = core::evidence_to_opinion(alpha_civ, beta_civ, 2.0);
-    let scores = json!({
-        "did": did,
-        "handle": handle,
-        "updatedAt": (chrono::Utc::now().timestamp_millis()),
-        "facets": {
-            "accuracy": {"alpha": alpha_acc as i64, "beta": beta_acc as i64, "b": o_acc.b, "d": o_acc.d, "u": o_acc.u},
-            "civility": {"alpha": alpha_civ as i64, "beta": beta_civ as i64, "b": o_civ.b, "d": o_civ.d, "u": o_civ.u}
-        },
-        "botProb": 0.12,
-        "expertise": [],
-        "evidence": evidence
+        Ok(None)
+    }
+
+    async fn update(&self, job_id: &str, f: JobUpdate) -> Result<Job, JobError> {
+        let mut jobs = self.lock()?;
+        let job = jobs.get_mut(job_id).ok_or(JobError::NotFound)?;
+        let mut next = job.clone();
+        f(&mut next)?;
+        *job = next.clone();
+        Ok(next)
+    }
+}
+
+/// Selected by `JOB_BACKEND` (`memory` or `sqlite`). Defaults to `sqlite`
+/// when the graph is on SQLite so a single-node deployment keeps its
+/// queue in the same file.
+static JOB_STORE: Lazy<Arc<dyn JobStore>> = Lazy::new(|| {
+    let graph_backend = std::env::var("GRAPH_BACKEND").unwrap_or_default();
+    let default = if graph_backend == "sqlite" { "sqlite" } else { "memory" };
+    match std::env::var("JOB_BACKEND").unwrap_or_else(|_| default.to_string()).as_str() {
+        "sqlite" => sqlite::shared(),
+        _ => Arc::new(MemoryJobStore::default()),
+    }
+});
+
+pub fn job_store() -> Arc<dyn JobStore> {
+    JOB_STORE.clone()
+}
+
+/// Priority for jobs a user is waiting on (lookups); background jobs use 0.
+pub const LOOKUP_PRIORITY: i32 = 10;
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Every stored job change, for `/v1/jobs/:id/events`. Process-local:
+/// subscribers also poll the store to see changes made elsewhere.
+static EVENTS: Lazy<broadcast::Sender<Job>> = Lazy::new(|| broadcast::channel(256).0);
+
+pub fn subscribe() -> broadcast::Receiver<Job> {
+    EVENTS.subscribe()
+}
+
+fn publish(job: &Job) {
+    // no subscribers is fine
+    let _ = EVENTS.send(job.clone());
+}
+
+async fn update(job_id: &str, f: JobUpdate) -> Result<Job, JobError> {
+    let job = job_store().update(job_id, f).await?;
+    publish(&job);
+    Ok(job)
+}
+
+/// How often [`watch`] re-reads the store for changes made by other
+/// processes.
+const WATCH_POLL: Duration = Duration::from_secs(2);
+
+/// `job` as it changes: its current view first, then one per visible
+/// change, ending once it is finished.
+pub fn watch(job: Job) -> impl Stream<Item = JobView> {
+    struct Watch { job_id: String, rx: broadcast::Receiver<Job>, next: Option<JobView>, last: Option<JobView> }
+    let state = Watch { job_id: job.job_id.clone(), rx: subscribe(), next: Some(JobView::from(&job)), last: None };
+    stream::unfold(state, |mut st| async move {
+        if st.last.as_ref().is_some_and(|v| v.status.is_finished()) {
+            return None;
+        }
+        let view = loop {
+            if let Some(v) = st.next.take() {
+                if st.last.as_ref() != Some(&v) {
+                    break v;
+                }
+            }
+            let job = tokio::select! {
+                msg = st.rx.recv() => match msg {
+                    Ok(job) if job.job_id == st.job_id => Some(job),
+                    Ok(_) => None,
+                    Err(broadcast::error::RecvError::Lagged(_)) => get_job(&st.job_id).await,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = tokio::time::sleep(WATCH_POLL) => get_job(&st.job_id).await,
+            };
+            st.next = job.as_ref().map(JobView::from);
+        };
+        st.last = Some(view.clone());
+        Some((view, st))
+    })
+}
+
+/// Queues a score job for `did` unless one is in flight, its scores are
+/// fresh (skipped when `force`) or it is cooling down. `force` also
+/// replaces a job waiting out a failure's backoff.
+pub async fn enqueue_score_job(did: &str, force: bool, priority: i32) -> Result<Enqueued> {
+    let now = now_ms();
+    if !force {
+        if let Some(scores) = graph::store().get_scores(did).await? {
+            if now - scores.updated_at < JOB_CONFIG.freshness_ms {
+                return Ok(Enqueued { job: None, outcome: EnqueueOutcome::Fresh });
+            }
+        }
+    }
+    let job = Job::new(&uuid::Uuid::new_v4().to_string(), did, priority, &JOB_CONFIG, now);
+    let (job, outcome) = job_store().enqueue(job, force, &JOB_CONFIG).await?;
+    if matches!(outcome, EnqueueOutcome::Created | EnqueueOutcome::Replaced) {
+        publish(&job);
+        transport::wake();
+    } else {
+        tracing::debug!(did, job_id = %job.job_id, ?outcome, "score request reused a job");
+    }
+    Ok(Enqueued { job: Some(job), outcome })
+}
+
+pub async fn get_job(job_id: &str) -> Option<Job> {
+    job_store().get(job_id).await.unwrap_or_else(|e| {
+        tracing::warn!(job_id, error = %e, "job lookup failed");
+        None
+    })
+}
+
+pub async fn claim_job(worker: &str) -> Option<Job> {
+    let job = job_store().claim(worker, now_ms(), &JOB_CONFIG).await.unwrap_or_else(|e| {
+        tracing::warn!(worker, error = %e, "job claim failed");
+        None
     });
-    let _ = crate::services::graph::upsert_user_scores(
-        scores.get("did").and_then(|v| v.as_str()).unwrap_or("") ,
-        scores.clone()
-    ).await;
-    mark_done(&job_id).await;
+    job.inspect(publish)
+}
+
+pub async fn lease_job(job_id: &str, worker: &str) -> Result<Job, JobError> {
+    let worker = worker.to_string();
+    update(job_id, Box::new(move |j| j.lease(&worker, now_ms(), &JOB_CONFIG))).await
+}
+
+pub async fn expire_lease(job_id: &str) -> Result<Job, JobError> {
+    update(job_id, Box::new(|j| j.expire_lease(now_ms(), &JOB_CONFIG))).await
+}
+
+pub async fn heartbeat(job_id: &str, worker: &str) -> Result<Job, JobError> {
+    let worker = worker.to_string();
+    job_store().update(job_id, Box::new(move |j| j.heartbeat(&worker, now_ms(), &JOB_CONFIG))).await
+}
+
+pub async fn complete_job(job_id: &str, worker: &str) -> Result<Job, JobError> {
+    let worker = worker.to_string();
+    update(job_id, Box::new(move |j| j.complete(&worker, now_ms()))).await
 }
 
+pub async fn report_progress(job_id: &str, worker: &str, progress: ProgressUpdate) -> Result<Job, JobError> {
+    let worker = worker.to_string();
+    update(job_id, Box::new(move |j| j.record_progress(&worker, &progress, now_ms()))).await
+}
 
+pub async fn release_job(job_id: &str, worker: &str) -> Result<Job, JobError> {
+    let worker = worker.to_string();
+    update(job_id, Box::new(move |j| j.release(&worker, now_ms()))).await
+}
 
+pub async fn fail_job(job_id: &str, worker: &str, error: &str) -> Result<Job, JobError> {
+    let (worker, error) = (worker.to_string(), error.to_string());
+    let job = update(job_id, Box::new(move |j| j.fail(&worker, &error, now_ms(), &JOB_CONFIG))).await?;
+    if job.status == JobStatus::DeadLetter {
+        tracing::warn!(job_id, did = %job.did, error = job.last_error.as_deref().unwrap_or(""), "job dead-lettered");
+    }
+    Ok(job)
+}
+
+/// Worker name used when the API runs a job itself.
+const INLINE_WORKER: &str = "inline";
+
+/// A worker id sent over HTTP: required, and never the API's own, so only
+/// inline processing can act on inline leases.
+pub fn external_worker(worker: Option<String>) -> Result<String, JobError> {
+    match worker.filter(|w| !w.trim().is_empty()) {
+        None => Err(JobError::InvalidWorker("worker is required".to_string())),
+        Some(w) if w == INLINE_WORKER => Err(JobError::InvalidWorker(format!("worker id `{w}` is reserved"))),
+        Some(w) => Ok(w),
+    }
+}
+
+/// Progress reports for an inline job.
+struct JobObserver {
+    job_id: String,
+}
+
+impl JobObserver {
+    async fn report(&self, update: ProgressUpdate) {
+        if let Err(e) = report_progress(&self.job_id, INLINE_WORKER, update).await {
+            tracing::warn!(job_id = %self.job_id, error = %e, "progress update failed");
+        }
+    }
+}
+
+#[async_trait]
+impl scoring::Observer for JobObserver {
+    async fn classified(&self, posts: u32) {
+        self.report(ProgressUpdate { posts_classified: Some(posts), ..Default::default() }).await;
+    }
+
+    async fn facets_computed(&self, facets: u32) {
+        self.report(ProgressUpdate { facets_computed: Some(facets), ..Default::default() }).await;
+    }
+
+    async fn error(&self, error: String) {
+        self.report(ProgressUpdate { error: Some(error), ..Default::default() }).await;
+    }
+}
+
+pub async fn process_job_inline(did: String, handle: String, job_id: String) {
+    // lease it first so a polling worker doesn't run the same job
+    if let Err(e) = lease_job(&job_id, INLINE_WORKER).await {
+        tracing::info!(%job_id, reason = %e, "skipping inline processing");
+        return;
+    }
+    let observer = JobObserver { job_id: job_id.clone() };
+    let posts = match scoring::fetch_posts(&did, &handle).await {
+        Ok(posts) => posts,
+        Err(e) => {
+            scoring::Observer::error(&observer, format!("fetch posts: {e:#}")).await;
+            Vec::new()
+        }
+    };
+    observer.report(ProgressUpdate { posts_fetched: Some(posts.len() as u32), ..Default::default() }).await;
+    let scored = scoring::score_posts(&did, &handle, &posts, &scoring::GeminiClassifier, &FACET_FUSION, &observer, now_ms()).await;
+    let res = match graph::apply_scored(scored).await {
+        Ok(()) => complete_job(&job_id, INLINE_WORKER).await,
+        Err(e) => fail_job(&job_id, INLINE_WORKER, &format!("{e:#}")).await,
+    };
+    if let Err(e) = res {
+        tracing::warn!(%job_id, error = %e, "inline job could not be closed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cfg() -> JobConfig {
+        JobConfig { lease_ms: 100, max_attempts: 2, backoff_ms: 10, backoff_max_ms: 15, freshness_ms: 1_000, cooldown_ms: 50 }
+    }
+
+    #[test]
+    fn t_backoff_doubles_up_to_the_cap() {
+        let cfg = JobConfig { backoff_max_ms: 1_000, ..test_cfg() };
+        assert_eq!([1, 2, 3, 8, 200].map(|n| cfg.backoff(n)), [10, 20, 40, 1_000, 1_000]);
+    }
+
+    #[tokio::test]
+    async fn t_leases_retry_and_dead_letter() {
+        let (store, cfg) = (MemoryJobStore::default(), test_cfg());
+        store.enqueue(Job::new("old", "did:a", 0, &cfg, 0), false, &cfg).await.unwrap();
+        store.enqueue(Job::new("new", "did:b", 0, &cfg, 1), false, &cfg).await.unwrap();
+        store.enqueue(Job::new("urgent", "did:c", 5, &cfg, 2), false, &cfg).await.unwrap();
+
+        let mut claimed = Vec::new();
+        while let Some(job) = store.claim("w1", 2, &cfg).await.unwrap() {
+            claimed.push(job.job_id);
+        }
+        assert_eq!(claimed, ["urgent", "old", "new"]);
+
+        let hb = store.update("old", Box::new(|j| j.heartbeat("w2", 50, &test_cfg()))).await;
+        assert!(matches!(hb, Err(JobError::NotLeaseHolder)));
+        store.update("old", Box::new(|j| j.heartbeat("w1", 50, &test_cfg()))).await.unwrap();
+        store.update("urgent", Box::new(|j| j.complete("w1", 60))).await.unwrap();
+        let failed = store.update("new", Box::new(|j| j.fail("w1", "boom", 60, &test_cfg()))).await.unwrap();
+        assert_eq!((failed.status, failed.run_at, failed.last_error.as_deref()), (JobStatus::Failed, 70, Some("boom")));
+
+        // "old" was renewed until 150; "new" is due again at 70
+        assert_eq!(store.claim("w2", 69, &cfg).await.unwrap(), None);
+        let retry = store.claim("w2", 70, &cfg).await.unwrap().unwrap();
+        assert_eq!((retry.job_id.as_str(), retry.attempts), ("new", 2));
+
+        // "old" lapses; its one attempt is recorded and it backs off
+        assert_eq!(store.claim("w3", 150, &cfg).await.unwrap(), None);
+        let old = store.get("old").await.unwrap().unwrap();
+        assert_eq!((old.status, old.last_error.as_deref()), (JobStatus::Failed, Some("lease expired (worker w1)")));
+
+        let dead = store.update("new", Box::new(|j| j.fail("w2", "boom again", 80, &test_cfg()))).await.unwrap();
+        assert_eq!(dead.status, JobStatus::DeadLetter);
+        assert_eq!(store.list(Some(JobStatus::DeadLetter)).await.unwrap().len(), 1);
+        assert!(matches!(store.update("new", Box::new(|j| j.complete("w2", 90))).await, Err(JobError::WrongState(JobStatus::DeadLetter))));
+        assert!(matches!(store.update("nope", Box::new(|j| j.complete("w2", 90))).await, Err(JobError::NotFound)));
+    }
+
+    #[test]
+    fn t_released_jobs_requeue_without_spending_an_attempt() {
+        let cfg = test_cfg();
+        let mut job = Job::new("j", "did:a", 0, &cfg, 0);
+        assert!(job.try_lease("w1", 10, &cfg));
+        assert!(matches!(job.release("w2", 20), Err(JobError::NotLeaseHolder)));
+        job.release("w1", 20).unwrap();
+        assert_eq!((job.status, job.attempts, job.run_at, job.lease_owner.as_deref()), (JobStatus::Queued, 0, 20, None));
+        assert!(job.try_lease("w2", 20, &cfg));
+        assert_eq!(job.attempts, 1);
+    }
+
+    #[test]
+    fn t_only_the_live_lease_holder_closes_a_job() {
+        let cfg = test_cfg();
+        let mut job = Job::new("j", "did:a", 0, &cfg, 0);
+        assert!(job.try_lease("w1", 0, &cfg));
+        assert!(matches!(job.complete("w2", 50), Err(JobError::NotLeaseHolder)));
+        assert!(matches!(job.complete("w1", 100), Err(JobError::LeaseExpired)));
+        assert!(matches!(job.fail("w1", "late", 100, &cfg), Err(JobError::LeaseExpired)));
+        job.complete("w1", 99).unwrap();
+
+        assert!(matches!(external_worker(None), Err(JobError::InvalidWorker(_))));
+        assert!(matches!(external_worker(Some(" ".into())), Err(JobError::InvalidWorker(_))));
+        assert!(matches!(external_worker(Some(INLINE_WORKER.into())), Err(JobError::InvalidWorker(_))));
+        assert_eq!(external_worker(Some("w1".into())).unwrap(), "w1");
+    }
+
+    #[tokio::test]
+    async fn t_requests_for_one_did_coalesce() {
+        let (store, cfg) = (MemoryJobStore::default(), test_cfg());
+        let (first, outcome) = store.enqueue(Job::new("j1", "did:a", 0, &cfg, 0), false, &cfg).await.unwrap();
+        assert_eq!((first.job_id.as_str(), outcome), ("j1", EnqueueOutcome::Created));
+        // a lookup joins the queued background job and lifts its priority
+        let (same, outcome) = store.enqueue(Job::new("j2", "did:a", LOOKUP_PRIORITY, &cfg, 10), false, &cfg).await.unwrap();
+        assert_eq!((same.job_id.as_str(), same.priority, outcome), ("j1", LOOKUP_PRIORITY, EnqueueOutcome::Coalesced));
+        assert!(store.get("j2").await.unwrap().is_none());
+
+        store.claim("w", 10, &cfg).await.unwrap().unwrap();
+        store.update("j1", Box::new(|j| j.complete("w", 20))).await.unwrap();
+        let (last, outcome) = store.enqueue(Job::new("j3", "did:a", 0, &cfg, 49), false, &cfg).await.unwrap();
+        assert_eq!((last.job_id.as_str(), outcome), ("j1", EnqueueOutcome::CoolingDown));
+        let (next, outcome) = store.enqueue(Job::new("j4", "did:a", 0, &cfg, 50), false, &cfg).await.unwrap();
+        assert_eq!((next.job_id.as_str(), outcome), ("j4", EnqueueOutcome::Created));
+    }
+
+    /// What a lookup does with the store: enqueue, then what it may run.
+    async fn lookup(store: &MemoryJobStore, id: &str, did: &str, force: bool, now: i64) -> Enqueued {
+        let cfg = test_cfg();
+        let (job, outcome) = store.enqueue(Job::new(id, did, LOOKUP_PRIORITY, &cfg, now), force, &cfg).await.unwrap();
+        Enqueued { job: Some(job), outcome }
+    }
+
+    #[tokio::test]
+    async fn t_inline_failures_rerun_on_a_later_lookup() {
+        let (store, cfg) = (MemoryJobStore::default(), test_cfg());
+        let first = lookup(&store, "j1", "did:a", false, 0).await;
+        assert_eq!(first.runnable(0).map(|j| j.job_id.as_str()), Some("j1"));
+        store.update("j1", Box::new(|j| j.lease(INLINE_WORKER, 0, &test_cfg()))).await.unwrap();
+        store.update("j1", Box::new(|j| j.fail(INLINE_WORKER, "boom", 5, &test_cfg()))).await.unwrap();
+
+        // still backing off: coalesced, nothing to run yet
+        let early = lookup(&store, "j2", "did:a", false, 10).await;
+        assert_eq!((early.outcome, early.runnable(10).is_none()), (EnqueueOutcome::Coalesced, true));
+        // due again: the next lookup runs it
+        let retry = lookup(&store, "j3", "did:a", false, 15).await;
+        assert_eq!((retry.outcome, retry.runnable(15).map(|j| j.job_id.as_str())), (EnqueueOutcome::Coalesced, Some("j1")));
+        let rerun = store.update("j1", Box::new(|j| j.lease(INLINE_WORKER, 15, &test_cfg()))).await.unwrap();
+        assert_eq!(rerun.attempts, 2);
+
+        // a forced lookup replaces a job waiting out its backoff
+        store.enqueue(Job::new("f1", "did:f", 0, &cfg, 0), false, &cfg).await.unwrap();
+        store.update("f1", Box::new(|j| j.lease(INLINE_WORKER, 0, &test_cfg()))).await.unwrap();
+        store.update("f1", Box::new(|j| j.fail(INLINE_WORKER, "boom", 0, &test_cfg()))).await.unwrap();
+        assert_eq!(lookup(&store, "f2", "did:f", false, 5).await.outcome, EnqueueOutcome::Coalesced);
+        let forced = lookup(&store, "f3", "did:f", true, 5).await;
+        assert_eq!((forced.outcome, forced.runnable(5).map(|j| j.job_id.as_str())), (EnqueueOutcome::Replaced, Some("f3")));
+        assert_eq!(store.get("f1").await.unwrap().unwrap().status, JobStatus::Superseded);
+    }
+
+    #[tokio::test]
+    async fn t_lapsed_leases_are_replaced() {
+        let store = MemoryJobStore::default();
+        lookup(&store, "j1", "did:a", false, 0).await;
+        // the process running it dies holding the lease
+        store.update("j1", Box::new(|j| j.lease(INLINE_WORKER, 0, &test_cfg()))).await.unwrap();
+        let held = lookup(&store, "j2", "did:a", false, 50).await;
+        assert_eq!((held.outcome, held.runnable(50).is_none()), (EnqueueOutcome::Coalesced, true));
+
+        let replaced = lookup(&store, "j3", "did:a", false, 100).await;
+        assert_eq!((replaced.outcome, replaced.runnable(100).map(|j| j.job_id.as_str())), (EnqueueOutcome::Replaced, Some("j3")));
+        let old = store.get("j1").await.unwrap().unwrap();
+        assert_eq!((old.status, old.last_error.as_deref()), (JobStatus::Superseded, Some("superseded by job j3")));
+        assert!(matches!(store.update("j1", Box::new(|j| j.complete(INLINE_WORKER, 110))).await, Err(JobError::WrongState(JobStatus::Superseded))));
+    }
+
+    #[test]
+    fn t_progress_restarts_with_each_attempt() {
+        let cfg = test_cfg();
+        let mut job = Job::new("j", "did:a", 0, &cfg, 0);
+        assert!(job.try_lease("w", 0, &cfg));
+        let update = ProgressUpdate { posts_fetched: Some(25), error: Some("timeout".into()), ..Default::default() };
+        job.record_progress("w", &update, 1).unwrap();
+        job.fail("w", "boom", 2, &cfg).unwrap();
+        assert!(job.try_lease("w", 100, &cfg));
+        assert_eq!((job.started_at, job.progress.posts_fetched), (Some(0), 0));
+        assert_eq!(job.progress.errors, ["timeout", "boom"]);
+        job.fail("w", "boom", 101, &cfg).unwrap();
+        assert_eq!((job.status, job.finished_at), (JobStatus::DeadLetter, Some(101)));
+    }
+
+    #[tokio::test]
+    async fn t_watch_follows_a_job_to_completion() {
+        use futures_util::StreamExt;
+
+        let job = enqueue_score_job("did:watch", false, i32::MAX).await.unwrap().job.unwrap();
+        let id = job.job_id.clone();
+        let events = watch(job);
+        tokio::spawn(async move {
+            let job = claim_job("w").await.unwrap();
+            assert_eq!(job.job_id, id);
+            let update = ProgressUpdate { posts_fetched: Some(3), ..Default::default() };
+            report_progress(&id, "w", update).await.unwrap();
+            complete_job(&id, "w").await.unwrap();
+        });
+        let views: Vec<JobView> = events.collect().await;
+        let statuses: Vec<JobStatus> = views.iter().map(|v| v.status).collect();
+        assert_eq!(statuses, [JobStatus::Queued, JobStatus::Processing, JobStatus::Processing, JobStatus::Done]);
+        let done = views.last().unwrap();
+        assert_eq!((done.progress.posts_fetched, done.result.as_deref()), (3, Some("/v1/user/did:watch/scores")));
+    }
+}
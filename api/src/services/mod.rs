@@ -1,7 +1,11 @@
-pub mod atproto;
+pub mod decay;
+pub mod disputes;
+pub mod explain;
+pub mod fusion;
 pub mod jobs;
-pub mod gemini;
 pub mod graph;
+pub mod sqlite;
+pub mod transport;
 
 
 
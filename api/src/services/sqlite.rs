@@ -0,0 +1,619 @@
+//! Embedded SQLite backend for single-node deployments. One database file
+//! holds users, edges, content, score documents, disputes and the job queue, so
+//! state survives restarts without a JanusGraph cluster.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use trustsystem_core::{EvidenceRecord, Opinion, UserScores};
+
+use super::disputes::{Dispute, DisputeStatus};
+use super::graph::migrations::{self, Migration};
+use super::graph::{Content, EndorseEdge, ScoreSample, FollowEdge, GraphStore, InteractEdge, TrustEdge, TrustEdgeQuery, User};
+use super::jobs::{coalesce, EnqueueOutcome, Job, JobConfig, JobError, JobStatus, JobStore, JobUpdate};
+
+/// SQLite-backed [`GraphStore`] and [`JobStore`]. Queries run on the
+/// blocking pool behind a single connection.
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+static SHARED: Lazy<Arc<SqliteStore>> = Lazy::new(|| {
+    let path = std::env::var("SQLITE_PATH").unwrap_or_else(|_| "trustsystem.db".to_string());
+    let store = SqliteStore::open(&path).unwrap_or_else(|e| panic!("open sqlite store at {path}: {e:#}"));
+    tracing::info!(%path, "sqlite store ready");
+    Arc::new(store)
+});
+
+/// Process-wide store opened at `SQLITE_PATH` (default `trustsystem.db`),
+/// shared by the graph and job services.
+pub fn shared() -> Arc<SqliteStore> {
+    SHARED.clone()
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "busy_timeout", 5000)?;
+        migrate(&mut conn)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| anyhow!("sqlite connection lock poisoned"))?;
+            Ok(f(&mut conn)?)
+        })
+        .await?
+    }
+}
+
+const LEDGER: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at INTEGER NOT NULL
+)";
+
+fn applied_versions(conn: &Connection) -> rusqlite::Result<Vec<u32>> {
+    conn.execute_batch(LEDGER)?;
+    query_all(conn, "SELECT version FROM schema_migrations ORDER BY version", [], |r| r.get(0))
+}
+
+/// Runs the migration's DDL and records it in one transaction.
+fn apply(conn: &mut Connection, m: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute_batch(m.sql)?;
+    tx.execute(
+        "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+        params![m.version, m.name, chrono::Utc::now().timestamp_millis()],
+    )?;
+    tx.commit()
+}
+
+/// Applies pending graph migrations and returns the versions it ran.
+fn migrate(conn: &mut Connection) -> Result<Vec<u32>> {
+    let applied = applied_versions(conn)?;
+    let mut ran = Vec::new();
+    for m in migrations::pending(&applied) {
+        apply(conn, m)?;
+        tracing::info!(version = m.version, name = m.name, "applied sqlite migration");
+        ran.push(m.version);
+    }
+    Ok(ran)
+}
+
+fn user_row(r: &Row) -> rusqlite::Result<User> {
+    Ok(User { did: r.get(0)?, handle: r.get(1)?, created_at: r.get(2)? })
+}
+
+fn trust_row(r: &Row) -> rusqlite::Result<TrustEdge> {
+    Ok(TrustEdge {
+        from_did: r.get(0)?,
+        to_did: r.get(1)?,
+        scope: r.get(2)?,
+        opinion: Opinion::with_base_rate(r.get(3)?, r.get(4)?, r.get(5)?, r.get(6)?),
+        evidence_ref: r.get(7)?,
+        ts: r.get(8)?,
+    })
+}
+
+fn follow_row(r: &Row) -> rusqlite::Result<FollowEdge> {
+    Ok(FollowEdge { from_did: r.get(0)?, to_did: r.get(1)?, ts: r.get(2)? })
+}
+
+fn endorse_row(r: &Row) -> rusqlite::Result<EndorseEdge> {
+    Ok(EndorseEdge { from_did: r.get(0)?, to_did: r.get(1)?, domain: r.get(2)?, strength: r.get(3)?, ts: r.get(4)? })
+}
+
+fn interact_row(r: &Row) -> rusqlite::Result<InteractEdge> {
+    Ok(InteractEdge { from_did: r.get(0)?, to_did: r.get(1)?, weight: r.get(2)?, ts: r.get(3)? })
+}
+
+fn content_row(r: &Row) -> rusqlite::Result<Content> {
+    Ok(Content {
+        cid: r.get(0)?,
+        uri: r.get(1)?,
+        author_did: r.get(2)?,
+        domain: r.get(3)?,
+        classification: r.get(4)?,
+        created_at: r.get(5)?,
+    })
+}
+
+const JOB_COLUMNS: &str =
+    "job_id, did, status, priority, attempts, max_attempts, run_at, lease_owner, lease_expires_at, last_error, created_at, updated_at, \
+     progress, started_at, finished_at";
+
+fn job_row(r: &Row) -> rusqlite::Result<Job> {
+    let status: String = r.get(2)?;
+    let progress: String = r.get(12)?;
+    Ok(Job {
+        job_id: r.get(0)?,
+        did: r.get(1)?,
+        status: JobStatus::parse(&status).ok_or_else(|| rusqlite::Error::InvalidColumnType(2, status, rusqlite::types::Type::Text))?,
+        priority: r.get(3)?,
+        attempts: r.get(4)?,
+        max_attempts: r.get(5)?,
+        run_at: r.get(6)?,
+        lease_owner: r.get(7)?,
+        lease_expires_at: r.get(8)?,
+        last_error: r.get(9)?,
+        created_at: r.get(10)?,
+        updated_at: r.get(11)?,
+        progress: serde_json::from_str(&progress).map_err(|e| rusqlite::Error::FromSqlConversionFailure(12, rusqlite::types::Type::Text, e.into()))?,
+        started_at: r.get(13)?,
+        finished_at: r.get(14)?,
+    })
+}
+
+fn write_job(c: &Connection, job: &Job) -> rusqlite::Result<()> {
+    c.execute(
+        &format!("INSERT OR REPLACE INTO jobs ({JOB_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"),
+        params![
+            job.job_id, job.did, job.status.as_str(), job.priority, job.attempts, job.max_attempts,
+            job.run_at, job.lease_owner, job.lease_expires_at, job.last_error, job.created_at, job.updated_at,
+            serde_json::to_string(&job.progress).unwrap_or_else(|_| "{}".into()), job.started_at, job.finished_at
+        ],
+    )
+    .map(drop)
+}
+
+fn evidence_row(r: &Row) -> rusqlite::Result<EvidenceRecord> {
+    let refs: String = r.get(8)?;
+    Ok(EvidenceRecord {
+        did: r.get(0)?,
+        facet: r.get(1)?,
+        cid: r.get(2)?,
+        uri: r.get(3)?,
+        domain: r.get(4)?,
+        classifier: r.get(5)?,
+        label: r.get(6)?,
+        weight: r.get(7)?,
+        evidence_refs: serde_json::from_str(&refs).map_err(|e| rusqlite::Error::FromSqlConversionFailure(8, rusqlite::types::Type::Text, e.into()))?,
+        ts: r.get(9)?,
+    })
+}
+
+fn query_all<T>(conn: &Connection, sql: &str, params: impl rusqlite::Params, f: fn(&Row) -> rusqlite::Result<T>) -> rusqlite::Result<Vec<T>> {
+    conn.prepare_cached(sql)?.query_map(params, f)?.collect()
+}
+
+#[async_trait]
+impl GraphStore for SqliteStore {
+    fn backend(&self) -> &'static str { "sqlite" }
+
+    async fn schema_versions(&self) -> Result<Vec<u32>> {
+        self.call(|c| applied_versions(c)).await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        let m = *migration;
+        self.call(move |c| apply(c, &m)).await
+    }
+
+    async fn upsert_user(&self, did: &str, handle: Option<&str>) -> Result<User> {
+        let (did, handle) = (did.to_string(), handle.map(str::to_string));
+        let now = chrono::Utc::now().timestamp_millis();
+        self.call(move |c| {
+            c.execute(
+                "INSERT INTO users (did, handle, created_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (did) DO UPDATE SET handle = COALESCE(excluded.handle, users.handle)",
+                params![did, handle, now],
+            )?;
+            c.query_row("SELECT did, handle, created_at FROM users WHERE did = ?1", [&did], user_row)
+        })
+        .await
+    }
+
+    async fn get_user(&self, did: &str) -> Result<Option<User>> {
+        let did = did.to_string();
+        self.call(move |c| c.query_row("SELECT did, handle, created_at FROM users WHERE did = ?1", [&did], user_row).optional())
+            .await
+    }
+
+    async fn upsert_trust_edge(&self, edge: TrustEdge) -> Result<()> {
+        self.call(move |c| {
+            c.execute(
+                "INSERT OR REPLACE INTO trusts (from_did, to_did, scope, b, d, u, a, evidence_ref, ts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    edge.from_did, edge.to_did, edge.scope,
+                    edge.opinion.b, edge.opinion.d, edge.opinion.u, edge.opinion.a,
+                    edge.evidence_ref, edge.ts
+                ],
+            )
+            .map(drop)
+        })
+        .await
+    }
+
+    async fn trust_edges(&self, query: &TrustEdgeQuery) -> Result<Vec<TrustEdge>> {
+        let query = query.clone();
+        self.call(move |c| {
+            query_all(
+                c,
+                "SELECT from_did, to_did, scope, b, d, u, a, evidence_ref, ts FROM trusts
+                 WHERE (?1 IS NULL OR from_did = ?1) AND (?2 IS NULL OR to_did = ?2) AND (?3 IS NULL OR scope = ?3)
+                 ORDER BY from_did, to_did, scope",
+                params![query.from_did, query.to_did, query.scope],
+                trust_row,
+            )
+        })
+        .await
+    }
+
+    async fn upsert_follow(&self, from_did: &str, to_did: &str, ts: i64) -> Result<()> {
+        let (from, to) = (from_did.to_string(), to_did.to_string());
+        self.call(move |c| c.execute("INSERT OR REPLACE INTO follows (from_did, to_did, ts) VALUES (?1, ?2, ?3)", params![from, to, ts]).map(drop))
+            .await
+    }
+
+    async fn follows_from(&self, did: &str) -> Result<Vec<FollowEdge>> {
+        let did = did.to_string();
+        self.call(move |c| query_all(c, "SELECT from_did, to_did, ts FROM follows WHERE from_did = ?1 ORDER BY to_did", [did], follow_row))
+            .await
+    }
+
+    async fn followers_of(&self, did: &str) -> Result<Vec<FollowEdge>> {
+        let did = did.to_string();
+        self.call(move |c| query_all(c, "SELECT from_did, to_did, ts FROM follows WHERE to_did = ?1 ORDER BY from_did", [did], follow_row))
+            .await
+    }
+
+    async fn upsert_endorsement(&self, edge: EndorseEdge) -> Result<()> {
+        self.call(move |c| {
+            c.execute(
+                "INSERT OR REPLACE INTO endorsements (from_did, to_did, domain, strength, ts) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![edge.from_did, edge.to_did, edge.domain, edge.strength, edge.ts],
+            )
+            .map(drop)
+        })
+        .await
+    }
+
+    async fn endorsements_of(&self, did: &str) -> Result<Vec<EndorseEdge>> {
+        let did = did.to_string();
+        self.call(move |c| {
+            query_all(
+                c,
+                "SELECT from_did, to_did, domain, strength, ts FROM endorsements WHERE to_did = ?1 ORDER BY from_did, domain",
+                [did],
+                endorse_row,
+            )
+        })
+        .await
+    }
+
+    async fn upsert_interaction(&self, edge: InteractEdge) -> Result<()> {
+        self.call(move |c| {
+            c.execute(
+                "INSERT OR REPLACE INTO interactions (from_did, to_did, weight, ts) VALUES (?1, ?2, ?3, ?4)",
+                params![edge.from_did, edge.to_did, edge.weight, edge.ts],
+            )
+            .map(drop)
+        })
+        .await
+    }
+
+    async fn interactions_from(&self, did: &str) -> Result<Vec<InteractEdge>> {
+        let did = did.to_string();
+        self.call(move |c| {
+            query_all(c, "SELECT from_did, to_did, weight, ts FROM interactions WHERE from_did = ?1 ORDER BY to_did", [did], interact_row)
+        })
+        .await
+    }
+
+    async fn upsert_content(&self, content: Content) -> Result<()> {
+        self.call(move |c| {
+            c.execute(
+                "INSERT OR REPLACE INTO content (cid, uri, author_did, domain, classification, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![content.cid, content.uri, content.author_did, content.domain, content.classification, content.created_at],
+            )
+            .map(drop)
+        })
+        .await
+    }
+
+    async fn content_by_author(&self, did: &str) -> Result<Vec<Content>> {
+        let did = did.to_string();
+        self.call(move |c| {
+            query_all(
+                c,
+                "SELECT cid, uri, author_did, domain, classification, created_at FROM content WHERE author_did = ?1 ORDER BY cid",
+                [did],
+                content_row,
+            )
+        })
+        .await
+    }
+
+    async fn upsert_scores(&self, scores: UserScores) -> Result<()> {
+        let doc = serde_json::to_string(&scores)?;
+        let did = scores.did;
+        let now = chrono::Utc::now().timestamp_millis();
+        self.call(move |c| {
+            c.execute("INSERT OR REPLACE INTO scores (did, doc, updated_at) VALUES (?1, ?2, ?3)", params![did, doc, now]).map(drop)
+        })
+        .await
+    }
+
+    async fn get_scores(&self, did: &str) -> Result<Option<UserScores>> {
+        let did = did.to_string();
+        let doc: Option<String> =
+            self.call(move |c| c.query_row("SELECT doc FROM scores WHERE did = ?1", [did], |r| r.get(0)).optional()).await?;
+        Ok(doc.map(|d| serde_json::from_str(&d)).transpose()?)
+    }
+
+    async fn append_score_sample(&self, did: &str, facet: &str, sample: ScoreSample) -> Result<()> {
+        let (did, facet, doc) = (did.to_string(), facet.to_string(), serde_json::to_string(&sample)?);
+        self.call(move |c| {
+            c.execute("INSERT INTO score_history (did, facet, ts, sample) VALUES (?1, ?2, ?3, ?4)", params![did, facet, sample.ts, doc])
+                .map(drop)
+        })
+        .await
+    }
+
+    async fn score_history(&self, did: &str, facet: &str, from: i64, to: i64) -> Result<Vec<ScoreSample>> {
+        let (did, facet) = (did.to_string(), facet.to_string());
+        let docs: Vec<String> = self
+            .call(move |c| {
+                query_all(
+                    c,
+                    "SELECT sample FROM score_history WHERE did = ?1 AND facet = ?2 AND ts BETWEEN ?3 AND ?4 ORDER BY ts, rowid",
+                    params![did, facet, from, to],
+                    |r| r.get(0),
+                )
+            })
+            .await?;
+        Ok(docs.iter().map(|d| serde_json::from_str(d)).collect::<Result<_, _>>()?)
+    }
+
+    async fn upsert_evidence(&self, record: EvidenceRecord) -> Result<()> {
+        let refs = serde_json::to_string(&record.evidence_refs)?;
+        self.call(move |c| {
+            c.execute(
+                "INSERT OR REPLACE INTO evidence (did, facet, cid, uri, domain, classifier, label, weight, evidence_refs, ts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    record.did, record.facet, record.cid, record.uri, record.domain,
+                    record.classifier, record.label, record.weight, refs, record.ts
+                ],
+            )
+            .map(drop)
+        })
+        .await
+    }
+
+    async fn evidence_for(&self, did: &str, facet: Option<&str>) -> Result<Vec<EvidenceRecord>> {
+        let (did, facet) = (did.to_string(), facet.map(str::to_string));
+        self.call(move |c| {
+            query_all(
+                c,
+                "SELECT did, facet, cid, uri, domain, classifier, label, weight, evidence_refs, ts FROM evidence
+                 WHERE did = ?1 AND (?2 IS NULL OR facet = ?2) ORDER BY ts DESC, cid",
+                params![did, facet],
+                evidence_row,
+            )
+        })
+        .await
+    }
+
+    async fn prune_score_history(&self, did: &str, before: i64) -> Result<()> {
+        let did = did.to_string();
+        self.call(move |c| c.execute("DELETE FROM score_history WHERE did = ?1 AND ts < ?2", params![did, before]).map(drop)).await
+    }
+
+    async fn upsert_dispute(&self, dispute: Dispute) -> Result<()> {
+        let doc = serde_json::to_string(&dispute)?;
+        self.call(move |c| {
+            c.execute(
+                "INSERT OR REPLACE INTO disputes (id, did, status, doc, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![dispute.id, dispute.did, dispute.status.as_str(), doc, dispute.created_at],
+            )
+            .map(drop)
+        })
+        .await
+    }
+
+    async fn get_dispute(&self, id: &str) -> Result<Option<Dispute>> {
+        let id = id.to_string();
+        let doc: Option<String> =
+            self.call(move |c| c.query_row("SELECT doc FROM disputes WHERE id = ?1", [id], |r| r.get(0)).optional()).await?;
+        Ok(doc.map(|d| serde_json::from_str(&d)).transpose()?)
+    }
+
+    async fn disputes(&self, did: Option<&str>, status: Option<DisputeStatus>) -> Result<Vec<Dispute>> {
+        let (did, status) = (did.map(str::to_string), status.map(DisputeStatus::as_str));
+        let docs: Vec<String> = self
+            .call(move |c| {
+                query_all(
+                    c,
+                    "SELECT doc FROM disputes WHERE (?1 IS NULL OR did = ?1) AND (?2 IS NULL OR status = ?2) ORDER BY created_at, id",
+                    params![did, status],
+                    |r| r.get(0),
+                )
+            })
+            .await?;
+        Ok(docs.iter().map(|d| serde_json::from_str(d)).collect::<Result<_, _>>()?)
+    }
+}
+
+#[async_trait]
+impl JobStore for SqliteStore {
+    async fn enqueue(&self, job: Job, force: bool, cfg: &JobConfig) -> Result<(Job, EnqueueOutcome)> {
+        let cfg = cfg.clone();
+        self.call(move |c| {
+            let tx = c.transaction()?;
+            let latest = tx
+                .query_row(
+                    &format!("SELECT {JOB_COLUMNS} FROM jobs WHERE did = ?1 ORDER BY created_at DESC, job_id DESC LIMIT 1"),
+                    [&job.did],
+                    job_row,
+                )
+                .optional()?;
+            let (job, outcome, superseded) = coalesce(latest, job, force, &cfg);
+            if let Some(old) = &superseded {
+                write_job(&tx, old)?;
+            }
+            write_job(&tx, &job)?;
+            tx.commit()?;
+            Ok((job, outcome))
+        })
+        .await
+    }
+
+    async fn get(&self, job_id: &str) -> Result<Option<Job>> {
+        let job_id = job_id.to_string();
+        self.call(move |c| c.query_row(&format!("SELECT {JOB_COLUMNS} FROM jobs WHERE job_id = ?1"), [job_id], job_row).optional())
+            .await
+    }
+
+    async fn list(&self, status: Option<JobStatus>) -> Result<Vec<Job>> {
+        let status = status.map(JobStatus::as_str);
+        self.call(move |c| {
+            query_all(
+                c,
+                &format!("SELECT {JOB_COLUMNS} FROM jobs WHERE ?1 IS NULL OR status = ?1 ORDER BY priority DESC, created_at, job_id"),
+                [status],
+                job_row,
+            )
+        })
+        .await
+    }
+
+    async fn claim(&self, worker: &str, now: i64, cfg: &JobConfig) -> Result<Option<Job>> {
+        let (worker, cfg) = (worker.to_string(), cfg.clone());
+        self.call(move |c| {
+            let tx = c.transaction()?;
+            let due = query_all(
+                &tx,
+                &format!(
+                    "SELECT {JOB_COLUMNS} FROM jobs
+                     WHERE (status IN ('queued', 'failed') AND run_at <= ?1)
+                        OR (status = 'processing' AND (lease_expires_at IS NULL OR lease_expires_at <= ?1))
+                     ORDER BY priority DESC, created_at, job_id"
+                ),
+                [now],
+                job_row,
+            )?;
+            let mut leased = None;
+            for mut job in due {
+                let ok = job.try_lease(&worker, now, &cfg);
+                write_job(&tx, &job)?;
+                if ok {
+                    leased = Some(job);
+                    break;
+                }
+            }
+            tx.commit()?;
+            Ok(leased)
+        })
+        .await
+    }
+
+    async fn update(&self, job_id: &str, f: JobUpdate) -> Result<Job, JobError> {
+        let job_id = job_id.to_string();
+        self.call(move |c| {
+            let tx = c.transaction()?;
+            let Some(mut job) = tx.query_row(&format!("SELECT {JOB_COLUMNS} FROM jobs WHERE job_id = ?1"), [&job_id], job_row).optional()? else {
+                return Ok(Err(JobError::NotFound));
+            };
+            if let Err(e) = f(&mut job) {
+                return Ok(Err(e));
+            }
+            write_job(&tx, &job)?;
+            tx.commit()?;
+            Ok(Ok(job))
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trustsystem_core::OpinionRecord;
+
+    #[tokio::test]
+    async fn t_state_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("trustsystem-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("store.db");
+        let cfg = JobConfig::default();
+        {
+            let store = SqliteStore::open(&path).unwrap();
+            store.upsert_user("did:a", Some("a.test")).await.unwrap();
+            store
+                .upsert_trust_edge(TrustEdge {
+                    from_did: "did:a".into(),
+                    to_did: "did:b".into(),
+                    scope: "general".into(),
+                    opinion: Opinion::with_base_rate(0.5, 0.25, 0.25, 0.4),
+                    evidence_ref: None,
+                    ts: 1,
+                })
+                .await
+                .unwrap();
+            store.upsert_scores(UserScores { bot_prob: 0.5, ..UserScores::vacuous("did:a") }).await.unwrap();
+            store.enqueue(Job::new("job-1", "did:a", 0, &cfg, 1), false, &cfg).await.unwrap();
+            store.enqueue(Job::new("job-2", "did:b", 0, &cfg, 2), false, &cfg).await.unwrap();
+            assert_eq!(store.claim("w1", 10, &cfg).await.unwrap().unwrap().job_id, "job-1");
+        }
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.get_user("did:a").await.unwrap().unwrap().handle.as_deref(), Some("a.test"));
+        let handle = store.upsert_user("did:a", None).await.unwrap().handle;
+        assert_eq!(handle.as_deref(), Some("a.test"));
+        let edges = store.trust_edges(&TrustEdgeQuery { to_did: Some("did:b".into()), ..Default::default() }).await.unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].opinion.a, 0.4);
+        assert_eq!(store.get_scores("did:a").await.unwrap().unwrap().bot_prob, 0.5);
+        let record = OpinionRecord::from_evidence(3.0, 1.0, 2.0);
+        for ts in [30, 10, 20] {
+            store.append_score_sample("did:a", "accuracy", ScoreSample { ts, record }).await.unwrap();
+        }
+        store.prune_score_history("did:a", 15).await.unwrap();
+        let history = store.score_history("did:a", "accuracy", 0, 25).await.unwrap();
+        assert_eq!(history.iter().map(|s| (s.ts, s.record.alpha)).collect::<Vec<_>>(), vec![(20, Some(3.0))]);
+        let job = store.get("job-1").await.unwrap().unwrap();
+        assert_eq!((job.status, job.lease_owner.as_deref()), (JobStatus::Processing, Some("w1")));
+        assert_eq!(store.claim("w2", 10, &cfg).await.unwrap().unwrap().job_id, "job-2");
+        assert_eq!(store.claim("w2", 10, &cfg).await.unwrap(), None);
+        // both leases lapse: recorded as failed attempts, not re-leased yet
+        assert_eq!(store.claim("w3", 10 + cfg.lease_ms, &cfg).await.unwrap(), None);
+        let failed = store.list(Some(JobStatus::Failed)).await.unwrap();
+        assert_eq!(failed.iter().map(|j| j.job_id.as_str()).collect::<Vec<_>>(), ["job-1", "job-2"]);
+        let done = store.update("job-2", Box::new(|j| j.complete("w2", 20))).await;
+        assert!(matches!(done, Err(JobError::WrongState(JobStatus::Failed))));
+        assert!(matches!(store.update("job-9", Box::new(|_| Ok(()))).await, Err(JobError::NotFound)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn t_migrations_run_once() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mut conn = store.conn.lock().unwrap();
+        assert!(migrate(&mut conn).unwrap().is_empty());
+        let count: u32 = conn.query_row("SELECT COUNT(*) FROM schema_migrations", [], |r| r.get(0)).unwrap();
+        assert_eq!(count as usize, migrations::MIGRATIONS.len());
+    }
+}
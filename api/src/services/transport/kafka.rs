@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use serde::Serialize;
+
+use super::JobTransport;
+use crate::services::jobs::Job;
+
+/// What a pushed delivery carries; the job store stays the source of truth,
+/// so workers lease the job by id before running it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobMessage {
+    pub job_id: String,
+    pub did: String,
+    pub priority: i32,
+    pub attempts: u32,
+}
+
+impl From<&Job> for JobMessage {
+    fn from(job: &Job) -> Self {
+        Self { job_id: job.job_id.clone(), did: job.did.clone(), priority: job.priority, attempts: job.attempts }
+    }
+}
+
+pub const DEFAULT_JOBS_TOPIC: &str = "score.jobs";
+
+/// Produces jobs to a Kafka-compatible topic, keyed by DID so a DID's jobs
+/// stay on one partition. Workers in one consumer group share the work.
+pub struct KafkaTransport {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl KafkaTransport {
+    pub fn new(brokers: &str, topic: &str) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+        Ok(Self { producer, topic: topic.to_string() })
+    }
+
+    /// `KAFKA_BROKERS` and `KAFKA_JOBS_TOPIC` (default `score.jobs`).
+    pub fn from_env() -> Result<Self> {
+        let brokers = std::env::var("KAFKA_BROKERS").ok().filter(|b| !b.is_empty()).ok_or_else(|| anyhow!("KAFKA_BROKERS is not set"))?;
+        let topic = std::env::var("KAFKA_JOBS_TOPIC").unwrap_or_else(|_| DEFAULT_JOBS_TOPIC.to_string());
+        Self::new(&brokers, &topic)
+    }
+}
+
+#[async_trait]
+impl JobTransport for KafkaTransport {
+    fn name(&self) -> &'static str { "kafka" }
+
+    fn pushes(&self) -> bool { true }
+
+    async fn dispatch(&self, job: &Job) -> Result<()> {
+        let payload = serde_json::to_vec(&JobMessage::from(job))?;
+        let record = FutureRecord::to(&self.topic).key(&job.did).payload(&payload);
+        self.producer
+            .send(record, Timeout::After(Duration::from_secs(5)))
+            .await
+            .map_err(|(e, _)| anyhow!("produce to {}: {e}", self.topic))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jobs::JobConfig;
+    use rdkafka::consumer::{Consumer, StreamConsumer};
+    use rdkafka::Message;
+
+    #[tokio::test]
+    #[ignore = "needs Kafka or Redpanda; set KAFKA_TEST_BROKERS"]
+    async fn t_kafka_round_trip() {
+        let Ok(brokers) = std::env::var("KAFKA_TEST_BROKERS") else { return };
+        let run = uuid::Uuid::new_v4().simple().to_string();
+        let topic = format!("score.jobs.test.{run}");
+        let transport = KafkaTransport::new(&brokers, &topic).unwrap();
+        let job = Job::new(&run, "did:test:kafka", 3, &JobConfig::default(), 0);
+        transport.dispatch(&job).await.unwrap();
+
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &brokers)
+            .set("group.id", format!("test-{run}"))
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .unwrap();
+        consumer.subscribe(&[&topic]).unwrap();
+        let msg = tokio::time::timeout(Duration::from_secs(30), consumer.recv()).await.unwrap().unwrap();
+        assert_eq!(msg.key(), Some("did:test:kafka".as_bytes()));
+        let body: serde_json::Value = serde_json::from_slice(msg.payload().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({"jobId": run, "did": "did:test:kafka", "priority": 3, "attempts": 0}));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+
+use crate::services::jobs::{self, Job, JobStatus};
+
+#[cfg(feature = "kafka")]
+mod kafka;
+
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaTransport;
+
+/// How runnable jobs reach workers.
+#[async_trait]
+pub trait JobTransport: Send + Sync {
+    /// Short transport name for logs.
+    fn name(&self) -> &'static str;
+    /// Whether jobs are pushed through [`JobTransport::dispatch`]; polling
+    /// workers find them through `/internal/jobs/next` instead.
+    fn pushes(&self) -> bool;
+    async fn dispatch(&self, job: &Job) -> Result<()>;
+}
+
+/// Workers poll `/internal/jobs/next`; nothing is pushed.
+pub struct PollTransport;
+
+#[async_trait]
+impl JobTransport for PollTransport {
+    fn name(&self) -> &'static str { "poll" }
+
+    fn pushes(&self) -> bool { false }
+
+    async fn dispatch(&self, _job: &Job) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Selected by `JOB_TRANSPORT`: `kafka` produces to `KAFKA_JOBS_TOPIC` on
+/// `KAFKA_BROKERS` (needs the `kafka` feature); anything else is `poll`.
+static TRANSPORT: Lazy<Arc<dyn JobTransport>> = Lazy::new(|| {
+    let transport: Arc<dyn JobTransport> = match std::env::var("JOB_TRANSPORT").unwrap_or_default().as_str() {
+        #[cfg(feature = "kafka")]
+        "kafka" => match KafkaTransport::from_env() {
+            Ok(t) => Arc::new(t),
+            Err(e) => {
+                tracing::error!(error = %e, "kafka transport unavailable; workers must poll");
+                Arc::new(PollTransport)
+            }
+        },
+        #[cfg(not(feature = "kafka"))]
+        "kafka" => {
+            tracing::error!("built without the kafka feature; workers must poll");
+            Arc::new(PollTransport)
+        }
+        _ => Arc::new(PollTransport),
+    };
+    tracing::info!(transport = transport.name(), "job transport ready");
+    transport
+});
+
+pub fn transport() -> Arc<dyn JobTransport> {
+    TRANSPORT.clone()
+}
+
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Nudges the dispatcher so a new job goes out without waiting a tick.
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Which run of each job has been pushed. A job is pushed again once it is
+/// runnable under a different (attempts, run_at), i.e. after a failure's
+/// backoff, a lapsed lease or a release.
+#[derive(Debug, Default)]
+pub struct Dispatched {
+    sent: HashMap<String, (u32, i64)>,
+}
+
+impl Dispatched {
+    pub fn due(&self, job: &Job, now: i64) -> bool {
+        job.status != JobStatus::Processing
+            && job.claimable(now)
+            && self.sent.get(&job.job_id) != Some(&(job.attempts, job.run_at))
+    }
+
+    pub fn mark(&mut self, job: &Job) {
+        self.sent.insert(job.job_id.clone(), (job.attempts, job.run_at));
+    }
+
+    /// Forgets jobs that are no longer waiting or running.
+    pub fn retain(&mut self, live: &[Job]) {
+        self.sent.retain(|id, _| live.iter().any(|j| &j.job_id == id));
+    }
+}
+
+/// How often the dispatcher rescans the queue (`JOB_DISPATCH_MS`).
+fn dispatch_interval() -> Duration {
+    let ms = std::env::var("JOB_DISPATCH_MS").ok().and_then(|v| v.parse().ok()).filter(|&ms| ms > 0).unwrap_or(1_000);
+    Duration::from_millis(ms)
+}
+
+/// Pushes runnable jobs through the transport and expires lapsed leases,
+/// which no poller is around to notice. Only runs for pushing transports.
+/// Restarting forgets what was sent, so jobs may go out twice; leasing by
+/// id lets only one worker run them.
+pub async fn run_dispatcher() {
+    let transport = transport();
+    if !transport.pushes() {
+        return;
+    }
+    let store = jobs::job_store();
+    let every = dispatch_interval();
+    let mut dispatched = Dispatched::default();
+    loop {
+        tokio::select! {
+            _ = WAKE.notified() => {}
+            _ = tokio::time::sleep(every) => {}
+        }
+        let mut live = Vec::new();
+        for status in [JobStatus::Queued, JobStatus::Failed, JobStatus::Processing] {
+            match store.list(Some(status)).await {
+                Ok(jobs) => live.extend(jobs),
+                Err(e) => tracing::warn!(error = %e, "dispatcher could not list jobs"),
+            }
+        }
+        live.sort_by(jobs::queue_order);
+        let now = chrono::Utc::now().timestamp_millis();
+        for job in live.iter_mut() {
+            if job.status == JobStatus::Processing && job.claimable(now) {
+                match jobs::expire_lease(&job.job_id).await {
+                    Ok(expired) => *job = expired,
+                    Err(e) => tracing::debug!(job_id = %job.job_id, reason = %e, "lease not expired"),
+                }
+            }
+            if !dispatched.due(job, now) {
+                continue;
+            }
+            match transport.dispatch(job).await {
+                Ok(()) => dispatched.mark(job),
+                Err(e) => tracing::warn!(job_id = %job.job_id, error = %e, "job dispatch failed"),
+            }
+        }
+        dispatched.retain(&live);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jobs::JobConfig;
+
+    #[test]
+    fn t_jobs_go_out_once_per_run() {
+        let cfg = JobConfig { lease_ms: 100, backoff_ms: 10, ..JobConfig::default() };
+        let mut job = Job::new("j", "did:a", 0, &cfg, 0);
+        let mut dispatched = Dispatched::default();
+        assert!(dispatched.due(&job, 0));
+        dispatched.mark(&job);
+        assert!(!dispatched.due(&job, 5));
+
+        assert!(job.try_lease("w1", 5, &cfg));
+        assert!(!dispatched.due(&job, 5));
+        job.fail("w1", "boom", 20, &cfg).unwrap();
+        // backing off until 30, then due again
+        assert!(!dispatched.due(&job, 29));
+        assert!(dispatched.due(&job, 30));
+        dispatched.mark(&job);
+
+        assert!(job.try_lease("w2", 30, &cfg));
+        job.release("w2", 40).unwrap();
+        assert!(dispatched.due(&job, 40));
+
+        dispatched.retain(&[]);
+        assert!(dispatched.sent.is_empty());
+    }
+}
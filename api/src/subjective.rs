@@ -1,11 +1,8 @@
 pub use trustsystem_core::{
     Opinion,
-    evidence_to_opinion,
-    discounting,
-    consensus_fusion,
-    time_decay,
-    hop_decay,
+    DEFAULT_PRIOR_WEIGHT,
+    Normalization,
+    UserScores,
+    TnaConfig,
+    Facets,
 };
-
-
-
@@ -0,0 +1,324 @@
+//! Conditional reasoning: deduction and abduction for binomial opinions.
+//!
+//! Deduction derives `ω_{y‖x}` from an opinion about the antecedent `x` and
+//! the conditionals `ω_{y|x}`, `ω_{y|¬x}` (Jøsang, Pope & Daniel,
+//! "Conditional deduction under uncertainty", 2005). Abduction goes the
+//! other way: it inverts the conditionals and deduces `ω_{x‖y}` from an
+//! opinion about `y` (Jøsang, "Subjective Logic", 2016, ch. 10).
+//!
+//! E.g. with `x` = "accurate on medicine" and `y` = "accurate on biology",
+//! a user's medicine opinion plus the two conditionals yields a derived
+//! biology opinion.
+
+use crate::Opinion;
+
+const EPS: f64 = 1e-12;
+
+/// Deduces `ω_{y‖x}`. The consequent's base rate `a_y` is taken from
+/// `y_given_x`; both conditionals are expected to share it.
+pub fn deduction(x: Opinion, y_given_x: Opinion, y_given_not_x: Opinion) -> Opinion {
+    let (yx, ynx) = (y_given_x, y_given_not_x);
+    let ax = x.a;
+    let ay = yx.a;
+
+    // image of x's opinion in the sub-triangle spanned by the conditionals
+    let bi = x.b * yx.b + x.d * ynx.b + x.u * (yx.b * ax + ynx.b * (1.0 - ax));
+    let di = x.b * yx.d + x.d * ynx.d + x.u * (yx.d * ax + ynx.d * (1.0 - ax));
+    let ui = x.b * yx.u + x.d * ynx.u + x.u * (yx.u * ax + ynx.u * (1.0 - ax));
+
+    let k = deduction_k(x, yx, ynx, bi, di);
+    Opinion { b: bi - ay * k, d: di - (1.0 - ay) * k, u: ui + k, a: ay }
+}
+
+/// Uncertainty adjustment `K` that moves the image so a vacuous
+/// antecedent lands on the sub-triangle apex.
+fn deduction_k(x: Opinion, yx: Opinion, ynx: Opinion, bi: f64, di: f64) -> f64 {
+    let ax = x.a;
+    let ay = yx.a;
+    let px = x.projected_probability();
+    // projected probability of y given a vacuous antecedent
+    let py_vac = yx.b * ax + ynx.b * (1.0 - ax) + ay * (yx.u * ax + ynx.u * (1.0 - ax));
+    let lo = x.b + ax * x.u;
+    let hi = x.d + (1.0 - ax) * x.u;
+
+    let ratio = |num: f64, den: f64| if den.abs() < EPS { 0.0 } else { num / den };
+
+    if (yx.b > ynx.b && yx.d > ynx.d) || (yx.b <= ynx.b && yx.d <= ynx.d) {
+        // case I
+        0.0
+    } else if yx.b > ynx.b {
+        // case II: d_{y|x} <= d_{y|¬x}
+        if py_vac <= ynx.b + ay * (1.0 - ynx.b - yx.d) {
+            if px <= ax {
+                ratio(ax * x.u * (bi - ynx.b), lo * ay)
+            } else {
+                ratio(ax * x.u * (di - yx.d) * (yx.b - ynx.b), hi * ay * (ynx.d - yx.d))
+            }
+        } else if px <= ax {
+            ratio((1.0 - ax) * x.u * (bi - ynx.b) * (ynx.d - yx.d), lo * (1.0 - ay) * (yx.b - ynx.b))
+        } else {
+            ratio((1.0 - ax) * x.u * (di - yx.d), hi * (1.0 - ay))
+        }
+    } else {
+        // case III: b_{y|x} <= b_{y|¬x}, d_{y|x} > d_{y|¬x}
+        if py_vac <= yx.b + ay * (1.0 - yx.b - ynx.d) {
+            if px <= ax {
+                ratio((1.0 - ax) * x.u * (di - ynx.d) * (ynx.b - yx.b), lo * ay * (yx.d - ynx.d))
+            } else {
+                ratio((1.0 - ax) * x.u * (bi - yx.b), hi * ay)
+            }
+        } else if px <= ax {
+            ratio(ax * x.u * (di - ynx.d), lo * (1.0 - ay))
+        } else {
+            ratio(ax * x.u * (bi - yx.b) * (yx.d - ynx.d), hi * (1.0 - ay) * (ynx.b - yx.b))
+        }
+    }
+}
+
+/// Inverts `ω_{y|x}`, `ω_{y|¬x}` into `(ω_{x|y}, ω_{x|¬y})` given the base
+/// rate `a_x` of the antecedent. Projected probabilities follow Bayes'
+/// theorem; uncertainty grows with the conditionals' own uncertainty and
+/// with their irrelevance `1 - |P(y|x) - P(y|¬x)|`.
+pub fn invert_conditionals(y_given_x: Opinion, y_given_not_x: Opinion, ax: f64) -> (Opinion, Opinion) {
+    let p_yx = y_given_x.projected_probability();
+    let p_ynx = y_given_not_x.projected_probability();
+    let p_y = ax * p_yx + (1.0 - ax) * p_ynx;
+
+    let p_xy = if p_y < EPS { ax } else { ax * p_yx / p_y };
+    let p_xny = if 1.0 - p_y < EPS { ax } else { ax * (1.0 - p_yx) / (1.0 - p_y) };
+
+    let u_sum = y_given_x.u + y_given_not_x.u;
+    let (uw_yx, uw_ynx) = if u_sum < EPS {
+        (0.0, 0.0)
+    } else {
+        (y_given_x.u * y_given_x.u / u_sum, y_given_not_x.u * y_given_not_x.u / u_sum)
+    };
+    let irrelevance = 1.0 - (p_yx - p_ynx).abs();
+    let coproduct = |a: f64, b: f64| a + b - a * b;
+    let relative_u = coproduct(coproduct(uw_yx, uw_ynx), irrelevance);
+
+    let build = |p: f64| {
+        let u_max = if p < ax { p / ax } else if ax < 1.0 { (1.0 - p) / (1.0 - ax) } else { 0.0 };
+        let u = u_max * relative_u;
+        let b = p - ax * u;
+        Opinion { b, d: 1.0 - b - u, u, a: ax }
+    };
+    (build(p_xy), build(p_xny))
+}
+
+/// Abduces `ω_{x‖y}` from an opinion about `y` and the conditionals
+/// `ω_{y|x}`, `ω_{y|¬x}`, with `ax` the base rate of `x`.
+pub fn abduction(y: Opinion, y_given_x: Opinion, y_given_not_x: Opinion, ax: f64) -> Opinion {
+    let (x_given_y, x_given_not_y) = invert_conditionals(y_given_x, y_given_not_x, ax);
+    deduction(y, x_given_y, x_given_not_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn assert_op(o: Opinion, b: f64, d: f64, u: f64) {
+        assert_relative_eq!(o.b, b, epsilon=1e-9);
+        assert_relative_eq!(o.d, d, epsilon=1e-9);
+        assert_relative_eq!(o.u, u, epsilon=1e-9);
+    }
+
+    fn assert_valid(o: Opinion) {
+        for v in [o.b, o.d, o.u] {
+            assert!((-1e-9..=1.0 + 1e-9).contains(&v), "{o:?}");
+        }
+        assert_relative_eq!(o.b + o.d + o.u, 1.0, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_deduction_identity_conditionals() {
+        // y ≡ x: deduction must reproduce the antecedent (cases II.A.1/II.A.2)
+        let yx = Opinion::with_base_rate(1.0, 0.0, 0.0, 0.4);
+        let ynx = Opinion::with_base_rate(0.0, 1.0, 0.0, 0.4);
+        assert_op(deduction(Opinion::with_base_rate(0.3, 0.2, 0.5, 0.4), yx, ynx), 0.3, 0.2, 0.5);
+        assert_op(deduction(Opinion::with_base_rate(0.1, 0.5, 0.4, 0.4), yx, ynx), 0.1, 0.5, 0.4);
+    }
+
+    #[test]
+    fn t_deduction_negation_conditionals() {
+        // y ≡ ¬x: belief and disbelief swap (case III)
+        let yx = Opinion::with_base_rate(0.0, 1.0, 0.0, 0.6);
+        let ynx = Opinion::with_base_rate(1.0, 0.0, 0.0, 0.6);
+        assert_op(deduction(Opinion::with_base_rate(0.3, 0.2, 0.5, 0.4), yx, ynx), 0.2, 0.3, 0.5);
+        assert_op(deduction(Opinion::with_base_rate(0.1, 0.5, 0.4, 0.4), yx, ynx), 0.5, 0.1, 0.4);
+    }
+
+    #[test]
+    fn t_deduction_dogmatic_antecedent() {
+        let yx = Opinion::with_base_rate(0.72, 0.18, 0.10, 0.5);
+        let ynx = Opinion::with_base_rate(0.13, 0.57, 0.30, 0.5);
+        let t = deduction(Opinion::with_base_rate(1.0, 0.0, 0.0, 0.5), yx, ynx);
+        assert_op(t, 0.72, 0.18, 0.10);
+        let f = deduction(Opinion::with_base_rate(0.0, 1.0, 0.0, 0.5), yx, ynx);
+        assert_op(f, 0.13, 0.57, 0.30);
+    }
+
+    #[test]
+    fn t_deduction_case_i() {
+        // both belief and disbelief higher given x: K = 0, plain image
+        let x = Opinion::with_base_rate(0.5, 0.2, 0.3, 0.5);
+        let yx = Opinion::with_base_rate(0.5, 0.4, 0.1, 0.5);
+        let ynx = Opinion::with_base_rate(0.3, 0.2, 0.5, 0.5);
+        assert_op(deduction(x, yx, ynx), 0.43, 0.33, 0.24);
+    }
+
+    // The vectors below are worked by hand, in exact fractions, from the
+    // case formulas of Jøsang, Pope & Daniel (2005). A vacuous antecedent
+    // lands on the sub-triangle apex: the most uncertain opinion with the
+    // vacuous projected probability whose belief (case A) or disbelief
+    // (case B) doesn't drop below the lower of the two conditionals'.
+
+    #[test]
+    fn t_deduction_case_ii() {
+        // b_{y|x} > b_{y|¬x}, d_{y|x} < d_{y|¬x}
+        let cond = |ay| (Opinion::with_base_rate(0.6, 0.1, 0.3, ay), Opinion::with_base_rate(0.2, 0.4, 0.4, ay));
+
+        // II.A: P(y‖x̂) = 0.68 <= b_{y|¬x} + a_y(1 - b_{y|¬x} - d_{y|x}) = 0.76
+        let (yx, ynx) = cond(0.8);
+        // apex keeps b_{y|¬x}; K = 1/4
+        assert_op(deduction(Opinion::with_base_rate(0.0, 0.0, 1.0, 0.5), yx, ynx), 0.2, 0.2, 0.6);
+        // II.A.1, P(x) = 0.45 <= a_x: K = 1/8
+        assert_op(deduction(Opinion::with_base_rate(0.2, 0.3, 0.5, 0.5), yx, ynx), 0.28, 0.24, 0.48);
+        // II.A.2, P(x) = 0.7 > a_x: K = 1/10
+        assert_op(deduction(Opinion::with_base_rate(0.5, 0.1, 0.4, 0.5), yx, ynx), 0.40, 0.17, 0.43);
+
+        // II.B: P(y‖x̂) = 0.575 > 0.55
+        let (yx, ynx) = cond(0.5);
+        // apex keeps d_{y|x}; K = 3/10
+        assert_op(deduction(Opinion::with_base_rate(0.0, 0.0, 1.0, 0.5), yx, ynx), 0.25, 0.10, 0.65);
+        // II.B.1: K = 3/20
+        assert_op(deduction(Opinion::with_base_rate(0.2, 0.3, 0.5, 0.5), yx, ynx), 0.305, 0.19, 0.505);
+        // II.B.2: K = 3/25
+        assert_op(deduction(Opinion::with_base_rate(0.5, 0.1, 0.4, 0.5), yx, ynx), 0.42, 0.13, 0.45);
+    }
+
+    #[test]
+    fn t_deduction_case_iii() {
+        // b_{y|x} <= b_{y|¬x}, d_{y|x} > d_{y|¬x}
+        let cond = |ay| (Opinion::with_base_rate(0.2, 0.4, 0.4, ay), Opinion::with_base_rate(0.6, 0.1, 0.3, ay));
+
+        // III.A: P(y‖x̂) = 0.68 <= b_{y|x} + a_y(1 - b_{y|x} - d_{y|¬x}) = 0.76
+        let (yx, ynx) = cond(0.8);
+        // apex keeps b_{y|x}; K = 1/4
+        assert_op(deduction(Opinion::with_base_rate(0.0, 0.0, 1.0, 0.5), yx, ynx), 0.2, 0.2, 0.6);
+        // III.A.1: K = 1/8
+        assert_op(deduction(Opinion::with_base_rate(0.2, 0.3, 0.5, 0.5), yx, ynx), 0.32, 0.21, 0.47);
+        // III.A.2: K = 1/10
+        assert_op(deduction(Opinion::with_base_rate(0.5, 0.1, 0.4, 0.5), yx, ynx), 0.24, 0.29, 0.47);
+
+        // III.B: P(y‖x̂) = 0.47 > 0.34
+        let (yx, ynx) = cond(0.2);
+        // apex keeps d_{y|¬x}; K = 3/16
+        assert_op(deduction(Opinion::with_base_rate(0.0, 0.0, 1.0, 0.5), yx, ynx), 0.3625, 0.10, 0.5375);
+        // III.B.1: K = 3/32
+        assert_op(deduction(Opinion::with_base_rate(0.2, 0.3, 0.5, 0.5), yx, ynx), 0.40125, 0.16, 0.43875);
+        // III.B.2: K = 3/40
+        assert_op(deduction(Opinion::with_base_rate(0.5, 0.1, 0.4, 0.5), yx, ynx), 0.305, 0.25, 0.445);
+    }
+
+    #[test]
+    fn t_deduction_negated_antecedent_swaps_cases() {
+        // deducing from ¬x with the conditionals swapped is the same
+        // question, so case II and case III must agree
+        let (c1, c2) = (Opinion::with_base_rate(0.6, 0.1, 0.3, 0.8), Opinion::with_base_rate(0.2, 0.4, 0.4, 0.8));
+        for x in [
+            Opinion::with_base_rate(0.2, 0.3, 0.5, 0.5),
+            Opinion::with_base_rate(0.5, 0.1, 0.4, 0.3),
+            Opinion::with_base_rate(0.1, 0.6, 0.3, 0.7),
+            Opinion::with_base_rate(0.0, 0.0, 1.0, 0.4),
+        ] {
+            let not_x = Opinion::with_base_rate(x.d, x.b, x.u, 1.0 - x.a);
+            let y = deduction(x, c1, c2);
+            assert_op(deduction(not_x, c2, c1), y.b, y.d, y.u);
+        }
+    }
+
+    #[test]
+    fn t_deduction_total_probability() {
+        let xs = [
+            Opinion::with_base_rate(0.2, 0.3, 0.5, 0.3),
+            Opinion::with_base_rate(0.7, 0.1, 0.2, 0.6),
+            Opinion::with_base_rate(0.0, 0.0, 1.0, 0.5),
+        ];
+        let conds = [
+            (Opinion::with_base_rate(0.72, 0.18, 0.10, 0.4), Opinion::with_base_rate(0.13, 0.57, 0.30, 0.4)),
+            (Opinion::with_base_rate(0.10, 0.80, 0.10, 0.7), Opinion::with_base_rate(0.60, 0.20, 0.20, 0.7)),
+            (Opinion::with_base_rate(0.40, 0.20, 0.40, 0.5), Opinion::with_base_rate(0.10, 0.50, 0.40, 0.5)),
+        ];
+        for x in xs {
+            for (yx, ynx) in conds {
+                let y = deduction(x, yx, ynx);
+                assert_valid(y);
+                let px = x.projected_probability();
+                let expected = px * yx.projected_probability() + (1.0 - px) * ynx.projected_probability();
+                assert_relative_eq!(y.projected_probability(), expected, epsilon=1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn t_abduction_identity_conditionals() {
+        let yx = Opinion::with_base_rate(1.0, 0.0, 0.0, 0.4);
+        let ynx = Opinion::with_base_rate(0.0, 1.0, 0.0, 0.4);
+        let (xy, xny) = invert_conditionals(yx, ynx, 0.4);
+        assert_op(xy, 1.0, 0.0, 0.0);
+        assert_op(xny, 0.0, 1.0, 0.0);
+        let x = abduction(Opinion::with_base_rate(0.3, 0.2, 0.5, 0.4), yx, ynx, 0.4);
+        assert_op(x, 0.3, 0.2, 0.5);
+    }
+
+    #[test]
+    fn t_abduction_irrelevant_conditionals() {
+        // y tells us nothing about x: the abduced opinion is vacuous
+        let c = Opinion::with_base_rate(0.6, 0.2, 0.2, 0.5);
+        let x = abduction(Opinion::with_base_rate(0.9, 0.05, 0.05, 0.5), c, c, 0.3);
+        assert_op(x, 0.0, 0.0, 1.0);
+        assert_relative_eq!(x.projected_probability(), 0.3, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_abduction_bayes() {
+        let yx = Opinion::with_base_rate(0.72, 0.18, 0.10, 0.4);
+        let ynx = Opinion::with_base_rate(0.13, 0.57, 0.30, 0.4);
+        let ax = 0.2;
+        let (xy, xny) = invert_conditionals(yx, ynx, ax);
+        assert_valid(xy);
+        assert_valid(xny);
+        let p_yx = yx.projected_probability();
+        let p_y = ax * p_yx + (1.0 - ax) * ynx.projected_probability();
+        assert_relative_eq!(xy.projected_probability(), ax * p_yx / p_y, epsilon=1e-9);
+        assert_relative_eq!(xny.projected_probability(), ax * (1.0 - p_yx) / (1.0 - p_y), epsilon=1e-9);
+
+        let y = Opinion::with_base_rate(0.6, 0.1, 0.3, 0.4);
+        let x = abduction(y, yx, ynx, ax);
+        assert_valid(x);
+        let py = y.projected_probability();
+        let expected = py * xy.projected_probability() + (1.0 - py) * xny.projected_probability();
+        assert_relative_eq!(x.projected_probability(), expected, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_abduction_worked_example() {
+        // worked in exact fractions from the inversion in Jøsang (2016,
+        // ch. 10): P(x|y) = 15/47, P(x|¬y) = 5/53 by Bayes, and the relative
+        // uncertainty is 5353/7000
+        let yx = Opinion::with_base_rate(0.6, 0.1, 0.3, 0.5);
+        let ynx = Opinion::with_base_rate(0.2, 0.4, 0.4, 0.5);
+        let (xy, xny) = invert_conditionals(yx, ynx, 0.2);
+        assert_op(xy, 7772.0 / 41125.0, 6588.0 / 41125.0, 5353.0 / 8225.0);
+        assert_op(xny, 1647.0 / 74200.0, 11447.0 / 18550.0, 101.0 / 280.0);
+
+        // the inverted conditionals fall under case II.B
+        let x = abduction(Opinion::with_base_rate(0.7, 0.1, 0.2, 0.5), yx, ynx, 0.2);
+        assert_op(x, 25144997.0 / 174370000.0, 8974997.0 / 43592500.0, 22665003.0 / 34874000.0);
+        // a vacuous y keeps d_{x|y} at the apex
+        let x = abduction(Opinion::with_base_rate(0.0, 0.0, 1.0, 0.5), yx, ynx, 0.2);
+        assert_op(x, 105666.0 / 2179625.0, 6588.0 / 41125.0, 344959.0 / 435925.0);
+    }
+}
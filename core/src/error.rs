@@ -0,0 +1,171 @@
+use std::fmt;
+
+/// Reasons an opinion is rejected by the validated constructors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpinionError {
+    /// A component is NaN or infinite.
+    NonFinite { field: &'static str, value: f64 },
+    /// A component lies outside `[0, 1]`.
+    OutOfRange { field: &'static str, value: f64 },
+    /// `b + d + u` differs from 1 by more than [`crate::ADDITIVITY_TOLERANCE`].
+    NotAdditive { sum: f64 },
+}
+
+impl OpinionError {
+    /// Stable, machine-readable name of the violated rule.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            OpinionError::NonFinite { .. } => "non_finite",
+            OpinionError::OutOfRange { .. } => "out_of_range",
+            OpinionError::NotAdditive { .. } => "not_additive",
+        }
+    }
+}
+
+impl fmt::Display for OpinionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpinionError::NonFinite { field, value } => write!(f, "{field} is not finite ({value})"),
+            OpinionError::OutOfRange { field, value } => write!(f, "{field} must be within [0, 1], got {value}"),
+            OpinionError::NotAdditive { sum } => write!(f, "b + d + u must equal 1, got {sum}"),
+        }
+    }
+}
+
+impl std::error::Error for OpinionError {}
+
+/// Stored evidence counts and the stored opinion disagree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvidenceMismatch {
+    /// Opinion the counts map to.
+    pub expected: crate::Opinion,
+    pub stored: crate::Opinion,
+}
+
+impl fmt::Display for EvidenceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stored opinion {} does not match its evidence {}", self.stored, self.expected)
+    }
+}
+
+impl std::error::Error for EvidenceMismatch {}
+
+/// Returned when parsing a [`crate::FusionKind`] from an unknown name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFusionOperator(pub String);
+
+impl fmt::Display for UnknownFusionOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown fusion operator '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFusionOperator {}
+
+/// Reasons a [`crate::FacetFusion`] spec is rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FusionSpecError {
+    /// An entry isn't a `name=operator` pair.
+    MalformedPair(String),
+    UnknownOperator(UnknownFusionOperator),
+}
+
+impl fmt::Display for FusionSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FusionSpecError::MalformedPair(pair) => write!(f, "'{pair}' is not a name=operator pair"),
+            FusionSpecError::UnknownOperator(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FusionSpecError {}
+
+impl From<UnknownFusionOperator> for FusionSpecError {
+    fn from(e: UnknownFusionOperator) -> Self {
+        FusionSpecError::UnknownOperator(e)
+    }
+}
+
+/// Reasons a multinomial or hyper-opinion operation is rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultinomialError {
+    /// A value is not part of the frame.
+    UnknownValue(String),
+    /// Opinions over different frames were combined.
+    FrameMismatch,
+    /// A vector does not have one entry per frame value.
+    DimensionMismatch { expected: usize, got: usize },
+    /// A belief, uncertainty or base rate lies outside `[0, 1]`.
+    OutOfRange,
+    /// Beliefs plus uncertainty, or base rates, do not sum to 1.
+    NotAdditive { sum: f64 },
+    /// Nothing to operate on.
+    Empty,
+    /// The frame has more values than a hyper-opinion can index.
+    FrameTooLarge(usize),
+}
+
+impl fmt::Display for MultinomialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultinomialError::UnknownValue(v) => write!(f, "'{v}' is not in the frame"),
+            MultinomialError::FrameMismatch => f.write_str("opinions are over different frames"),
+            MultinomialError::DimensionMismatch { expected, got } => write!(f, "expected {expected} entries, got {got}"),
+            MultinomialError::OutOfRange => f.write_str("values must be finite and within [0, 1]"),
+            MultinomialError::NotAdditive { sum } => write!(f, "values must sum to 1, got {sum}"),
+            MultinomialError::Empty => f.write_str("nothing to operate on"),
+            MultinomialError::FrameTooLarge(n) => write!(f, "frame of {n} values exceeds hyper-opinion limit of {}", crate::multinomial::MAX_HYPER_FRAME),
+        }
+    }
+}
+
+impl std::error::Error for MultinomialError {}
+
+/// Reasons a [`crate::UserScores`] document is rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoresError {
+    /// Written by a newer producer than this build understands.
+    UnsupportedVersion(u32),
+    /// `did` is empty or not a DID.
+    InvalidDid(String),
+    /// A probability-like field is non-finite or outside `[0, 1]`.
+    OutOfRange { field: String, value: f64 },
+    /// A facet opinion is invalid.
+    Facet { facet: &'static str, error: OpinionError },
+    /// An observation names a facet that isn't scored.
+    UnknownFacet(String),
+    /// An observation count is negative or non-finite.
+    InvalidEvidence { field: &'static str, value: f64 },
+}
+
+impl ScoresError {
+    /// Stable, machine-readable name of the violated rule.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            ScoresError::UnsupportedVersion(_) => "unsupported_version",
+            ScoresError::InvalidDid(_) => "invalid_did",
+            ScoresError::OutOfRange { .. } => "out_of_range",
+            ScoresError::Facet { error, .. } => error.rule(),
+            ScoresError::UnknownFacet(_) => "unknown_facet",
+            ScoresError::InvalidEvidence { .. } => "invalid_evidence",
+        }
+    }
+}
+
+impl fmt::Display for ScoresError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoresError::UnsupportedVersion(v) => {
+                write!(f, "scores version {v} is newer than supported version {}", crate::scores::SCORES_VERSION)
+            }
+            ScoresError::InvalidDid(did) => write!(f, "'{did}' is not a DID"),
+            ScoresError::OutOfRange { field, value } => write!(f, "{field} must be within [0, 1], got {value}"),
+            ScoresError::Facet { facet, error } => write!(f, "facet {facet}: {error}"),
+            ScoresError::UnknownFacet(facet) => write!(f, "unknown facet '{facet}'"),
+            ScoresError::InvalidEvidence { field, value } => write!(f, "observation {field} must be a non-negative count, got {value}"),
+        }
+    }
+}
+
+impl std::error::Error for ScoresError {}
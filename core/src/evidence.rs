@@ -0,0 +1,301 @@
+//! Beta evidence: the `alpha`/`beta` observation counts behind a binomial
+//! opinion, with the statistics needed for error bars.
+
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign, Mul};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{evidence_to_opinion_with_base_rate, Opinion, DEFAULT_BASE_RATE, DEFAULT_PRIOR_WEIGHT, DOGMATIC_EPSILON};
+
+/// Positive (`alpha`) and negative (`beta`) observation counts together with
+/// the prior weight `W` and base rate `a` used to map them to an opinion.
+///
+/// The counts are evidence, not Beta parameters: the distribution is
+/// `Beta(alpha + a·W, beta + (1 - a)·W)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BetaEvidence {
+    pub alpha: f64,
+    pub beta: f64,
+    #[serde(default = "default_prior_weight")]
+    pub prior_weight: f64,
+    #[serde(default = "default_base_rate")]
+    pub base_rate: f64,
+}
+
+fn default_prior_weight() -> f64 { DEFAULT_PRIOR_WEIGHT }
+
+fn default_base_rate() -> f64 { DEFAULT_BASE_RATE }
+
+impl Default for BetaEvidence {
+    fn default() -> Self { Self::new(0.0, 0.0) }
+}
+
+impl BetaEvidence {
+    pub fn new(alpha: f64, beta: f64) -> Self {
+        Self { alpha, beta, prior_weight: DEFAULT_PRIOR_WEIGHT, base_rate: DEFAULT_BASE_RATE }
+    }
+
+    pub fn with_prior_weight(mut self, w: f64) -> Self {
+        self.prior_weight = w;
+        self
+    }
+
+    pub fn with_base_rate(mut self, a: f64) -> Self {
+        self.base_rate = a;
+        self
+    }
+
+    pub fn total(&self) -> f64 { self.alpha + self.beta }
+
+    pub fn to_opinion(&self) -> Opinion {
+        evidence_to_opinion_with_base_rate(self.alpha, self.beta, self.prior_weight, self.base_rate)
+    }
+
+    /// Inverse of [`BetaEvidence::to_opinion`]: `alpha = W·b/u`,
+    /// `beta = W·d/u`. Dogmatic opinions stand for infinite evidence and
+    /// return `None`.
+    pub fn from_opinion(o: &Opinion, prior_weight: f64) -> Option<Self> {
+        if o.u <= DOGMATIC_EPSILON {
+            return None;
+        }
+        Some(Self {
+            alpha: prior_weight * o.b / o.u,
+            beta: prior_weight * o.d / o.u,
+            prior_weight,
+            base_rate: o.a,
+        })
+    }
+
+    /// Parameters of the Beta distribution, `(alpha + a·W, beta + (1 - a)·W)`.
+    pub fn beta_params(&self) -> (f64, f64) {
+        (
+            self.alpha + self.base_rate * self.prior_weight,
+            self.beta + (1.0 - self.base_rate) * self.prior_weight,
+        )
+    }
+
+    /// Mean of the Beta distribution; equals the opinion's projected probability.
+    pub fn expected(&self) -> f64 {
+        let (a, b) = self.beta_params();
+        a / (a + b)
+    }
+
+    pub fn variance(&self) -> f64 {
+        let (a, b) = self.beta_params();
+        let s = a + b;
+        a * b / (s * s * (s + 1.0))
+    }
+
+    /// Equal-tailed credible interval holding `level` (e.g. 0.95) of the
+    /// probability mass. A parameter of 0 (base rate 0 or 1 with no counts
+    /// against it) puts all the mass on that end; with both at 0 nothing
+    /// is known and the interval is `(0, 1)`.
+    pub fn credible_interval(&self, level: f64) -> (f64, f64) {
+        let (a, b) = self.beta_params();
+        match (a > 0.0, b > 0.0) {
+            (false, false) => return (0.0, 1.0),
+            (false, true) => return (0.0, 0.0),
+            (true, false) => return (1.0, 1.0),
+            (true, true) => {}
+        }
+        let tail = (1.0 - level.clamp(0.0, 1.0)) / 2.0;
+        (beta_quantile(a, b, tail), beta_quantile(a, b, 1.0 - tail))
+    }
+
+    /// Evidence weighted by `factor`, e.g. to age or discount it.
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self { alpha: self.alpha * factor, beta: self.beta * factor, ..*self }
+    }
+
+    /// Whether `opinion` is what these counts map to, within `tolerance`.
+    pub fn agrees_with(&self, opinion: &Opinion, tolerance: f64) -> bool {
+        let expected = self.to_opinion();
+        (expected.b - opinion.b).abs() <= tolerance
+            && (expected.d - opinion.d).abs() <= tolerance
+            && (expected.u - opinion.u).abs() <= tolerance
+    }
+}
+
+/// Adds the counts; the prior weight and base rate of the left side are kept.
+impl Add for BetaEvidence {
+    type Output = BetaEvidence;
+
+    fn add(self, rhs: BetaEvidence) -> BetaEvidence {
+        Self { alpha: self.alpha + rhs.alpha, beta: self.beta + rhs.beta, ..self }
+    }
+}
+
+impl AddAssign for BetaEvidence {
+    fn add_assign(&mut self, rhs: BetaEvidence) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul<f64> for BetaEvidence {
+    type Output = BetaEvidence;
+
+    fn mul(self, factor: f64) -> BetaEvidence { self.scaled(factor) }
+}
+
+/// Quantile of `Beta(a, b)` by bisection on the regularised incomplete beta.
+fn beta_quantile(a: f64, b: f64, p: f64) -> f64 {
+    if p <= 0.0 {
+        return 0.0;
+    }
+    if p >= 1.0 {
+        return 1.0;
+    }
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if reg_inc_beta(a, b, mid) < p { lo = mid } else { hi = mid }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Regularised incomplete beta `I_x(a, b)`.
+fn reg_inc_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_cf(a, b, x) / a
+    } else {
+        1.0 - front * beta_cf(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta (modified Lentz).
+fn beta_cf(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY { d = TINY; }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY { d = TINY; }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY { c = TINY; }
+        d = 1.0 / d;
+        h *= d * c;
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY { d = TINY; }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY { c = TINY; }
+        d = 1.0 / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    h
+}
+
+/// Lanczos approximation of `ln Γ(x)`.
+fn ln_gamma(x: f64) -> f64 {
+    const COEF: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEF[1..].iter().enumerate().fold(COEF[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn t_round_trip() {
+        let ev = BetaEvidence::new(8.0, 2.0).with_base_rate(0.3);
+        let o = ev.to_opinion();
+        assert_relative_eq!(o.b, 2.0 / 3.0, epsilon=1e-9);
+        assert_relative_eq!(o.a, 0.3, epsilon=1e-9);
+        let back = BetaEvidence::from_opinion(&o, DEFAULT_PRIOR_WEIGHT).unwrap();
+        assert_relative_eq!(back.alpha, 8.0, epsilon=1e-9);
+        assert_relative_eq!(back.beta, 2.0, epsilon=1e-9);
+        assert_relative_eq!(back.base_rate, 0.3, epsilon=1e-9);
+        assert!(BetaEvidence::from_opinion(&Opinion::new(1.0, 0.0, 0.0), DEFAULT_PRIOR_WEIGHT).is_none());
+    }
+
+    #[test]
+    fn t_moments() {
+        let ev = BetaEvidence::new(8.0, 2.0).with_base_rate(0.25);
+        assert_relative_eq!(ev.expected(), ev.to_opinion().projected_probability(), epsilon=1e-12);
+        // Beta(8.5, 3.5)
+        assert_relative_eq!(ev.variance(), 8.5 * 3.5 / (144.0 * 13.0), epsilon=1e-12);
+    }
+
+    #[test]
+    fn t_incomplete_beta() {
+        assert_relative_eq!(ln_gamma(5.0), 24f64.ln(), epsilon=1e-10);
+        assert_relative_eq!(ln_gamma(0.5), PI.sqrt().ln(), epsilon=1e-10);
+        // I_x(2, 2) = 3x² - 2x³
+        assert_relative_eq!(reg_inc_beta(2.0, 2.0, 0.25), 0.15625, epsilon=1e-10);
+        assert_relative_eq!(reg_inc_beta(2.0, 2.0, 0.9), 0.972, epsilon=1e-10);
+    }
+
+    #[test]
+    fn t_credible_interval() {
+        // no evidence, W = 2, a = 0.5: uniform Beta(1, 1)
+        let (lo, hi) = BetaEvidence::default().credible_interval(0.95);
+        assert_relative_eq!(lo, 0.025, epsilon=1e-9);
+        assert_relative_eq!(hi, 0.975, epsilon=1e-9);
+        // more evidence narrows the interval around the mean
+        let few = BetaEvidence::new(4.0, 1.0).credible_interval(0.95);
+        let many = BetaEvidence::new(400.0, 100.0).credible_interval(0.95);
+        assert!(many.1 - many.0 < few.1 - few.0);
+        assert!(many.0 < 0.8 && 0.8 < many.1);
+    }
+
+    #[test]
+    fn t_credible_interval_at_degenerate_parameters() {
+        // a base rate at either end with nothing against it leaves one parameter at 0
+        assert_eq!(BetaEvidence::default().with_base_rate(0.0).credible_interval(0.95), (0.0, 0.0));
+        assert_eq!(BetaEvidence::default().with_base_rate(1.0).credible_interval(0.95), (1.0, 1.0));
+        assert_eq!(BetaEvidence::new(3.0, 0.0).with_base_rate(1.0).credible_interval(0.95), (1.0, 1.0));
+        assert_eq!(BetaEvidence::default().with_prior_weight(0.0).credible_interval(0.95), (0.0, 1.0));
+        let (lo, hi) = BetaEvidence::new(0.0, 2.0).with_base_rate(1.0).credible_interval(0.95);
+        assert!(lo.is_finite() && hi.is_finite() && lo < hi);
+    }
+
+    #[test]
+    fn t_arithmetic_and_agreement() {
+        let sum = BetaEvidence::new(3.0, 1.0) + BetaEvidence::new(5.0, 1.0);
+        assert_eq!((sum.alpha, sum.beta), (8.0, 2.0));
+        let mut acc = BetaEvidence::default();
+        acc += BetaEvidence::new(1.0, 0.0);
+        assert_eq!(acc.alpha, 1.0);
+        let half = sum * 0.5;
+        assert_eq!((half.alpha, half.beta), (4.0, 1.0));
+        assert!(sum.agrees_with(&crate::evidence_to_opinion(8.0, 2.0, 2.0), 1e-9));
+        assert!(!sum.agrees_with(&Opinion::new(0.5, 0.2, 0.3), 1e-6));
+    }
+}
@@ -0,0 +1,443 @@
+//! Belief fusion operators for combining opinions from several sources
+//! about the same proposition.
+//!
+//! Which operator fits depends on how the sources relate: cumulative fusion
+//! for independent evidence, averaging for dependent observers, weighted
+//! fusion when confidence should count, and consensus & compromise when
+//! conflict should turn into uncertainty rather than be averaged away.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BetaEvidence, FusionSpecError, Opinion, UnknownFusionOperator, DEFAULT_BASE_RATE, DOGMATIC_EPSILON};
+
+/// A fusion operator over any number of binomial opinions.
+pub trait FusionOperator {
+    fn name(&self) -> &'static str;
+
+    /// Fuses `opinions`. An empty slice yields a vacuous opinion.
+    fn fuse(&self, opinions: &[Opinion]) -> Opinion;
+
+    fn fuse_pair(&self, o1: Opinion, o2: Opinion) -> Opinion {
+        self.fuse(&[o1, o2])
+    }
+}
+
+/// Cumulative belief fusion (CBF): evidence from independent sources adds up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CumulativeFusion;
+
+/// Averaging belief fusion (ABF): sources observed the same evidence.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AveragingFusion;
+
+/// Weighted belief fusion (WBF): sources weighted by their confidence `1 - u`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeightedFusion;
+
+/// Consensus & compromise fusion (CCF): shared belief is kept, conflicting
+/// belief becomes uncertainty. With more than two sources the opinions are
+/// folded pairwise from left to right.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsensusCompromiseFusion;
+
+impl FusionOperator for CumulativeFusion {
+    fn name(&self) -> &'static str { "cumulative" }
+
+    fn fuse(&self, opinions: &[Opinion]) -> Opinion {
+        if let Some(o) = trivial(opinions) { return o; }
+        let n = opinions.len() as f64;
+        let prod = product_u(opinions);
+        let excl = products_except(opinions);
+        let denom = excl.iter().sum::<f64>() - (n - 1.0) * prod;
+        let b = opinions.iter().zip(&excl).map(|(o, e)| o.b * e).sum::<f64>() / denom;
+        let d = opinions.iter().zip(&excl).map(|(o, e)| o.d * e).sum::<f64>() / denom;
+        let u = prod / denom;
+        let weights: Vec<f64> = opinions.iter().zip(&excl).map(|(o, e)| (1.0 - o.u) * e).collect();
+        Opinion { b, d, u, a: weighted_base_rate(opinions, &weights) }
+    }
+}
+
+impl FusionOperator for AveragingFusion {
+    fn name(&self) -> &'static str { "averaging" }
+
+    fn fuse(&self, opinions: &[Opinion]) -> Opinion {
+        if let Some(o) = trivial(opinions) { return o; }
+        let n = opinions.len() as f64;
+        let prod = product_u(opinions);
+        let excl = products_except(opinions);
+        let denom = excl.iter().sum::<f64>();
+        let b = opinions.iter().zip(&excl).map(|(o, e)| o.b * e).sum::<f64>() / denom;
+        let d = opinions.iter().zip(&excl).map(|(o, e)| o.d * e).sum::<f64>() / denom;
+        let u = n * prod / denom;
+        Opinion { b, d, u, a: mean_base_rate(opinions) }
+    }
+}
+
+impl FusionOperator for WeightedFusion {
+    fn name(&self) -> &'static str { "weighted" }
+
+    fn fuse(&self, opinions: &[Opinion]) -> Opinion {
+        if let Some(o) = trivial(opinions) { return o; }
+        if opinions.iter().all(|o| o.u >= 1.0 - DOGMATIC_EPSILON) {
+            return vacuous(opinions);
+        }
+        let n = opinions.len() as f64;
+        let sum_u: f64 = opinions.iter().map(|o| o.u).sum();
+        let prod = product_u(opinions);
+        let excl = products_except(opinions);
+        let denom = excl.iter().sum::<f64>() - n * prod;
+        let b = opinions.iter().zip(&excl).map(|(o, e)| o.b * (1.0 - o.u) * e).sum::<f64>() / denom;
+        let d = opinions.iter().zip(&excl).map(|(o, e)| o.d * (1.0 - o.u) * e).sum::<f64>() / denom;
+        let u = (n - sum_u) * prod / denom;
+        let weights: Vec<f64> = opinions.iter().map(|o| 1.0 - o.u).collect();
+        Opinion { b, d, u, a: weighted_base_rate(opinions, &weights) }
+    }
+}
+
+impl FusionOperator for ConsensusCompromiseFusion {
+    fn name(&self) -> &'static str { "consensus_compromise" }
+
+    fn fuse(&self, opinions: &[Opinion]) -> Opinion {
+        match opinions {
+            [] => vacuous(opinions),
+            [first, rest @ ..] => rest.iter().fold(*first, |acc, o| ccf_pair(acc, *o)),
+        }
+    }
+}
+
+fn ccf_pair(o1: Opinion, o2: Opinion) -> Opinion {
+    // consensus: belief both sources agree on
+    let cons_b = o1.b.min(o2.b);
+    let cons_d = o1.d.min(o2.d);
+    // residue each source holds beyond the consensus
+    let (res1_b, res1_d) = (o1.b - cons_b, o1.d - cons_d);
+    let (res2_b, res2_d) = (o2.b - cons_b, o2.d - cons_d);
+    // compromise: residue kept where the other source is uncertain, and
+    // conflicting residue assigned to the whole frame
+    let comp_b = res1_b * o2.u + res2_b * o1.u;
+    let comp_d = res1_d * o2.u + res2_d * o1.u;
+    let comp_frame = res1_b * res2_d + res1_d * res2_b;
+    let u_pre = o1.u * o2.u;
+    let comp_total = comp_b + comp_d + comp_frame;
+    let a = (o1.a + o2.a) / 2.0;
+    if comp_total <= f64::EPSILON {
+        // nothing to compromise on: the consensus is all that remains
+        return Opinion { b: cons_b, d: cons_d, u: 1.0 - cons_b - cons_d, a };
+    }
+    let eta = (1.0 - cons_b - cons_d - u_pre) / comp_total;
+    Opinion {
+        b: cons_b + eta * comp_b,
+        d: cons_d + eta * comp_d,
+        u: u_pre + eta * comp_frame,
+        a,
+    }
+}
+
+/// Handles the empty, single-source and dogmatic cases shared by the
+/// product-based operators. Dogmatic sources get equal relative dogmatism.
+fn trivial(opinions: &[Opinion]) -> Option<Opinion> {
+    match opinions {
+        [] => return Some(vacuous(opinions)),
+        [o] => return Some(*o),
+        _ => {}
+    }
+    let dogmatic: Vec<&Opinion> = opinions.iter().filter(|o| o.u <= DOGMATIC_EPSILON).collect();
+    if dogmatic.is_empty() {
+        return None;
+    }
+    let g = 1.0 / dogmatic.len() as f64;
+    Some(Opinion {
+        b: dogmatic.iter().map(|o| g * o.b).sum(),
+        d: dogmatic.iter().map(|o| g * o.d).sum(),
+        u: 0.0,
+        a: dogmatic.iter().map(|o| g * o.a).sum(),
+    })
+}
+
+fn vacuous(opinions: &[Opinion]) -> Opinion {
+    Opinion { b: 0.0, d: 0.0, u: 1.0, a: mean_base_rate(opinions) }
+}
+
+fn mean_base_rate(opinions: &[Opinion]) -> f64 {
+    if opinions.is_empty() {
+        return DEFAULT_BASE_RATE;
+    }
+    opinions.iter().map(|o| o.a).sum::<f64>() / opinions.len() as f64
+}
+
+fn weighted_base_rate(opinions: &[Opinion], weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().sum();
+    if total <= f64::EPSILON {
+        return mean_base_rate(opinions);
+    }
+    opinions.iter().zip(weights).map(|(o, w)| o.a * w).sum::<f64>() / total
+}
+
+fn product_u(opinions: &[Opinion]) -> f64 {
+    opinions.iter().map(|o| o.u).product()
+}
+
+/// `Π_{j≠i} u_j` for every `i`, multiplied out rather than divided so small
+/// uncertainties stay exact.
+fn products_except(opinions: &[Opinion]) -> Vec<f64> {
+    (0..opinions.len())
+        .map(|i| opinions.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, o)| o.u).product())
+        .collect()
+}
+
+/// Fusion operators selectable by name, e.g. from configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionKind {
+    Cumulative,
+    Averaging,
+    Weighted,
+    ConsensusCompromise,
+}
+
+impl FusionKind {
+    pub const ALL: [FusionKind; 4] = [
+        FusionKind::Cumulative,
+        FusionKind::Averaging,
+        FusionKind::Weighted,
+        FusionKind::ConsensusCompromise,
+    ];
+
+    fn operator(&self) -> &'static dyn FusionOperator {
+        match self {
+            FusionKind::Cumulative => &CumulativeFusion,
+            FusionKind::Averaging => &AveragingFusion,
+            FusionKind::Weighted => &WeightedFusion,
+            FusionKind::ConsensusCompromise => &ConsensusCompromiseFusion,
+        }
+    }
+}
+
+impl FusionOperator for FusionKind {
+    fn name(&self) -> &'static str { self.operator().name() }
+
+    fn fuse(&self, opinions: &[Opinion]) -> Opinion { self.operator().fuse(opinions) }
+}
+
+impl fmt::Display for FusionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FusionKind {
+    type Err = UnknownFusionOperator;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FusionKind::ALL
+            .into_iter()
+            .find(|k| k.name() == s)
+            .ok_or_else(|| UnknownFusionOperator(s.to_string()))
+    }
+}
+
+/// Per-facet choice of fusion operator; also used per trust scope.
+///
+/// The default fuses accuracy cumulatively (independent fact-checks) and
+/// civility by averaging (reports from overlapping observers).
+#[derive(Debug, Clone)]
+pub struct FacetFusion {
+    fallback: FusionKind,
+    facets: HashMap<String, FusionKind>,
+}
+
+impl FacetFusion {
+    pub fn new(fallback: FusionKind) -> Self {
+        Self { fallback, facets: HashMap::new() }
+    }
+
+    pub fn with_facet(mut self, facet: impl Into<String>, kind: FusionKind) -> Self {
+        self.facets.insert(facet.into(), kind);
+        self
+    }
+
+    /// Parses `facet=operator` pairs separated by commas, e.g.
+    /// `civility=weighted`, on top of the default choices. Empty entries
+    /// are skipped; anything else that isn't such a pair is an error.
+    pub fn parse(spec: &str) -> Result<Self, FusionSpecError> {
+        Self::default().with_spec(spec)
+    }
+
+    /// [`parse`](Self::parse) on top of these choices instead of the
+    /// defaults.
+    pub fn with_spec(mut self, spec: &str) -> Result<Self, FusionSpecError> {
+        for pair in spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let Some((facet, kind)) = pair.split_once('=').filter(|(facet, _)| !facet.trim().is_empty()) else {
+                return Err(FusionSpecError::MalformedPair(pair.to_string()));
+            };
+            self.facets.insert(facet.trim().to_string(), kind.trim().parse()?);
+        }
+        Ok(self)
+    }
+
+    pub fn operator(&self, facet: &str) -> FusionKind {
+        self.facets.get(facet).copied().unwrap_or(self.fallback)
+    }
+
+    pub fn fuse(&self, facet: &str, opinions: &[Opinion]) -> Opinion {
+        self.operator(facet).fuse(opinions)
+    }
+
+    /// Combines `(source, evidence)` items about `facet`. A source's items
+    /// add up, since they are its own separate observations; the sources
+    /// are then fused with the facet's operator and mapped back to
+    /// evidence. A single source's sum is returned as is. `None` without
+    /// any items.
+    pub fn fuse_evidence<'a>(&self, facet: &str, items: impl IntoIterator<Item = (&'a str, BetaEvidence)>) -> Option<BetaEvidence> {
+        let mut sources: Vec<(&str, BetaEvidence)> = Vec::new();
+        for (source, ev) in items {
+            match sources.iter_mut().find(|(s, _)| *s == source) {
+                Some((_, sum)) => *sum += ev,
+                None => sources.push((source, ev)),
+            }
+        }
+        match sources.as_slice() {
+            [] => None,
+            [(_, ev)] => Some(*ev),
+            [(_, first), ..] => {
+                let opinions: Vec<Opinion> = sources.iter().map(|(_, ev)| ev.to_opinion()).collect();
+                let fused = self.fuse(facet, &opinions);
+                // evidence-based opinions are never dogmatic, nor is their fusion
+                Some(BetaEvidence::from_opinion(&fused, first.prior_weight).unwrap_or(*first))
+            }
+        }
+    }
+}
+
+impl Default for FacetFusion {
+    fn default() -> Self {
+        FacetFusion::new(FusionKind::Cumulative)
+            .with_facet("accuracy", FusionKind::Cumulative)
+            .with_facet("civility", FusionKind::Averaging)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus_fusion;
+    use approx::assert_relative_eq;
+
+    fn assert_op(o: Opinion, b: f64, d: f64, u: f64) {
+        assert_relative_eq!(o.b, b, epsilon=1e-9);
+        assert_relative_eq!(o.d, d, epsilon=1e-9);
+        assert_relative_eq!(o.u, u, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_cumulative_matches_consensus() {
+        let o1 = Opinion::with_base_rate(0.6, 0.2, 0.2, 0.4);
+        let o2 = Opinion::with_base_rate(0.5, 0.3, 0.2, 0.6);
+        let o3 = Opinion::with_base_rate(0.1, 0.5, 0.4, 0.5);
+        let pair = CumulativeFusion.fuse(&[o1, o2]);
+        let expected = consensus_fusion(o1, o2);
+        assert_op(pair, expected.b, expected.d, expected.u);
+        assert_relative_eq!(pair.a, expected.a, epsilon=1e-9);
+
+        let all = CumulativeFusion.fuse(&[o1, o2, o3]);
+        let seq = consensus_fusion(consensus_fusion(o1, o2), o3);
+        assert_op(all, seq.b, seq.d, seq.u);
+    }
+
+    #[test]
+    fn t_averaging() {
+        let o1 = Opinion::new(0.6, 0.2, 0.2);
+        let o2 = Opinion::new(0.5, 0.3, 0.2);
+        assert_op(AveragingFusion.fuse(&[o1, o2]), 0.55, 0.25, 0.2);
+        // averaging is idempotent
+        assert_op(AveragingFusion.fuse(&[o1, o1, o1]), 0.6, 0.2, 0.2);
+    }
+
+    #[test]
+    fn t_weighted() {
+        let o1 = Opinion::new(0.6, 0.2, 0.2);
+        let o2 = Opinion::new(0.5, 0.3, 0.2);
+        assert_op(WeightedFusion.fuse(&[o1, o2]), 0.55, 0.25, 0.2);
+        // a vacuous source carries no weight
+        let v = Opinion::new(0.0, 0.0, 1.0);
+        assert_op(WeightedFusion.fuse(&[o1, v]), 0.6, 0.2, 0.2);
+        assert_op(WeightedFusion.fuse(&[v, v]), 0.0, 0.0, 1.0);
+        // the more confident source dominates
+        let o3 = Opinion::new(0.1, 0.1, 0.8);
+        let w = WeightedFusion.fuse(&[o1, o3]);
+        assert!(w.b > 0.5);
+    }
+
+    #[test]
+    fn t_consensus_compromise() {
+        let o1 = Opinion::new(0.6, 0.2, 0.2);
+        let o2 = Opinion::new(0.5, 0.3, 0.2);
+        assert_op(ConsensusCompromiseFusion.fuse(&[o1, o2]), 0.604, 0.304, 0.092);
+        assert_op(ConsensusCompromiseFusion.fuse(&[o1, o1]), 0.6, 0.2, 0.2);
+        // total conflict between dogmatic sources becomes uncertainty
+        let yes = Opinion::new(1.0, 0.0, 0.0);
+        let no = Opinion::new(0.0, 1.0, 0.0);
+        assert_op(ConsensusCompromiseFusion.fuse(&[yes, no]), 0.0, 0.0, 1.0);
+    }
+
+    #[test]
+    fn t_dogmatic_sources() {
+        let yes = Opinion::new(1.0, 0.0, 0.0);
+        let no = Opinion::new(0.0, 1.0, 0.0);
+        let soft = Opinion::new(0.3, 0.3, 0.4);
+        for kind in [FusionKind::Cumulative, FusionKind::Averaging, FusionKind::Weighted] {
+            assert_op(kind.fuse(&[yes, no, soft]), 0.5, 0.5, 0.0);
+        }
+    }
+
+    #[test]
+    fn t_facet_selection() {
+        let cfg = FacetFusion::default().with_facet("expertise", FusionKind::Weighted);
+        assert_eq!(cfg.operator("accuracy"), FusionKind::Cumulative);
+        assert_eq!(cfg.operator("civility"), FusionKind::Averaging);
+        assert_eq!(cfg.operator("expertise"), FusionKind::Weighted);
+        assert_eq!(cfg.operator("other"), FusionKind::Cumulative);
+        assert_eq!("consensus_compromise".parse::<FusionKind>().unwrap(), FusionKind::ConsensusCompromise);
+        assert!("median".parse::<FusionKind>().is_err());
+
+        let parsed = FacetFusion::parse("civility = weighted,").unwrap();
+        assert_eq!(parsed.operator("civility"), FusionKind::Weighted);
+        assert_eq!(parsed.operator("accuracy"), FusionKind::Cumulative);
+        assert!(matches!(FacetFusion::parse("accuracy=median"), Err(FusionSpecError::UnknownOperator(_))));
+        assert_eq!(FacetFusion::parse("civility:weighted").unwrap_err(), FusionSpecError::MalformedPair("civility:weighted".into()));
+        assert!(matches!(FacetFusion::parse("accuracy=averaging, civility"), Err(FusionSpecError::MalformedPair(_))));
+        assert!(matches!(FacetFusion::parse("=weighted"), Err(FusionSpecError::MalformedPair(_))));
+
+        let scopes = FacetFusion::new(FusionKind::Cumulative).with_spec("medicine=averaging").unwrap();
+        assert_eq!(scopes.operator("medicine"), FusionKind::Averaging);
+        assert_eq!(scopes.operator("civility"), FusionKind::Cumulative);
+    }
+
+    #[test]
+    fn t_facets_fuse_sources_with_their_own_operator() {
+        let cfg = FacetFusion::default();
+        let items = || [
+            ("checker-a", BetaEvidence::new(3.0, 1.0)),
+            ("checker-a", BetaEvidence::new(1.0, 1.0)),
+            ("checker-b", BetaEvidence::new(8.0, 4.0)),
+        ];
+        // accuracy is cumulative: independent sources' evidence adds up
+        let acc = cfg.fuse_evidence("accuracy", items()).unwrap();
+        assert_relative_eq!(acc.alpha, 12.0, epsilon=1e-9);
+        assert_relative_eq!(acc.beta, 6.0, epsilon=1e-9);
+        // civility averages (4, 2) and (8, 4) from overlapping observers
+        let civ = cfg.fuse_evidence("civility", items()).unwrap();
+        assert_relative_eq!(civ.alpha, 6.0, epsilon=1e-9);
+        assert_relative_eq!(civ.beta, 3.0, epsilon=1e-9);
+        assert_op(civ.to_opinion(), 6.0 / 11.0, 3.0 / 11.0, 2.0 / 11.0);
+
+        // one source is passed through untouched
+        let single = cfg.fuse_evidence("civility", [("kw", BetaEvidence::new(1.0, 0.0)), ("kw", BetaEvidence::new(0.0, 1.0))]).unwrap();
+        assert_eq!((single.alpha, single.beta), (1.0, 1.0));
+        assert!(cfg.fuse_evidence("accuracy", []).is_none());
+    }
+}
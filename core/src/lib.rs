@@ -1,35 +1,284 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+pub mod conditional;
+mod error;
+pub mod evidence;
+pub mod fusion;
+pub mod multinomial;
+pub mod network;
+pub mod scores;
+
+pub use conditional::{abduction, deduction, invert_conditionals};
+pub use error::{EvidenceMismatch, FusionSpecError, MultinomialError, OpinionError, ScoresError, UnknownFusionOperator};
+pub use evidence::BetaEvidence;
+pub use fusion::{FacetFusion, FusionKind, FusionOperator};
+pub use multinomial::{DirichletEvidence, Frame, HyperOpinion, MultinomialOpinion};
+pub use network::{derive_trust, DerivedTrust, TnaConfig, TrustGraph, TrustPath};
+pub use scores::{decay_evidence, EvidenceItem, EvidenceRecord, ExpertiseScore, Facets, Observation, UserScores, SCORES_VERSION};
+
+use std::fmt;
+
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Base rate used when nothing better is known about the population.
+pub const DEFAULT_BASE_RATE: f64 = 0.5;
+
+/// Non-informative prior weight `W` used when mapping evidence to opinions.
+pub const DEFAULT_PRIOR_WEIGHT: f64 = 2.0;
+
+/// Allowed deviation of `b + d + u` from 1 before an opinion is rejected.
+pub const ADDITIVITY_TOLERANCE: f64 = 1e-6;
+
+/// How the validated constructors treat a triple that does not sum to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Reject with [`OpinionError::NotAdditive`].
+    #[default]
+    Strict,
+    /// Rescale `b`, `d`, `u` by their sum. Negative or non-finite
+    /// components are still rejected.
+    Rescale,
+}
+
+/// Binomial opinion. On the wire it is `{"b", "d", "u", "a", "p"}` where `p`
+/// is the projected probability; `p` is ignored and `a` defaults to
+/// [`DEFAULT_BASE_RATE`] when reading.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
 pub struct Opinion {
     pub b: f64,
     pub d: f64,
     pub u: f64,
+    /// Base rate (prior probability in the absence of evidence).
+    #[serde(default = "default_base_rate")]
+    pub a: f64,
 }
 
+fn default_base_rate() -> f64 { DEFAULT_BASE_RATE }
+
 impl Opinion {
-    pub fn new(b: f64, d: f64, u: f64) -> Self { Self { b, d, u } }
+    pub fn new(b: f64, d: f64, u: f64) -> Self { Self { b, d, u, a: DEFAULT_BASE_RATE } }
+
+    pub fn with_base_rate(b: f64, d: f64, u: f64, a: f64) -> Self { Self { b, d, u, a } }
+
+    /// Validated counterpart of [`Opinion::new`].
+    pub fn try_new(b: f64, d: f64, u: f64) -> Result<Self, OpinionError> {
+        Self::try_from_parts(b, d, u, DEFAULT_BASE_RATE, Normalization::Strict)
+    }
+
+    /// Builds an opinion after checking every component is finite and in
+    /// `[0, 1]` and that `b + d + u = 1`, rescaling first if `mode` asks for it.
+    pub fn try_from_parts(b: f64, d: f64, u: f64, a: f64, mode: Normalization) -> Result<Self, OpinionError> {
+        for (field, value) in [("b", b), ("d", d), ("u", u), ("a", a)] {
+            if !value.is_finite() {
+                return Err(OpinionError::NonFinite { field, value });
+            }
+        }
+        let (b, d, u) = match mode {
+            Normalization::Strict => (b, d, u),
+            Normalization::Rescale => {
+                let sum = b + d + u;
+                if b < 0.0 || d < 0.0 || u < 0.0 || sum <= 0.0 {
+                    // let the range check below name the offending field
+                    (b, d, u)
+                } else {
+                    (b / sum, d / sum, u / sum)
+                }
+            }
+        };
+        let o = Self { b, d, u, a };
+        o.validate()?;
+        Ok(o)
+    }
+
+    /// Checks the invariants enforced by [`Opinion::try_from_parts`].
+    pub fn validate(&self) -> Result<(), OpinionError> {
+        for (field, value) in [("b", self.b), ("d", self.d), ("u", self.u), ("a", self.a)] {
+            if !value.is_finite() {
+                return Err(OpinionError::NonFinite { field, value });
+            }
+            if !(0.0..=1.0).contains(&value) {
+                return Err(OpinionError::OutOfRange { field, value });
+            }
+        }
+        let sum = self.b + self.d + self.u;
+        if (sum - 1.0).abs() > ADDITIVITY_TOLERANCE {
+            return Err(OpinionError::NotAdditive { sum });
+        }
+        Ok(())
+    }
+
+    /// Projected probability `P = b + a·u`.
+    pub fn projected_probability(&self) -> f64 {
+        self.b + self.a * self.u
+    }
+}
+
+impl Serialize for Opinion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut st = serializer.serialize_struct("Opinion", 5)?;
+        st.serialize_field("b", &self.b)?;
+        st.serialize_field("d", &self.d)?;
+        st.serialize_field("u", &self.u)?;
+        st.serialize_field("a", &self.a)?;
+        st.serialize_field("p", &self.projected_probability())?;
+        st.end()
+    }
+}
+
+/// Formats as `(b, d, u; a)`, three decimals unless a precision is given.
+impl fmt::Display for Opinion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let p = f.precision().unwrap_or(3);
+        write!(f, "({:.p$}, {:.p$}, {:.p$}; a={:.p$})", self.b, self.d, self.u, self.a)
+    }
+}
+
+/// Unchecked, like [`Opinion::new`].
+impl From<(f64, f64, f64)> for Opinion {
+    fn from((b, d, u): (f64, f64, f64)) -> Self { Opinion::new(b, d, u) }
+}
+
+/// Unchecked, like [`Opinion::with_base_rate`].
+impl From<(f64, f64, f64, f64)> for Opinion {
+    fn from((b, d, u, a): (f64, f64, f64, f64)) -> Self { Opinion::with_base_rate(b, d, u, a) }
+}
+
+/// Validated `[b, d, u]`.
+impl TryFrom<[f64; 3]> for Opinion {
+    type Error = OpinionError;
+
+    fn try_from([b, d, u]: [f64; 3]) -> Result<Self, Self::Error> { Opinion::try_new(b, d, u) }
+}
+
+/// Validated `[b, d, u, a]`.
+impl TryFrom<[f64; 4]> for Opinion {
+    type Error = OpinionError;
+
+    fn try_from([b, d, u, a]: [f64; 4]) -> Result<Self, Self::Error> {
+        Opinion::try_from_parts(b, d, u, a, Normalization::Strict)
+    }
+}
+
+impl From<Opinion> for (f64, f64, f64) {
+    fn from(o: Opinion) -> Self { (o.b, o.d, o.u) }
+}
+
+/// Shared wire representation of a stored opinion: the opinion fields
+/// plus, when it was derived from evidence, the `alpha`/`beta` counts and
+/// a 95% credible interval for the projected probability.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OpinionRecord {
+    #[serde(flatten)]
+    pub opinion: Opinion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beta: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<[f64; 2]>,
+}
+
+/// Level of the credible interval stored in [`OpinionRecord::interval`].
+pub const RECORD_CREDIBLE_LEVEL: f64 = 0.95;
+
+impl OpinionRecord {
+    /// Record for an opinion mapped from `alpha`/`beta` evidence with
+    /// [`evidence_to_opinion`].
+    pub fn from_evidence(alpha: f64, beta: f64, prior: f64) -> Self {
+        BetaEvidence::new(alpha, beta).with_prior_weight(prior).into()
+    }
+
+    /// The stored counts as evidence under the record's base rate and the
+    /// given prior weight, if the record carries counts.
+    pub fn evidence(&self, prior_weight: f64) -> Option<BetaEvidence> {
+        let (alpha, beta) = (self.alpha?, self.beta?);
+        Some(BetaEvidence::new(alpha, beta).with_prior_weight(prior_weight).with_base_rate(self.opinion.a))
+    }
+
+    /// Checks that the stored counts map to the stored opinion. Records
+    /// without counts are trivially consistent.
+    pub fn check_consistency(&self, prior_weight: f64) -> Result<(), EvidenceMismatch> {
+        match self.evidence(prior_weight) {
+            Some(ev) if !ev.agrees_with(&self.opinion, ADDITIVITY_TOLERANCE) => {
+                Err(EvidenceMismatch { expected: ev.to_opinion(), stored: self.opinion })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<Opinion> for OpinionRecord {
+    fn from(opinion: Opinion) -> Self { Self { opinion, alpha: None, beta: None, interval: None } }
+}
+
+impl From<BetaEvidence> for OpinionRecord {
+    fn from(ev: BetaEvidence) -> Self {
+        let (lo, hi) = ev.credible_interval(RECORD_CREDIBLE_LEVEL);
+        Self { opinion: ev.to_opinion(), alpha: Some(ev.alpha), beta: Some(ev.beta), interval: Some([lo, hi]) }
+    }
 }
 
 pub fn evidence_to_opinion(alpha: f64, beta: f64, prior: f64) -> Opinion {
+    evidence_to_opinion_with_base_rate(alpha, beta, prior, DEFAULT_BASE_RATE)
+}
+
+pub fn evidence_to_opinion_with_base_rate(alpha: f64, beta: f64, prior: f64, a: f64) -> Opinion {
     let denom = alpha + beta + prior;
     let b = if denom == 0.0 { 0.0 } else { alpha / denom };
     let d = if denom == 0.0 { 0.0 } else { beta / denom };
     let u = if denom == 0.0 { 1.0 } else { prior / denom };
-    Opinion { b, d, u }
+    Opinion { b, d, u, a }
 }
 
+/// Trust discounting: A's opinion of X through B. The base rate is the one
+/// B holds about X.
 pub fn discounting(op_ab: Opinion, op_bx: Opinion) -> Opinion {
     let b = op_ab.b * op_bx.b;
     let d = op_ab.b * op_bx.d;
     let u = op_ab.d + op_ab.u + (op_ab.b * op_bx.u);
-    Opinion { b, d, u }
+    Opinion { b, d, u, a: op_bx.a }
 }
 
+/// Uncertainty at or below which an opinion is treated as dogmatic.
+pub const DOGMATIC_EPSILON: f64 = 1e-9;
+
+/// Cumulative fusion of two opinions. Two dogmatic opinions are weighted
+/// equally; see [`consensus_fusion_with_gamma`].
 pub fn consensus_fusion(o1: Opinion, o2: Opinion) -> Opinion {
+    consensus_fusion_with_gamma(o1, o2, 0.5)
+}
+
+/// Cumulative fusion where `gamma` is the relative dogmatism of `o1`
+/// (`γ = lim u2 / (u1 + u2)` as both uncertainties go to zero). It only
+/// matters when both inputs are dogmatic, in which case the result is the
+/// `gamma`-weighted average of the two; `o2` gets `1 - gamma`.
+pub fn consensus_fusion_with_gamma(o1: Opinion, o2: Opinion, gamma: f64) -> Opinion {
+    if o1.u <= DOGMATIC_EPSILON && o2.u <= DOGMATIC_EPSILON {
+        let g1 = gamma.clamp(0.0, 1.0);
+        let g2 = 1.0 - g1;
+        return Opinion {
+            b: g1 * o1.b + g2 * o2.b,
+            d: g1 * o1.d + g2 * o2.d,
+            u: 0.0,
+            a: g1 * o1.a + g2 * o2.a,
+        };
+    }
     let k = o1.u + o2.u - (o1.u * o2.u);
     let b = (o1.b * o2.u + o2.b * o1.u) / k;
     let d = (o1.d * o2.u + o2.d * o1.u) / k;
     let u = (o1.u * o2.u) / k;
-    Opinion { b, d, u }
+    let a = fused_base_rate(o1, o2);
+    Opinion { b, d, u, a }
+}
+
+/// Base rate of the cumulative fusion of two opinions; falls back to the
+/// plain average when both are vacuous.
+fn fused_base_rate(o1: Opinion, o2: Opinion) -> f64 {
+    let denom = o1.u + o2.u - 2.0 * o1.u * o2.u;
+    if denom.abs() < f64::EPSILON {
+        return (o1.a + o2.a) / 2.0;
+    }
+    (o1.a * o2.u + o2.a * o1.u - (o1.a + o2.a) * o1.u * o2.u) / denom
 }
 
 pub fn time_decay(mut o: Opinion, delta_days: f64, half_life_days: f64) -> Opinion {
@@ -89,6 +338,45 @@ mod tests {
         assert_relative_eq!(o.u, 0.111_111_111_1, epsilon=1e-9);
     }
 
+    #[test]
+    fn t_consensus_dogmatic_dogmatic() {
+        let o1 = Opinion::with_base_rate(0.8, 0.2, 0.0, 0.4);
+        let o2 = Opinion::with_base_rate(0.4, 0.6, 0.0, 0.6);
+        let o = consensus_fusion(o1, o2);
+        assert_relative_eq!(o.b, 0.6, epsilon=1e-9);
+        assert_relative_eq!(o.d, 0.4, epsilon=1e-9);
+        assert_relative_eq!(o.u, 0.0, epsilon=1e-9);
+        assert_relative_eq!(o.a, 0.5, epsilon=1e-9);
+
+        let w = consensus_fusion_with_gamma(o1, o2, 0.75);
+        assert_relative_eq!(w.b, 0.7, epsilon=1e-9);
+        assert_relative_eq!(w.d, 0.3, epsilon=1e-9);
+        assert_relative_eq!(w.a, 0.45, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_consensus_dogmatic_vacuous() {
+        let o1 = Opinion::with_base_rate(0.8, 0.2, 0.0, 0.4);
+        let o2 = Opinion::with_base_rate(0.0, 0.0, 1.0, 0.6);
+        for o in [consensus_fusion(o1, o2), consensus_fusion(o2, o1)] {
+            assert_relative_eq!(o.b, 0.8, epsilon=1e-9);
+            assert_relative_eq!(o.d, 0.2, epsilon=1e-9);
+            assert_relative_eq!(o.u, 0.0, epsilon=1e-9);
+            assert_relative_eq!(o.a, 0.4, epsilon=1e-9);
+        }
+    }
+
+    #[test]
+    fn t_consensus_vacuous_vacuous() {
+        let o1 = Opinion::with_base_rate(0.0, 0.0, 1.0, 0.2);
+        let o2 = Opinion::with_base_rate(0.0, 0.0, 1.0, 0.4);
+        let o = consensus_fusion(o1, o2);
+        assert_relative_eq!(o.b, 0.0, epsilon=1e-9);
+        assert_relative_eq!(o.d, 0.0, epsilon=1e-9);
+        assert_relative_eq!(o.u, 1.0, epsilon=1e-9);
+        assert_relative_eq!(o.a, 0.3, epsilon=1e-9);
+    }
+
     #[test]
     fn t_time_decay() {
         let o = Opinion::new(0.6, 0.2, 0.2);
@@ -97,6 +385,87 @@ mod tests {
         assert_relative_eq!(o2.d, 0.1, epsilon=1e-9);
         assert_relative_eq!(o2.u, 0.6, epsilon=1e-9);
     }
+
+    #[test]
+    fn t_projected_probability() {
+        let o = Opinion::with_base_rate(0.6, 0.2, 0.2, 0.25);
+        assert_relative_eq!(o.projected_probability(), 0.65, epsilon=1e-9);
+        // vacuous opinion projects onto its base rate
+        let v = evidence_to_opinion_with_base_rate(0.0, 0.0, 2.0, 0.3);
+        assert_relative_eq!(v.projected_probability(), 0.3, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_base_rate_carried() {
+        let ab = Opinion::with_base_rate(0.7, 0.1, 0.2, 0.9);
+        let bx = Opinion::with_base_rate(0.6, 0.2, 0.2, 0.3);
+        assert_relative_eq!(discounting(ab, bx).a, 0.3, epsilon=1e-9);
+        assert_relative_eq!(hop_decay(bx, 0.85).a, 0.3, epsilon=1e-9);
+        assert_relative_eq!(time_decay(bx, 10.0, 30.0).a, 0.3, epsilon=1e-9);
+
+        let o1 = Opinion::with_base_rate(0.6, 0.2, 0.2, 0.4);
+        let o2 = Opinion::with_base_rate(0.5, 0.3, 0.2, 0.6);
+        assert_relative_eq!(consensus_fusion(o1, o2).a, 0.5, epsilon=1e-9);
+        let o3 = Opinion::with_base_rate(0.5, 0.3, 0.2, 0.4);
+        assert_relative_eq!(consensus_fusion(o1, o3).a, 0.4, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_try_new() {
+        assert!(Opinion::try_new(0.6, 0.2, 0.2).is_ok());
+        assert_eq!(Opinion::try_new(f64::NAN, 0.2, 0.2).unwrap_err().rule(), "non_finite");
+        assert_eq!(Opinion::try_new(-0.1, 0.6, 0.5).unwrap_err(), OpinionError::OutOfRange { field: "b", value: -0.1 });
+        assert_eq!(Opinion::try_new(0.5, 0.5, 0.5).unwrap_err().rule(), "not_additive");
+        assert_eq!(Opinion::try_from_parts(0.6, 0.2, 0.2, 1.5, Normalization::Strict).unwrap_err().rule(), "out_of_range");
+    }
+
+    #[test]
+    fn t_serde() {
+        let o = Opinion::with_base_rate(0.6, 0.2, 0.2, 0.25);
+        let v = serde_json::to_value(o).unwrap();
+        assert_eq!(v, serde_json::json!({"b": 0.6, "d": 0.2, "u": 0.2, "a": 0.25, "p": 0.65}));
+        assert_eq!(serde_json::from_value::<Opinion>(v).unwrap(), o);
+        let legacy: Opinion = serde_json::from_str(r#"{"b": 0.6, "d": 0.2, "u": 0.2}"#).unwrap();
+        assert_eq!(legacy.a, DEFAULT_BASE_RATE);
+
+        let rec = OpinionRecord::from_evidence(8.0, 2.0, 2.0);
+        let v = serde_json::to_value(rec).unwrap();
+        assert_eq!(v["alpha"], 8.0);
+        assert_eq!(v["beta"], 2.0);
+        assert_eq!(serde_json::from_value::<OpinionRecord>(v).unwrap(), rec);
+        assert!(rec.check_consistency(2.0).is_ok());
+        let [lo, hi] = rec.interval.unwrap();
+        assert!(lo < rec.opinion.projected_probability() && rec.opinion.projected_probability() < hi);
+        let drifted = OpinionRecord { alpha: Some(9.0), ..rec };
+        assert!(drifted.check_consistency(2.0).is_err());
+        let bare = serde_json::to_value(OpinionRecord::from(o)).unwrap();
+        assert!(bare.get("alpha").is_none() && bare.get("interval").is_none());
+        // legacy documents stored integer counts
+        let old: OpinionRecord = serde_json::from_str(r#"{"alpha": 5, "beta": 1, "b": 0.625, "d": 0.125, "u": 0.25}"#).unwrap();
+        assert_eq!(old.alpha, Some(5.0));
+    }
+
+    #[test]
+    fn t_display_and_conversions() {
+        let o: Opinion = (0.6, 0.2, 0.2).into();
+        assert_eq!(o.to_string(), "(0.600, 0.200, 0.200; a=0.500)");
+        assert_eq!(format!("{o:.1}"), "(0.6, 0.2, 0.2; a=0.5)");
+        assert_eq!(Opinion::from((0.6, 0.2, 0.2, 0.3)).a, 0.3);
+        assert!(Opinion::try_from([0.6, 0.2, 0.2]).is_ok());
+        assert_eq!(Opinion::try_from([0.6, 0.6, 0.2, 0.5]).unwrap_err().rule(), "not_additive");
+        let (b, d, u): (f64, f64, f64) = o.into();
+        assert_eq!((b, d, u), (0.6, 0.2, 0.2));
+    }
+
+    #[test]
+    fn t_try_new_rescale() {
+        let o = Opinion::try_from_parts(3.0, 1.0, 1.0, 0.5, Normalization::Rescale).unwrap();
+        assert_relative_eq!(o.b, 0.6, epsilon=1e-9);
+        assert_relative_eq!(o.d, 0.2, epsilon=1e-9);
+        assert_relative_eq!(o.u, 0.2, epsilon=1e-9);
+        assert_eq!(Opinion::try_from_parts(0.0, 0.0, 0.0, 0.5, Normalization::Rescale).unwrap_err().rule(), "not_additive");
+        assert_eq!(Opinion::try_from_parts(-1.0, 1.0, 1.0, 0.5, Normalization::Rescale).unwrap_err().rule(), "out_of_range");
+    }
 }
 
 
@@ -0,0 +1,569 @@
+//! Multinomial and hyper-opinions over a frame of domains.
+//!
+//! A multinomial opinion spreads belief over the singletons of a frame
+//! (e.g. the domains in `domain_catalog.json`) with a single uncertainty
+//! mass; its evidence is a Dirichlet distribution. A hyper-opinion can
+//! also hold belief on composite sets ("some science domain") and projects
+//! down to a multinomial one.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::{Opinion, MultinomialError, ADDITIVITY_TOLERANCE, DEFAULT_PRIOR_WEIGHT, DOGMATIC_EPSILON};
+
+/// Largest frame a [`HyperOpinion`] supports (subsets are bit masks).
+pub const MAX_HYPER_FRAME: usize = 32;
+
+/// Ordered set of mutually exclusive values, e.g. expertise domains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    values: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Catalog { domains: Vec<String> }
+
+impl Frame {
+    pub fn new<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { values: values.into_iter().map(Into::into).collect() }
+    }
+
+    /// Parses a `{"domains": [...]}` catalog such as `domain_catalog.json`.
+    pub fn from_catalog_json(json: &str) -> Result<Self, serde_json::Error> {
+        let c: Catalog = serde_json::from_str(json)?;
+        Ok(Self::new(c.domains))
+    }
+
+    pub fn len(&self) -> usize { self.values.len() }
+
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+
+    pub fn values(&self) -> &[String] { &self.values }
+
+    pub fn index_of(&self, value: &str) -> Option<usize> {
+        self.values.iter().position(|v| v == value)
+    }
+
+    fn index(&self, value: &str) -> Result<usize, MultinomialError> {
+        self.index_of(value).ok_or_else(|| MultinomialError::UnknownValue(value.to_string()))
+    }
+
+    fn uniform_base_rate(&self) -> Vec<f64> {
+        vec![1.0 / self.len().max(1) as f64; self.len()]
+    }
+}
+
+/// Dirichlet evidence: observation counts per frame value plus the
+/// non-informative prior weight `W`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirichletEvidence {
+    frame: Frame,
+    counts: Vec<f64>,
+    base_rate: Vec<f64>,
+    prior_weight: f64,
+}
+
+impl DirichletEvidence {
+    /// No observations yet, uniform base rate and the default prior weight.
+    pub fn new(frame: Frame) -> Self {
+        let base_rate = frame.uniform_base_rate();
+        let counts = vec![0.0; frame.len()];
+        Self { frame, counts, base_rate, prior_weight: DEFAULT_PRIOR_WEIGHT }
+    }
+
+    pub fn with_prior_weight(mut self, w: f64) -> Self {
+        self.prior_weight = w;
+        self
+    }
+
+    pub fn with_base_rate(mut self, base_rate: Vec<f64>) -> Result<Self, MultinomialError> {
+        check_base_rate(&self.frame, &base_rate)?;
+        self.base_rate = base_rate;
+        Ok(self)
+    }
+
+    pub fn frame(&self) -> &Frame { &self.frame }
+
+    pub fn counts(&self) -> &[f64] { &self.counts }
+
+    pub fn count(&self, value: &str) -> Option<f64> {
+        self.frame.index_of(value).map(|i| self.counts[i])
+    }
+
+    /// Records `weight` observations of `value`.
+    pub fn observe(&mut self, value: &str, weight: f64) -> Result<(), MultinomialError> {
+        let i = self.frame.index(value)?;
+        self.counts[i] += weight;
+        Ok(())
+    }
+
+    /// Dirichlet parameters `α_i = r_i + a_i·W`.
+    pub fn alphas(&self) -> Vec<f64> {
+        self.counts.iter().zip(&self.base_rate).map(|(r, a)| r + a * self.prior_weight).collect()
+    }
+
+    /// Expected probability of each value, `E[p_i] = (r_i + a_i·W) / (W + Σr)`.
+    pub fn expected(&self) -> Vec<f64> {
+        let total = self.prior_weight + self.counts.iter().sum::<f64>();
+        self.alphas().into_iter().map(|alpha| alpha / total).collect()
+    }
+
+    pub fn to_opinion(&self) -> MultinomialOpinion {
+        let total = self.prior_weight + self.counts.iter().sum::<f64>();
+        if total <= 0.0 {
+            return MultinomialOpinion::vacuous_with_base_rate(self.frame.clone(), self.base_rate.clone());
+        }
+        MultinomialOpinion {
+            frame: self.frame.clone(),
+            belief: self.counts.iter().map(|r| r / total).collect(),
+            u: self.prior_weight / total,
+            base_rate: self.base_rate.clone(),
+        }
+    }
+
+    /// Inverse of [`DirichletEvidence::to_opinion`]: `r_i = W·b_i / u`.
+    /// Dogmatic opinions have no finite evidence and map to
+    /// `W / DOGMATIC_EPSILON` observations in total.
+    pub fn from_opinion(o: &MultinomialOpinion, prior_weight: f64) -> Self {
+        let u = o.u.max(DOGMATIC_EPSILON);
+        Self {
+            frame: o.frame.clone(),
+            counts: o.belief.iter().map(|b| prior_weight * b / u).collect(),
+            base_rate: o.base_rate.clone(),
+            prior_weight,
+        }
+    }
+}
+
+impl std::ops::Add for DirichletEvidence {
+    type Output = Result<DirichletEvidence, MultinomialError>;
+
+    fn add(mut self, rhs: DirichletEvidence) -> Self::Output {
+        if self.frame != rhs.frame {
+            return Err(MultinomialError::FrameMismatch);
+        }
+        for (l, r) in self.counts.iter_mut().zip(&rhs.counts) {
+            *l += r;
+        }
+        Ok(self)
+    }
+}
+
+/// Opinion over the singletons of a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultinomialOpinion {
+    frame: Frame,
+    belief: Vec<f64>,
+    u: f64,
+    base_rate: Vec<f64>,
+}
+
+impl MultinomialOpinion {
+    /// Total uncertainty with a uniform base rate.
+    pub fn vacuous(frame: Frame) -> Self {
+        let base_rate = frame.uniform_base_rate();
+        Self::vacuous_with_base_rate(frame, base_rate)
+    }
+
+    fn vacuous_with_base_rate(frame: Frame, base_rate: Vec<f64>) -> Self {
+        let belief = vec![0.0; frame.len()];
+        Self { frame, belief, u: 1.0, base_rate }
+    }
+
+    /// Validated constructor: beliefs and `u` in `[0, 1]` summing to 1 and
+    /// base rates summing to 1, one entry per frame value.
+    pub fn try_new(frame: Frame, belief: Vec<f64>, u: f64, base_rate: Vec<f64>) -> Result<Self, MultinomialError> {
+        if belief.len() != frame.len() {
+            return Err(MultinomialError::DimensionMismatch { expected: frame.len(), got: belief.len() });
+        }
+        check_base_rate(&frame, &base_rate)?;
+        if belief.iter().chain([&u]).any(|v| !v.is_finite() || !(0.0..=1.0).contains(v)) {
+            return Err(MultinomialError::OutOfRange);
+        }
+        let sum = belief.iter().sum::<f64>() + u;
+        if (sum - 1.0).abs() > ADDITIVITY_TOLERANCE {
+            return Err(MultinomialError::NotAdditive { sum });
+        }
+        Ok(Self { frame, belief, u, base_rate })
+    }
+
+    pub fn frame(&self) -> &Frame { &self.frame }
+
+    pub fn belief(&self) -> &[f64] { &self.belief }
+
+    pub fn uncertainty(&self) -> f64 { self.u }
+
+    pub fn base_rate(&self) -> &[f64] { &self.base_rate }
+
+    /// Projected probability of every value, `P_i = b_i + a_i·u`.
+    pub fn projected(&self) -> Vec<f64> {
+        self.belief.iter().zip(&self.base_rate).map(|(b, a)| b + a * self.u).collect()
+    }
+
+    /// Binomial coarsening onto `{value, ¬value}`.
+    pub fn binomial(&self, value: &str) -> Option<Opinion> {
+        let i = self.frame.index_of(value)?;
+        let b = self.belief[i];
+        let d = self.belief.iter().sum::<f64>() - b;
+        Some(Opinion::with_base_rate(b, d, self.u, self.base_rate[i]))
+    }
+
+    /// Binomial coarsening for every value of the frame.
+    pub fn to_binomials(&self) -> Vec<(String, Opinion)> {
+        self.frame
+            .values()
+            .iter()
+            .filter_map(|v| self.binomial(v).map(|o| (v.clone(), o)))
+            .collect()
+    }
+
+    /// Builds a multinomial opinion from one binomial opinion per frame
+    /// value by reading each belief as evidence for that value
+    /// (`r_i = W·b_i / u_i`). Base rates are renormalised to sum to 1.
+    pub fn from_binomials(frame: Frame, opinions: &[Opinion]) -> Result<Self, MultinomialError> {
+        if opinions.len() != frame.len() {
+            return Err(MultinomialError::DimensionMismatch { expected: frame.len(), got: opinions.len() });
+        }
+        let a_sum: f64 = opinions.iter().map(|o| o.a).sum();
+        let base_rate = if a_sum > 0.0 {
+            opinions.iter().map(|o| o.a / a_sum).collect()
+        } else {
+            frame.uniform_base_rate()
+        };
+        let mut ev = DirichletEvidence::new(frame).with_base_rate(base_rate)?;
+        for (count, o) in ev.counts.iter_mut().zip(opinions) {
+            *count = DEFAULT_PRIOR_WEIGHT * o.b / o.u.max(DOGMATIC_EPSILON);
+        }
+        Ok(ev.to_opinion())
+    }
+
+    /// Cumulative fusion: equivalent to adding the sources' Dirichlet evidence.
+    pub fn cumulative_fusion(opinions: &[Self]) -> Result<Self, MultinomialError> {
+        fuse_with(opinions, |us, excl, prod| {
+            let n = us.len() as f64;
+            let denom = excl.iter().sum::<f64>() - (n - 1.0) * prod;
+            let rate_weights = us.iter().zip(excl).map(|(u, e)| (1.0 - u) * e).collect();
+            (excl.iter().map(|e| e / denom).collect(), prod / denom, rate_weights)
+        })
+    }
+
+    /// Averaging fusion: for sources that observed the same evidence.
+    pub fn averaging_fusion(opinions: &[Self]) -> Result<Self, MultinomialError> {
+        fuse_with(opinions, |us, excl, prod| {
+            let n = us.len() as f64;
+            let denom = excl.iter().sum::<f64>();
+            (excl.iter().map(|e| e / denom).collect(), n * prod / denom, vec![1.0; us.len()])
+        })
+    }
+
+    /// Weighted fusion: sources weighted by their confidence `1 - u`.
+    pub fn weighted_fusion(opinions: &[Self]) -> Result<Self, MultinomialError> {
+        if opinions.iter().all(|o| o.u >= 1.0 - DOGMATIC_EPSILON) {
+            return fuse_with(opinions, |us, _, _| (vec![0.0; us.len()], 1.0, vec![1.0; us.len()]));
+        }
+        fuse_with(opinions, |us, excl, prod| {
+            let n = us.len() as f64;
+            let denom = excl.iter().sum::<f64>() - n * prod;
+            let sum_u: f64 = us.iter().sum();
+            (
+                us.iter().zip(excl).map(|(u, e)| (1.0 - u) * e / denom).collect(),
+                (n - sum_u) * prod / denom,
+                us.iter().map(|u| 1.0 - u).collect(),
+            )
+        })
+    }
+
+    /// Trust discounting through a binomial trust opinion, mirroring
+    /// [`crate::discounting`].
+    pub fn discount(&self, trust: Opinion) -> Self {
+        Self {
+            frame: self.frame.clone(),
+            belief: self.belief.iter().map(|b| trust.b * b).collect(),
+            u: trust.d + trust.u + trust.b * self.u,
+            base_rate: self.base_rate.clone(),
+        }
+    }
+}
+
+/// Shared shape of the product-based fusion operators: `weights` maps
+/// `(u_i, Π_{j≠i} u_j, Π u_j)` to per-source belief weights, the fused
+/// uncertainty and per-source base-rate weights, the same ones the
+/// binomial operators in [`crate::fusion`] use. Dogmatic sources are
+/// averaged with equal weight.
+fn fuse_with<F>(opinions: &[MultinomialOpinion], weights: F) -> Result<MultinomialOpinion, MultinomialError>
+where
+    F: Fn(&[f64], &[f64], f64) -> (Vec<f64>, f64, Vec<f64>),
+{
+    let first = match opinions {
+        [] => return Err(MultinomialError::Empty),
+        [o] => return Ok(o.clone()),
+        [first, ..] => first,
+    };
+    if opinions.iter().any(|o| o.frame != first.frame) {
+        return Err(MultinomialError::FrameMismatch);
+    }
+    let k = first.frame.len();
+
+    let dogmatic: Vec<&MultinomialOpinion> = opinions.iter().filter(|o| o.u <= DOGMATIC_EPSILON).collect();
+    if !dogmatic.is_empty() {
+        let g = 1.0 / dogmatic.len() as f64;
+        let belief = (0..k).map(|i| dogmatic.iter().map(|o| g * o.belief[i]).sum()).collect();
+        let base_rate = (0..k).map(|i| dogmatic.iter().map(|o| g * o.base_rate[i]).sum()).collect();
+        return Ok(MultinomialOpinion { frame: first.frame.clone(), belief, u: 0.0, base_rate });
+    }
+
+    let us: Vec<f64> = opinions.iter().map(|o| o.u).collect();
+    let prod: f64 = us.iter().product();
+    let excl: Vec<f64> = (0..us.len())
+        .map(|i| us.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, u)| u).product())
+        .collect();
+    let (w, u, rate_weights) = weights(&us, &excl, prod);
+    let belief = (0..k).map(|i| opinions.iter().zip(&w).map(|(o, w)| o.belief[i] * w).sum()).collect();
+    // without any weight (e.g. all vacuous) the base rates are averaged
+    let total: f64 = rate_weights.iter().sum();
+    let rate_weights: Vec<f64> = if total <= f64::EPSILON {
+        vec![1.0 / opinions.len() as f64; opinions.len()]
+    } else {
+        rate_weights.iter().map(|w| w / total).collect()
+    };
+    let base_rate = (0..k).map(|i| opinions.iter().zip(&rate_weights).map(|(o, w)| o.base_rate[i] * w).sum()).collect();
+    Ok(MultinomialOpinion { frame: first.frame.clone(), belief, u, base_rate })
+}
+
+fn check_base_rate(frame: &Frame, base_rate: &[f64]) -> Result<(), MultinomialError> {
+    if base_rate.len() != frame.len() {
+        return Err(MultinomialError::DimensionMismatch { expected: frame.len(), got: base_rate.len() });
+    }
+    if base_rate.iter().any(|a| !a.is_finite() || !(0.0..=1.0).contains(a)) {
+        return Err(MultinomialError::OutOfRange);
+    }
+    let sum: f64 = base_rate.iter().sum();
+    if (sum - 1.0).abs() > ADDITIVITY_TOLERANCE {
+        return Err(MultinomialError::NotAdditive { sum });
+    }
+    Ok(())
+}
+
+/// Opinion with belief on arbitrary subsets of a frame, e.g. "expert in
+/// one of the sciences" without committing to which.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperOpinion {
+    frame: Frame,
+    belief: BTreeMap<u32, f64>,
+    u: f64,
+    base_rate: Vec<f64>,
+}
+
+impl HyperOpinion {
+    /// Vacuous hyper-opinion with a uniform base rate.
+    pub fn vacuous(frame: Frame) -> Result<Self, MultinomialError> {
+        if frame.len() > MAX_HYPER_FRAME {
+            return Err(MultinomialError::FrameTooLarge(frame.len()));
+        }
+        let base_rate = frame.uniform_base_rate();
+        Ok(Self { frame, belief: BTreeMap::new(), u: 1.0, base_rate })
+    }
+
+    /// Moves `mass` from uncertainty onto the set `values`.
+    pub fn with_belief(mut self, values: &[&str], mass: f64) -> Result<Self, MultinomialError> {
+        if !mass.is_finite() || mass < 0.0 || mass > self.u + ADDITIVITY_TOLERANCE {
+            return Err(MultinomialError::OutOfRange);
+        }
+        let mut mask = 0u32;
+        for v in values {
+            mask |= 1 << self.frame.index(v)?;
+        }
+        if mask == 0 {
+            return Err(MultinomialError::Empty);
+        }
+        *self.belief.entry(mask).or_insert(0.0) += mass;
+        self.u = (self.u - mass).max(0.0);
+        Ok(self)
+    }
+
+    pub fn uncertainty(&self) -> f64 { self.u }
+
+    /// Belief assigned exactly to the set `values`.
+    pub fn belief_on(&self, values: &[&str]) -> f64 {
+        let mut mask = 0u32;
+        for v in values {
+            match self.frame.index_of(v) {
+                Some(i) => mask |= 1 << i,
+                None => return 0.0,
+            }
+        }
+        self.belief.get(&mask).copied().unwrap_or(0.0)
+    }
+
+    /// Projects composite belief onto singletons in proportion to their
+    /// base rates, `b'(x) = Σ_{y∋x} a(x)/a(y) · b(y)`.
+    pub fn to_multinomial(&self) -> MultinomialOpinion {
+        let k = self.frame.len();
+        let mut belief = vec![0.0; k];
+        for (&mask, &mass) in &self.belief {
+            let members: Vec<usize> = (0..k).filter(|i| mask & (1 << i) != 0).collect();
+            let a_set: f64 = members.iter().map(|&i| self.base_rate[i]).sum();
+            for &i in &members {
+                let share = if a_set > 0.0 { self.base_rate[i] / a_set } else { 1.0 / members.len() as f64 };
+                belief[i] += share * mass;
+            }
+        }
+        MultinomialOpinion { frame: self.frame.clone(), belief, u: self.u, base_rate: self.base_rate.clone() }
+    }
+}
+
+impl TryFrom<MultinomialOpinion> for HyperOpinion {
+    type Error = MultinomialError;
+
+    fn try_from(o: MultinomialOpinion) -> Result<Self, Self::Error> {
+        if o.frame.len() > MAX_HYPER_FRAME {
+            return Err(MultinomialError::FrameTooLarge(o.frame.len()));
+        }
+        let belief = o.belief.iter().enumerate().filter(|(_, b)| **b > 0.0).map(|(i, b)| (1u32 << i, *b)).collect();
+        Ok(Self { frame: o.frame, belief, u: o.u, base_rate: o.base_rate })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FusionKind, FusionOperator};
+    use approx::assert_relative_eq;
+
+    fn frame() -> Frame { Frame::new(["medicine", "biology", "politics"]) }
+
+    #[test]
+    fn t_catalog_frame() {
+        let f = Frame::from_catalog_json(include_str!("../../domain_catalog.json")).unwrap();
+        assert_eq!(f.len(), 19);
+        assert_eq!(f.index_of("medicine"), Some(1));
+    }
+
+    #[test]
+    fn t_dirichlet_round_trip() {
+        let mut ev = DirichletEvidence::new(frame());
+        ev.observe("medicine", 6.0).unwrap();
+        ev.observe("biology", 2.0).unwrap();
+        assert!(ev.observe("cooking", 1.0).is_err());
+        let o = ev.to_opinion();
+        assert_relative_eq!(o.belief()[0], 0.6, epsilon=1e-9);
+        assert_relative_eq!(o.belief()[1], 0.2, epsilon=1e-9);
+        assert_relative_eq!(o.uncertainty(), 0.2, epsilon=1e-9);
+        let p = o.projected();
+        let e = ev.expected();
+        for i in 0..3 {
+            assert_relative_eq!(p[i], e[i], epsilon=1e-9);
+        }
+        let back = DirichletEvidence::from_opinion(&o, DEFAULT_PRIOR_WEIGHT);
+        assert_relative_eq!(back.count("medicine").unwrap(), 6.0, epsilon=1e-9);
+        assert_relative_eq!(back.count("politics").unwrap(), 0.0, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_binomial_conversion() {
+        let o = MultinomialOpinion::try_new(frame(), vec![0.5, 0.2, 0.1], 0.2, vec![0.2, 0.3, 0.5]).unwrap();
+        let med = o.binomial("medicine").unwrap();
+        assert_relative_eq!(med.b, 0.5, epsilon=1e-9);
+        assert_relative_eq!(med.d, 0.3, epsilon=1e-9);
+        assert_relative_eq!(med.u, 0.2, epsilon=1e-9);
+        assert_relative_eq!(med.projected_probability(), o.projected()[0], epsilon=1e-9);
+
+        let binomials: Vec<Opinion> = o.to_binomials().into_iter().map(|(_, b)| b).collect();
+        let back = MultinomialOpinion::from_binomials(frame(), &binomials).unwrap();
+        for i in 0..3 {
+            assert_relative_eq!(back.belief()[i], o.belief()[i], epsilon=1e-9);
+            assert_relative_eq!(back.base_rate()[i], o.base_rate()[i], epsilon=1e-9);
+        }
+        assert_relative_eq!(back.uncertainty(), 0.2, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_cumulative_is_evidence_addition() {
+        let mut e1 = DirichletEvidence::new(frame());
+        e1.observe("medicine", 4.0).unwrap();
+        let mut e2 = DirichletEvidence::new(frame());
+        e2.observe("biology", 2.0).unwrap();
+        e2.observe("medicine", 2.0).unwrap();
+        let fused = MultinomialOpinion::cumulative_fusion(&[e1.to_opinion(), e2.to_opinion()]).unwrap();
+        let summed = (e1 + e2).unwrap().to_opinion();
+        for i in 0..3 {
+            assert_relative_eq!(fused.belief()[i], summed.belief()[i], epsilon=1e-9);
+        }
+        assert_relative_eq!(fused.uncertainty(), summed.uncertainty(), epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_averaging_and_weighted() {
+        let o = MultinomialOpinion::try_new(frame(), vec![0.5, 0.2, 0.1], 0.2, vec![0.2, 0.3, 0.5]).unwrap();
+        let avg = MultinomialOpinion::averaging_fusion(&[o.clone(), o.clone()]).unwrap();
+        assert_eq!(avg.belief(), o.belief());
+        let vac = MultinomialOpinion::vacuous(frame());
+        let w = MultinomialOpinion::weighted_fusion(&[o.clone(), vac.clone()]).unwrap();
+        assert_relative_eq!(w.belief()[0], 0.5, epsilon=1e-9);
+        assert_relative_eq!(w.uncertainty(), 0.2, epsilon=1e-9);
+        let other = MultinomialOpinion::vacuous(Frame::new(["a", "b", "c"]));
+        assert_eq!(MultinomialOpinion::averaging_fusion(&[o, other]), Err(MultinomialError::FrameMismatch));
+    }
+
+    #[test]
+    fn t_fusion_agrees_with_binomial_fusion() {
+        let o1 = MultinomialOpinion::try_new(frame(), vec![0.5, 0.2, 0.1], 0.2, vec![0.2, 0.3, 0.5]).unwrap();
+        let o2 = MultinomialOpinion::try_new(frame(), vec![0.1, 0.1, 0.2], 0.6, vec![0.6, 0.3, 0.1]).unwrap();
+        let o3 = MultinomialOpinion::try_new(frame(), vec![0.0, 0.3, 0.3], 0.4, vec![0.4, 0.4, 0.2]).unwrap();
+        let dogmatic = MultinomialOpinion::try_new(frame(), vec![0.2, 0.3, 0.5], 0.0, vec![0.1, 0.1, 0.8]).unwrap();
+        let vac = MultinomialOpinion::vacuous_with_base_rate(frame(), vec![0.7, 0.2, 0.1]);
+        type Fuse = fn(&[MultinomialOpinion]) -> Result<MultinomialOpinion, MultinomialError>;
+        let operators: [(Fuse, FusionKind); 3] = [
+            (MultinomialOpinion::cumulative_fusion, FusionKind::Cumulative),
+            (MultinomialOpinion::averaging_fusion, FusionKind::Averaging),
+            (MultinomialOpinion::weighted_fusion, FusionKind::Weighted),
+        ];
+        let sources = [vec![o1.clone(), o2.clone()], vec![o1.clone(), o2, o3], vec![o1.clone(), dogmatic], vec![vac.clone(), vac]];
+        for (multinomial, binomial) in operators {
+            for opinions in &sources {
+                let fused = multinomial(opinions).unwrap();
+                for (value, expected) in frame().values().iter().map(|v| {
+                    let per_source: Vec<Opinion> = opinions.iter().map(|o| o.binomial(v).unwrap()).collect();
+                    (v, binomial.fuse(&per_source))
+                }) {
+                    let got = fused.binomial(value).unwrap();
+                    assert_relative_eq!(got.b, expected.b, epsilon=1e-9);
+                    assert_relative_eq!(got.d, expected.d, epsilon=1e-9);
+                    assert_relative_eq!(got.u, expected.u, epsilon=1e-9);
+                    assert_relative_eq!(got.a, expected.a, epsilon=1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn t_discount() {
+        let o = MultinomialOpinion::try_new(frame(), vec![0.5, 0.2, 0.1], 0.2, vec![0.2, 0.3, 0.5]).unwrap();
+        let d = o.discount(Opinion::new(0.5, 0.25, 0.25));
+        assert_relative_eq!(d.belief()[0], 0.25, epsilon=1e-9);
+        assert_relative_eq!(d.uncertainty(), 0.6, epsilon=1e-9);
+        assert_relative_eq!(d.belief().iter().sum::<f64>() + d.uncertainty(), 1.0, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_hyper_projection() {
+        let h = HyperOpinion::vacuous(frame())
+            .unwrap()
+            .with_belief(&["medicine", "biology"], 0.5)
+            .unwrap()
+            .with_belief(&["politics"], 0.2)
+            .unwrap();
+        assert_relative_eq!(h.uncertainty(), 0.3, epsilon=1e-9);
+        assert_relative_eq!(h.belief_on(&["biology", "medicine"]), 0.5, epsilon=1e-9);
+        let m = h.to_multinomial();
+        assert_relative_eq!(m.belief()[0], 0.25, epsilon=1e-9);
+        assert_relative_eq!(m.belief()[1], 0.25, epsilon=1e-9);
+        assert_relative_eq!(m.belief()[2], 0.2, epsilon=1e-9);
+        assert!(h.with_belief(&["medicine"], 0.9).is_err());
+    }
+}
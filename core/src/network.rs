@@ -0,0 +1,357 @@
+//! Trust network analysis: A's derived opinion of X over a graph of
+//! trust edges.
+//!
+//! All simple paths up to a maximum depth are enumerated and their union is
+//! reduced as a series-parallel graph: serial hops are discounted (with hop
+//! decay) and parallel routes between the same pair of nodes are fused.
+//! Reducing rather than fusing whole paths keeps a shared sub-path from
+//! being counted once per path that uses it. When the union is not
+//! series-parallel, the most uncertain edge not on every path is pruned and
+//! the reduction retried, as in Jøsang's TNA-SL.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{discounting, hop_decay, FusionKind, FusionOperator, Opinion};
+
+/// Directed graph of trust opinions keyed by node id (e.g. DID). Adding an
+/// edge between the same pair again replaces it.
+#[derive(Debug, Clone, Default)]
+pub struct TrustGraph {
+    edges: BTreeMap<String, BTreeMap<String, Opinion>>,
+}
+
+impl TrustGraph {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn add_edge(&mut self, from: impl Into<String>, to: impl Into<String>, opinion: Opinion) {
+        self.edges.entry(from.into()).or_default().insert(to.into(), opinion);
+    }
+
+    pub fn edge(&self, from: &str, to: &str) -> Option<Opinion> {
+        self.edges.get(from).and_then(|m| m.get(to)).copied()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(BTreeMap::len).sum()
+    }
+
+    fn out_edges<'a>(&'a self, from: &str) -> impl Iterator<Item = (&'a String, &'a Opinion)> + 'a {
+        self.edges.get(from).into_iter().flat_map(|m| m.iter())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TnaConfig {
+    /// Longest path considered, in hops.
+    pub max_depth: usize,
+    /// Per-hop decay applied to every hop after the first.
+    pub hop_lambda: f64,
+    /// Operator used to fuse parallel routes.
+    pub fusion: FusionKind,
+    /// Stop enumerating after this many paths.
+    pub max_paths: usize,
+}
+
+impl Default for TnaConfig {
+    fn default() -> Self {
+        Self { max_depth: 3, hop_lambda: 0.85, fusion: FusionKind::Cumulative, max_paths: 256 }
+    }
+}
+
+/// One contributing path with the opinion it yields on its own.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrustPath {
+    pub nodes: Vec<String>,
+    pub opinion: Opinion,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedTrust {
+    pub opinion: Opinion,
+    pub paths: Vec<TrustPath>,
+    /// Edges dropped to make the path union series-parallel.
+    pub pruned_edges: Vec<(String, String)>,
+}
+
+/// Derives `source`'s opinion of `target`, or `None` when no path of at
+/// most `cfg.max_depth` hops connects them.
+pub fn derive_trust(graph: &TrustGraph, source: &str, target: &str, cfg: &TnaConfig) -> Option<DerivedTrust> {
+    if source == target || cfg.max_depth == 0 {
+        return None;
+    }
+    let mut pruned: BTreeSet<(String, String)> = BTreeSet::new();
+    loop {
+        let paths = enumerate_paths(graph, source, target, cfg, &pruned);
+        if paths.is_empty() {
+            return None;
+        }
+        match reduce(graph, &paths, source, target, cfg) {
+            Some(opinion) => {
+                let paths = paths
+                    .into_iter()
+                    .map(|nodes| {
+                        let opinion = path_opinion(graph, &nodes, cfg.hop_lambda);
+                        TrustPath { nodes, opinion }
+                    })
+                    .collect();
+                return Some(DerivedTrust { opinion, paths, pruned_edges: pruned.into_iter().collect() });
+            }
+            None => {
+                let edge = edge_to_prune(graph, &paths)?;
+                pruned.insert(edge);
+            }
+        }
+    }
+}
+
+/// Opinion along a single path: discounted hop by hop, decayed after the first.
+pub fn path_opinion(graph: &TrustGraph, nodes: &[String], hop_lambda: f64) -> Opinion {
+    let mut hops = nodes.windows(2).filter_map(|w| graph.edge(&w[0], &w[1]));
+    let first = hops.next().unwrap_or(Opinion::new(0.0, 0.0, 1.0));
+    hops.fold(first, |acc, e| hop_decay(discounting(acc, e), hop_lambda))
+}
+
+fn enumerate_paths(
+    graph: &TrustGraph,
+    source: &str,
+    target: &str,
+    cfg: &TnaConfig,
+    pruned: &BTreeSet<(String, String)>,
+) -> Vec<Vec<String>> {
+    fn dfs(
+        graph: &TrustGraph,
+        target: &str,
+        cfg: &TnaConfig,
+        pruned: &BTreeSet<(String, String)>,
+        stack: &mut Vec<String>,
+        out: &mut Vec<Vec<String>>,
+    ) {
+        if out.len() >= cfg.max_paths {
+            return;
+        }
+        let here = stack.last().cloned().unwrap_or_default();
+        for (next, _) in graph.out_edges(&here) {
+            if stack.contains(next) || pruned.contains(&(here.clone(), next.clone())) {
+                continue;
+            }
+            if next == target {
+                let mut path = stack.clone();
+                path.push(next.clone());
+                out.push(path);
+                if out.len() >= cfg.max_paths {
+                    return;
+                }
+            } else if stack.len() < cfg.max_depth {
+                stack.push(next.clone());
+                dfs(graph, target, cfg, pruned, stack, out);
+                stack.pop();
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut stack = vec![source.to_string()];
+    dfs(graph, target, cfg, pruned, &mut stack, &mut out);
+    out
+}
+
+#[derive(Clone, Copy)]
+struct Edge {
+    from: usize,
+    to: usize,
+    op: Opinion,
+}
+
+/// Series-parallel reduction of the union of `paths`; `None` if the union
+/// is not series-parallel.
+fn reduce(graph: &TrustGraph, paths: &[Vec<String>], source: &str, target: &str, cfg: &TnaConfig) -> Option<Opinion> {
+    let mut ids: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut seen: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut edges = Vec::new();
+    for path in paths {
+        for w in path.windows(2) {
+            let next_id = ids.len();
+            let from = *ids.entry(w[0].as_str()).or_insert(next_id);
+            let next_id = ids.len();
+            let to = *ids.entry(w[1].as_str()).or_insert(next_id);
+            if seen.insert((from, to)) {
+                edges.push(Edge { from, to, op: graph.edge(&w[0], &w[1])? });
+            }
+        }
+    }
+    let (s, t) = (ids[source], ids[target]);
+
+    loop {
+        if let [e] = edges.as_slice() {
+            if e.from == s && e.to == t {
+                return Some(e.op);
+            }
+        }
+        if let Some(merged) = parallel_step(&edges, cfg.fusion) {
+            edges = merged;
+            continue;
+        }
+        if let Some(merged) = series_step(&edges, s, t, cfg.hop_lambda) {
+            edges = merged;
+            continue;
+        }
+        return None;
+    }
+}
+
+/// Fuses the first group of parallel edges found, if any.
+fn parallel_step(edges: &[Edge], fusion: FusionKind) -> Option<Vec<Edge>> {
+    let (i, j) = (0..edges.len())
+        .flat_map(|i| (i + 1..edges.len()).map(move |j| (i, j)))
+        .find(|&(i, j)| edges[i].from == edges[j].from && edges[i].to == edges[j].to)?;
+    let (from, to) = (edges[i].from, edges[j].to);
+    let group: Vec<Opinion> = edges.iter().filter(|e| e.from == from && e.to == to).map(|e| e.op).collect();
+    let mut out: Vec<Edge> = edges.iter().filter(|e| !(e.from == from && e.to == to)).copied().collect();
+    out.insert(i.min(out.len()), Edge { from, to, op: fusion.fuse(&group) });
+    Some(out)
+}
+
+/// Collapses the first inner node with exactly one incoming and one
+/// outgoing edge into a single discounted edge.
+fn series_step(edges: &[Edge], s: usize, t: usize, hop_lambda: f64) -> Option<Vec<Edge>> {
+    let nodes: BTreeSet<usize> = edges.iter().flat_map(|e| [e.from, e.to]).collect();
+    let v = nodes.into_iter().find(|&v| {
+        v != s && v != t && edges.iter().filter(|e| e.to == v).count() == 1 && edges.iter().filter(|e| e.from == v).count() == 1
+    })?;
+    let incoming = edges.iter().find(|e| e.to == v)?;
+    let outgoing = edges.iter().find(|e| e.from == v)?;
+    let mut out: Vec<Edge> = edges.iter().filter(|e| e.to != v && e.from != v).copied().collect();
+    if incoming.from != outgoing.to {
+        let op = hop_decay(discounting(incoming.op, outgoing.op), hop_lambda);
+        out.push(Edge { from: incoming.from, to: outgoing.to, op });
+    }
+    Some(out)
+}
+
+/// Most uncertain edge that does not lie on every path.
+fn edge_to_prune(graph: &TrustGraph, paths: &[Vec<String>]) -> Option<(String, String)> {
+    let mut uses: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for path in paths {
+        for w in path.windows(2) {
+            *uses.entry((w[0].clone(), w[1].clone())).or_default() += 1;
+        }
+    }
+    uses.into_iter()
+        .filter(|(_, n)| *n < paths.len())
+        .filter_map(|(k, _)| graph.edge(&k.0, &k.1).map(|o| (k, o.u)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    const L: f64 = 0.85;
+
+    fn assert_op(o: Opinion, e: Opinion) {
+        assert_relative_eq!(o.b, e.b, epsilon=1e-9);
+        assert_relative_eq!(o.d, e.d, epsilon=1e-9);
+        assert_relative_eq!(o.u, e.u, epsilon=1e-9);
+    }
+
+    fn hop(a: Opinion, b: Opinion) -> Opinion { hop_decay(discounting(a, b), L) }
+
+    fn fuse(a: Opinion, b: Opinion) -> Opinion { FusionKind::Cumulative.fuse(&[a, b]) }
+
+    #[test]
+    fn t_direct_and_chain() {
+        let ab = Opinion::new(0.7, 0.1, 0.2);
+        let bx = Opinion::new(0.6, 0.2, 0.2);
+        let mut g = TrustGraph::new();
+        g.add_edge("A", "B", ab);
+        g.add_edge("B", "X", bx);
+        let cfg = TnaConfig::default();
+        assert_op(derive_trust(&g, "A", "B", &cfg).unwrap().opinion, ab);
+        let d = derive_trust(&g, "A", "X", &cfg).unwrap();
+        assert_op(d.opinion, hop(ab, bx));
+        assert_eq!(d.paths.len(), 1);
+        assert_eq!(d.paths[0].nodes, ["A", "B", "X"]);
+        assert!(derive_trust(&g, "X", "A", &cfg).is_none());
+    }
+
+    #[test]
+    fn t_disjoint_paths_fuse() {
+        let mut g = TrustGraph::new();
+        let (ab, bx) = (Opinion::new(0.7, 0.1, 0.2), Opinion::new(0.6, 0.2, 0.2));
+        let (ac, cx) = (Opinion::new(0.5, 0.2, 0.3), Opinion::new(0.8, 0.0, 0.2));
+        g.add_edge("A", "B", ab);
+        g.add_edge("B", "X", bx);
+        g.add_edge("A", "C", ac);
+        g.add_edge("C", "X", cx);
+        let d = derive_trust(&g, "A", "X", &TnaConfig::default()).unwrap();
+        assert_op(d.opinion, fuse(hop(ab, bx), hop(ac, cx)));
+        assert_eq!(d.paths.len(), 2);
+        assert!(d.pruned_edges.is_empty());
+    }
+
+    #[test]
+    fn t_shared_prefix_counted_once() {
+        let mut g = TrustGraph::new();
+        let ab = Opinion::new(0.7, 0.1, 0.2);
+        let (bc, cx) = (Opinion::new(0.6, 0.2, 0.2), Opinion::new(0.8, 0.1, 0.1));
+        let (bd, dx) = (Opinion::new(0.5, 0.3, 0.2), Opinion::new(0.7, 0.0, 0.3));
+        g.add_edge("A", "B", ab);
+        g.add_edge("B", "C", bc);
+        g.add_edge("C", "X", cx);
+        g.add_edge("B", "D", bd);
+        g.add_edge("D", "X", dx);
+        let d = derive_trust(&g, "A", "X", &TnaConfig::default()).unwrap();
+        let expected = hop(ab, fuse(hop(bc, cx), hop(bd, dx)));
+        assert_op(d.opinion, expected);
+        // naive fusion of whole paths would count A→B twice
+        let naive = fuse(d.paths[0].opinion, d.paths[1].opinion);
+        assert!((naive.b - expected.b).abs() > 1e-3);
+    }
+
+    #[test]
+    fn t_shared_suffix_counted_once() {
+        let mut g = TrustGraph::new();
+        let (ab, bd) = (Opinion::new(0.7, 0.1, 0.2), Opinion::new(0.6, 0.2, 0.2));
+        let (ac, cd) = (Opinion::new(0.5, 0.2, 0.3), Opinion::new(0.8, 0.1, 0.1));
+        let dx = Opinion::new(0.9, 0.0, 0.1);
+        g.add_edge("A", "B", ab);
+        g.add_edge("B", "D", bd);
+        g.add_edge("A", "C", ac);
+        g.add_edge("C", "D", cd);
+        g.add_edge("D", "X", dx);
+        let d = derive_trust(&g, "A", "X", &TnaConfig::default()).unwrap();
+        assert_op(d.opinion, hop(fuse(hop(ab, bd), hop(ac, cd)), dx));
+    }
+
+    #[test]
+    fn t_bridge_is_pruned() {
+        // Wheatstone bridge: not series-parallel
+        let mut g = TrustGraph::new();
+        g.add_edge("A", "B", Opinion::new(0.7, 0.1, 0.2));
+        g.add_edge("A", "C", Opinion::new(0.6, 0.1, 0.3));
+        g.add_edge("B", "C", Opinion::new(0.2, 0.1, 0.7));
+        g.add_edge("B", "X", Opinion::new(0.8, 0.1, 0.1));
+        g.add_edge("C", "X", Opinion::new(0.7, 0.1, 0.2));
+        let d = derive_trust(&g, "A", "X", &TnaConfig::default()).unwrap();
+        assert_eq!(d.pruned_edges, [("B".to_string(), "C".to_string())]);
+        assert_eq!(d.paths.len(), 2);
+        assert_relative_eq!(d.opinion.b + d.opinion.d + d.opinion.u, 1.0, epsilon=1e-9);
+    }
+
+    #[test]
+    fn t_max_depth() {
+        let mut g = TrustGraph::new();
+        let o = Opinion::new(0.7, 0.1, 0.2);
+        g.add_edge("A", "B", o);
+        g.add_edge("B", "C", o);
+        g.add_edge("C", "X", o);
+        let shallow = TnaConfig { max_depth: 2, ..TnaConfig::default() };
+        assert!(derive_trust(&g, "A", "X", &shallow).is_none());
+        assert!(derive_trust(&g, "A", "X", &TnaConfig::default()).is_some());
+    }
+}
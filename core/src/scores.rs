@@ -0,0 +1,385 @@
+//! The per-user score document produced by the scoring pipeline and served
+//! to the UI. Both the API (inline scoring) and the workers build it from
+//! these types, so a field typo fails to compile instead of being stored.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{time_decay, BetaEvidence, FacetFusion, OpinionRecord, ScoresError, DEFAULT_PRIOR_WEIGHT};
+
+/// Current [`UserScores::version`]. Bump when the document shape changes
+/// and keep reading older versions.
+///
+/// 2: per-item `observations`.
+pub const SCORES_VERSION: u32 = 2;
+
+const DAY_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Documents from before versioning.
+fn unversioned() -> u32 { 1 }
+
+/// Opinions on each scored facet.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Facets {
+    pub accuracy: OpinionRecord,
+    pub civility: OpinionRecord,
+}
+
+impl Facets {
+    /// Names accepted wherever a facet is selected by string.
+    pub const NAMES: [&'static str; 2] = ["accuracy", "civility"];
+
+    /// Facets with no evidence yet.
+    pub fn vacuous() -> Self {
+        let vacuous = OpinionRecord::from_evidence(0.0, 0.0, DEFAULT_PRIOR_WEIGHT);
+        Self { accuracy: vacuous, civility: vacuous }
+    }
+
+    /// `(name, record)` pairs in a stable order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &OpinionRecord)> {
+        [("accuracy", &self.accuracy), ("civility", &self.civility)].into_iter()
+    }
+
+    pub fn get(&self, facet: &str) -> Option<&OpinionRecord> {
+        self.iter().find(|(name, _)| *name == facet).map(|(_, rec)| rec)
+    }
+
+    pub fn get_mut(&mut self, facet: &str) -> Option<&mut OpinionRecord> {
+        match facet {
+            "accuracy" => Some(&mut self.accuracy),
+            "civility" => Some(&mut self.civility),
+            _ => None,
+        }
+    }
+}
+
+/// Estimated expertise in one catalog domain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpertiseScore {
+    pub domain: String,
+    pub score: f64,
+}
+
+/// A post the classifier could not settle, kept for review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EvidenceItem {
+    pub cid: String,
+    pub domain: String,
+    pub classification: String,
+    #[serde(default)]
+    pub evidence_refs: Vec<String>,
+}
+
+/// Ages one item's evidence: its counts halve every `half_life_days`.
+/// The scaling is linear, so how evidence is split into items doesn't
+/// change the aged total.
+pub fn decay_evidence(evidence: BetaEvidence, age_days: f64, half_life_days: f64) -> BetaEvidence {
+    evidence.scaled(0.5f64.powf(age_days / half_life_days))
+}
+
+/// Evidence one scored item contributed to one facet, with the time it was
+/// observed so it can be aged at read time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Observation {
+    pub facet: String,
+    /// Epoch millis.
+    pub ts: i64,
+    pub alpha: f64,
+    pub beta: f64,
+    /// Classifier that made the observation; a facet's sources are fused
+    /// with its [`FacetFusion`] operator. Absent in older documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Provenance of one classified post: which classifier said what about it,
+/// for which facet, and how much that counted. Keyed by
+/// `(did, facet, cid)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvidenceRecord {
+    /// The scored user (the post's author).
+    pub did: String,
+    pub facet: String,
+    pub cid: String,
+    pub uri: String,
+    pub domain: String,
+    /// Classifier name and version, e.g. `gemini-claim-v1`.
+    pub classifier: String,
+    /// Classifier output, e.g. `accurate`, `inaccurate`, `contested`.
+    pub label: String,
+    pub weight: f64,
+    #[serde(default)]
+    pub evidence_refs: Vec<String>,
+    /// When the post was made, epoch millis.
+    pub ts: i64,
+}
+
+impl EvidenceRecord {
+    /// `(alpha, beta)` this record adds to its facet: positive labels count
+    /// `weight` for, negative ones against, anything else nothing.
+    pub fn counts(&self) -> (f64, f64) {
+        match self.label.as_str() {
+            "accurate" | "civil" => (self.weight, 0.0),
+            "inaccurate" | "uncivil" => (0.0, self.weight),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// [`counts`](Self::counts) as evidence under the default prior, for
+    /// fusing with [`FacetFusion::fuse_evidence`] by classifier.
+    pub fn evidence(&self) -> BetaEvidence {
+        let (alpha, beta) = self.counts();
+        BetaEvidence::new(alpha, beta)
+    }
+
+    /// The timestamped evidence this record feeds into read-time decay,
+    /// or `None` if it doesn't count either way.
+    pub fn observation(&self) -> Option<Observation> {
+        let (alpha, beta) = self.counts();
+        (alpha + beta > 0.0).then(|| Observation { facet: self.facet.clone(), ts: self.ts, alpha, beta, source: Some(self.classifier.clone()) })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UserScores {
+    /// Document version; documents written before versioning read as 1.
+    #[serde(default = "unversioned")]
+    pub version: u32,
+    pub did: String,
+    pub handle: String,
+    /// Epoch millis; 0 when never scored.
+    pub updated_at: i64,
+    pub facets: Facets,
+    pub bot_prob: f64,
+    #[serde(default)]
+    pub expertise: Vec<ExpertiseScore>,
+    #[serde(default)]
+    pub evidence: Vec<EvidenceItem>,
+    /// Since version 2; empty for older documents.
+    #[serde(default)]
+    pub observations: Vec<Observation>,
+}
+
+impl UserScores {
+    /// Placeholder served before the first scoring run.
+    pub fn vacuous(did: &str) -> Self {
+        Self {
+            version: SCORES_VERSION,
+            did: did.to_string(),
+            handle: did.to_string(),
+            updated_at: 0,
+            facets: Facets::vacuous(),
+            bot_prob: 0.0,
+            expertise: Vec::new(),
+            evidence: Vec::new(),
+            observations: Vec::new(),
+        }
+    }
+
+    /// Facet opinions as of `now_ms`, with each observation aged by
+    /// [`decay_evidence`] under its facet's half-life and the results
+    /// combined by [`FacetFusion::fuse_evidence`]. Facets without
+    /// observations (documents written before version 2) are aged as a
+    /// whole from `updated_at` with [`time_decay`]. A facet whose half-life is `None` or not positive is
+    /// returned unchanged.
+    pub fn decayed_facets(&self, now_ms: i64, half_life_days: impl Fn(&str) -> Option<f64>, fusion: &FacetFusion) -> Facets {
+        let decay = |facet: &'static str, raw: &OpinionRecord| -> OpinionRecord {
+            let Some(half_life) = half_life_days(facet).filter(|h| *h > 0.0) else { return *raw };
+            let age_days = |ts: i64| ((now_ms - ts) as f64 / DAY_MS).max(0.0);
+            let items = self.observations.iter().filter(|o| o.facet == facet).map(|o| {
+                let item = BetaEvidence::new(o.alpha, o.beta).with_base_rate(raw.opinion.a);
+                (o.source.as_deref().unwrap_or_default(), decay_evidence(item, age_days(o.ts), half_life))
+            });
+            let decayed = match fusion.fuse_evidence(facet, items) {
+                Some(fused) => fused.to_opinion(),
+                None => time_decay(raw.opinion, age_days(self.updated_at), half_life),
+            };
+            match BetaEvidence::from_opinion(&decayed, DEFAULT_PRIOR_WEIGHT) {
+                Some(ev) => ev.into(),
+                None => decayed.into(),
+            }
+        };
+        Facets { accuracy: decay("accuracy", &self.facets.accuracy), civility: decay("civility", &self.facets.civility) }
+    }
+
+    /// Checks what the type system can't: version, DID shape, ranges and
+    /// facet opinions. Evidence/opinion consistency is left to
+    /// [`OpinionRecord::check_consistency`].
+    pub fn validate(&self) -> Result<(), ScoresError> {
+        if self.version > SCORES_VERSION {
+            return Err(ScoresError::UnsupportedVersion(self.version));
+        }
+        if !self.did.starts_with("did:") || self.did.len() <= 4 {
+            return Err(ScoresError::InvalidDid(self.did.clone()));
+        }
+        let unit = |field: String, value: f64| {
+            if value.is_finite() && (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(ScoresError::OutOfRange { field, value })
+            }
+        };
+        unit("botProb".into(), self.bot_prob)?;
+        for o in &self.observations {
+            if !Facets::NAMES.contains(&o.facet.as_str()) {
+                return Err(ScoresError::UnknownFacet(o.facet.clone()));
+            }
+            for (name, value) in [("alpha", o.alpha), ("beta", o.beta)] {
+                if !value.is_finite() || value < 0.0 {
+                    return Err(ScoresError::InvalidEvidence { field: name, value });
+                }
+            }
+        }
+        for e in &self.expertise {
+            unit(format!("expertise.{}", e.domain), e.score)?;
+        }
+        for (facet, rec) in self.facets.iter() {
+            rec.opinion.validate().map_err(|error| ScoresError::Facet { facet, error })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use serde_json::json;
+
+    const NOW: i64 = 1_000 * DAY_MS as i64;
+
+    fn scored(observations: Vec<Observation>) -> UserScores {
+        let alpha = observations.iter().map(|o| o.alpha).sum();
+        let beta = observations.iter().map(|o| o.beta).sum();
+        let record = OpinionRecord::from_evidence(alpha, beta, DEFAULT_PRIOR_WEIGHT);
+        UserScores {
+            updated_at: NOW,
+            facets: Facets { accuracy: record, civility: record },
+            observations,
+            ..UserScores::vacuous("did:plc:abc")
+        }
+    }
+
+    fn obs(age_days: i64, alpha: f64, beta: f64) -> Observation {
+        Observation { facet: "accuracy".into(), ts: NOW - age_days * DAY_MS as i64, alpha, beta, source: None }
+    }
+
+    #[test]
+    fn t_fresh_observations_keep_the_raw_opinion() {
+        let scores = scored(vec![obs(0, 3.0, 1.0), obs(0, 2.0, 0.0)]);
+        let decayed = scores.decayed_facets(NOW, |_| Some(30.0), &FacetFusion::default());
+        assert_relative_eq!(decayed.accuracy.opinion.b, scores.facets.accuracy.opinion.b, epsilon = 1e-9);
+        assert_relative_eq!(decayed.accuracy.alpha.unwrap(), 5.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn t_old_observations_weigh_less() {
+        let scores = scored(vec![obs(730, 0.0, 10.0), obs(7, 10.0, 0.0)]);
+        let raw = scores.facets.accuracy.opinion;
+        let decayed = scores.decayed_facets(NOW, |f| (f == "accuracy").then_some(90.0), &FacetFusion::default());
+        assert_relative_eq!(raw.b, raw.d, epsilon = 1e-9);
+        assert!(decayed.accuracy.opinion.b > 5.0 * decayed.accuracy.opinion.d);
+        assert!(decayed.accuracy.opinion.u > raw.u);
+        // no half-life configured for civility
+        assert_eq!(decayed.civility, scores.facets.civility);
+    }
+
+    #[test]
+    fn t_splitting_evidence_does_not_change_its_decay() {
+        let whole = scored(vec![obs(60, 6.0, 2.0)]);
+        let split = scored(vec![obs(60, 1.0, 0.0), obs(60, 5.0, 1.0), obs(60, 0.0, 1.0)]);
+        let at = |s: &UserScores| s.decayed_facets(NOW, |_| Some(30.0), &FacetFusion::default()).accuracy;
+        let (whole, split) = (at(&whole), at(&split));
+        assert_relative_eq!(whole.alpha.unwrap(), 1.5, epsilon = 1e-9);
+        assert_relative_eq!(whole.beta.unwrap(), 0.5, epsilon = 1e-9);
+        assert_relative_eq!(split.alpha.unwrap(), whole.alpha.unwrap(), epsilon = 1e-9);
+        assert_relative_eq!(split.beta.unwrap(), whole.beta.unwrap(), epsilon = 1e-9);
+        assert_relative_eq!(split.opinion.b, whole.opinion.b, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn t_sources_are_fused_per_facet() {
+        let from = |facet: &str, source: &str, alpha: f64, beta: f64| Observation { facet: facet.into(), ts: NOW, alpha, beta, source: Some(source.into()) };
+        let mut scores = scored(Vec::new());
+        scores.observations = vec![
+            from("accuracy", "a", 4.0, 2.0),
+            from("accuracy", "b", 8.0, 4.0),
+            from("civility", "a", 4.0, 2.0),
+            from("civility", "b", 8.0, 4.0),
+        ];
+        let decayed = scores.decayed_facets(NOW, |_| Some(30.0), &FacetFusion::default());
+        // cumulative for accuracy, averaging for civility
+        assert_relative_eq!(decayed.accuracy.alpha.unwrap(), 12.0, epsilon = 1e-9);
+        assert_relative_eq!(decayed.civility.alpha.unwrap(), 6.0, epsilon = 1e-9);
+        assert_relative_eq!(decayed.civility.beta.unwrap(), 3.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn t_evidence_labels_map_to_counts() {
+        let mut rec = EvidenceRecord {
+            did: "did:plc:abc".into(),
+            facet: "accuracy".into(),
+            cid: "bafy1".into(),
+            uri: "at://did:plc:abc/app.bsky.feed.post/1".into(),
+            domain: "politics".into(),
+            classifier: "gemini-claim-v1".into(),
+            label: "inaccurate".into(),
+            weight: 0.5,
+            evidence_refs: vec![],
+            ts: 3,
+        };
+        assert_eq!(rec.counts(), (0.0, 0.5));
+        assert_eq!(rec.observation().unwrap().ts, 3);
+        rec.label = "contested".into();
+        assert_eq!(rec.observation(), None);
+    }
+
+    #[test]
+    fn t_legacy_documents_decay_from_updated_at() {
+        let mut scores = scored(Vec::new());
+        scores.facets.accuracy = OpinionRecord::from_evidence(8.0, 0.0, DEFAULT_PRIOR_WEIGHT);
+        let decayed = scores.decayed_facets(NOW + 30 * DAY_MS as i64, |_| Some(30.0), &FacetFusion::default());
+        assert_relative_eq!(decayed.accuracy.opinion.b, 0.4, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn t_round_trips_and_defaults_version() {
+        let mut scores = UserScores::vacuous("did:plc:abc");
+        scores.expertise.push(ExpertiseScore { domain: "medicine".into(), score: 0.4 });
+        let v = serde_json::to_value(&scores).unwrap();
+        assert_eq!(v["botProb"], 0.0);
+        assert_eq!(v["facets"]["accuracy"]["alpha"], 0.0);
+        assert_eq!(serde_json::from_value::<UserScores>(v.clone()).unwrap(), scores);
+
+        let mut legacy = v;
+        legacy.as_object_mut().unwrap().remove("version");
+        assert_eq!(serde_json::from_value::<UserScores>(legacy).unwrap().version, 1);
+    }
+
+    #[test]
+    fn t_typos_and_bad_values_are_rejected() {
+        let mut v = serde_json::to_value(UserScores::vacuous("did:plc:abc")).unwrap();
+        v["bot_prob"] = json!(0.2);
+        assert!(serde_json::from_value::<UserScores>(v).is_err());
+
+        let mut scores = UserScores::vacuous("did:plc:abc");
+        scores.bot_prob = 1.2;
+        assert_eq!(scores.validate().unwrap_err().rule(), "out_of_range");
+        scores.bot_prob = 0.1;
+        scores.facets.civility.opinion.u = 0.5;
+        assert_eq!(scores.validate().unwrap_err().rule(), "not_additive");
+        assert_eq!(UserScores::vacuous("bob").validate().unwrap_err().rule(), "invalid_did");
+        scores.facets = Facets::vacuous();
+        scores.observations.push(Observation { facet: "kindness".into(), ts: 0, alpha: 1.0, beta: 0.0, source: None });
+        assert_eq!(scores.validate().unwrap_err().rule(), "unknown_facet");
+        scores.observations[0] = Observation { facet: "civility".into(), ts: 0, alpha: -1.0, beta: 0.0, source: None };
+        assert_eq!(scores.validate().unwrap_err().rule(), "invalid_evidence");
+        scores.version = SCORES_VERSION + 1;
+        assert!(matches!(scores.validate(), Err(ScoresError::UnsupportedVersion(_))));
+    }
+}
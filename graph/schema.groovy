@@ -1,5 +1,7 @@
-// JanusGraph schema for Trust & Reputation MVP
-// Run in Gremlin Console connected to JanusGraph
+// JanusGraph schema for Trust & Reputation MVP (schema version 1)
+// Reference only: the API applies this and later versions on startup from
+// api/src/services/graph/migrations.rs (disable with GRAPH_MIGRATE=false).
+// Add new keys/labels/indexes there, not here.
 
 mgmt = graph.openManagement()
 
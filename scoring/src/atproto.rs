@@ -10,7 +10,19 @@ fn base_url() -> String {
 struct ProfileResp { did: String }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct PostRecord { pub text: Option<String> }
+pub struct PostRecord {
+    pub text: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<String>,
+}
+
+impl PostRecord {
+    /// `createdAt` as epoch millis, if present and well-formed.
+    pub fn created_at_ms(&self) -> Option<i64> {
+        let ts = chrono::DateTime::parse_from_rfc3339(self.created_at.as_deref()?).ok()?;
+        Some(ts.timestamp_millis())
+    }
+}
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct FeedPost {
@@ -0,0 +1,267 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use trustsystem_core as core;
+
+use crate::atproto::{self, FeedPost};
+use crate::gemini::{self, GeminiResponse};
+
+/// Classifier names recorded on evidence.
+pub const ACCURACY_CLASSIFIER: &str = "gemini-claim-v1";
+pub const CIVILITY_CLASSIFIER: &str = "keyword-civility-v1";
+
+/// Posts fetched per job.
+pub const POST_LIMIT: usize = 25;
+/// Most posts sent to the claim classifier per job.
+const MAX_CLAIM_CALLS: usize = 25;
+/// Posts always sent to the classifier, claim-like or not.
+const ALWAYS_CLASSIFY: usize = 10;
+const DOMAIN: &str = "politics";
+/// Placeholder until a bot classifier exists.
+const BOT_PROB: f64 = 0.12;
+
+/// Where score jobs run: `SCORING_MODE=inline` (default) has the API score
+/// lookups itself; `worker` leaves every job to the workers binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoringMode {
+    Inline,
+    Worker,
+}
+
+impl ScoringMode {
+    pub fn from_env() -> Self {
+        match std::env::var("SCORING_MODE").unwrap_or_default().as_str() {
+            "worker" | "workers" => ScoringMode::Worker,
+            _ => ScoringMode::Inline,
+        }
+    }
+}
+
+/// How each facet fuses evidence from several classifiers: the defaults of
+/// [`core::FacetFusion`], overridden by `FACET_FUSION`, e.g.
+/// `civility=weighted`.
+pub fn facet_fusion_from_env() -> core::FacetFusion {
+    let spec = std::env::var("FACET_FUSION").unwrap_or_default();
+    core::FacetFusion::parse(&spec).unwrap_or_else(|e| {
+        tracing::warn!(%spec, error = %e, "ignoring FACET_FUSION");
+        core::FacetFusion::default()
+    })
+}
+
+#[async_trait]
+pub trait ClaimClassifier: Send + Sync {
+    async fn classify(&self, text: &str, domain: &str) -> Result<GeminiResponse>;
+}
+
+/// [`gemini::analyze_claim`]; neutral without `GEMINI_API_KEY`.
+pub struct GeminiClassifier;
+
+#[async_trait]
+impl ClaimClassifier for GeminiClassifier {
+    async fn classify(&self, text: &str, domain: &str) -> Result<GeminiResponse> {
+        gemini::analyze_claim(text, domain).await
+    }
+}
+
+/// Progress callbacks; counts are running totals for the job.
+#[async_trait]
+pub trait Observer: Send + Sync {
+    async fn classified(&self, _posts: u32) {}
+    async fn facets_computed(&self, _facets: u32) {}
+    async fn error(&self, _error: String) {}
+}
+
+pub struct NoopObserver;
+
+impl Observer for NoopObserver {}
+
+/// A post as stored in the content graph, with its claim label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoredPost {
+    pub cid: String,
+    pub uri: String,
+    pub author_did: String,
+    pub domain: String,
+    pub classification: String,
+    pub created_at: i64,
+}
+
+/// Everything one scoring run produces, applied by the API as a unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scored {
+    pub scores: core::UserScores,
+    pub evidence: Vec<core::EvidenceRecord>,
+    pub content: Vec<ScoredPost>,
+}
+
+/// Recent posts by `did`, falling back to `handle` when the DID can't be
+/// read (e.g. it didn't resolve).
+pub async fn fetch_posts(did: &str, handle: &str) -> Result<Vec<FeedPost>> {
+    let posts = atproto::fetch_recent_posts(did, POST_LIMIT).await.unwrap_or_default();
+    if !posts.is_empty() || handle == did {
+        return Ok(posts);
+    }
+    atproto::fetch_recent_posts(handle, POST_LIMIT).await
+}
+
+/// Lightweight claim heuristic to widen coverage.
+fn looks_like_claim(text: &str) -> bool {
+    let t = text.to_lowercase();
+    if t.len() < 40 { return false; }
+    let has_digit = t.chars().any(|c| c.is_ascii_digit());
+    let has_link = t.contains("http://") || t.contains("https://");
+    let cues = [" is ", " are ", " was ", " were ", " will ", " has ", " have ", "%", " million", " billion", " according to ", " reports ", " says "];
+    let cue_hit = cues.iter().any(|p| t.contains(p));
+    let words = t.split_whitespace().count();
+    words >= 8 && (has_digit || has_link || cue_hit)
+}
+
+/// Classifies `posts` and builds the score document for `did`, fusing each
+/// facet's classifiers with `fusion`. A failed classification is reported
+/// to `observer` and counted as neutral.
+pub async fn score_posts(
+    did: &str,
+    handle: &str,
+    posts: &[FeedPost],
+    classifier: &dyn ClaimClassifier,
+    fusion: &core::FacetFusion,
+    observer: &dyn Observer,
+    now: i64,
+) -> Scored {
+    let mut evidence: Vec<core::EvidenceItem> = Vec::new();
+    let mut records: Vec<core::EvidenceRecord> = Vec::new();
+    let mut content: Vec<ScoredPost> = Vec::new();
+
+    let mut claim_calls = 0usize;
+    for (idx, p) in posts.iter().enumerate() {
+        let text = p.record.as_ref().and_then(|r| r.text.clone()).unwrap_or_default();
+        if text.is_empty() { continue; }
+        let posted_at = p.record.as_ref().and_then(|r| r.created_at_ms()).unwrap_or(now);
+        let record = |facet: &str, classifier: &str, label: &str, refs: Vec<String>| core::EvidenceRecord {
+            did: did.to_string(),
+            facet: facet.into(),
+            cid: p.cid.clone(),
+            uri: p.uri.clone(),
+            domain: DOMAIN.into(),
+            classifier: classifier.into(),
+            label: label.into(),
+            weight: 1.0,
+            evidence_refs: refs,
+            ts: posted_at,
+        };
+        if (idx < ALWAYS_CLASSIFY || looks_like_claim(&text)) && claim_calls < MAX_CLAIM_CALLS {
+            claim_calls += 1;
+            let r = match classifier.classify(&text, DOMAIN).await {
+                Ok(r) => r,
+                Err(e) => {
+                    observer.error(format!("classify {}: {e:#}", p.cid)).await;
+                    GeminiResponse { classification: "neutral".into(), evidence_refs: vec![] }
+                }
+            };
+            observer.classified(claim_calls as u32).await;
+            if r.classification == "contested" {
+                evidence.push(core::EvidenceItem {
+                    cid: p.cid.clone(),
+                    domain: DOMAIN.into(),
+                    classification: "contested".into(),
+                    evidence_refs: r.evidence_refs.clone(),
+                });
+            }
+            records.push(record("accuracy", ACCURACY_CLASSIFIER, &r.classification, r.evidence_refs.clone()));
+            content.push(ScoredPost {
+                cid: p.cid.clone(),
+                uri: p.uri.clone(),
+                author_did: p.author.did.clone(),
+                domain: DOMAIN.into(),
+                classification: r.classification,
+                created_at: posted_at,
+            });
+        }
+        // simplistic civility heuristic (only increment on posts > 5 chars)
+        if text.len() > 5 {
+            let label = if text.to_lowercase().contains("idiot") { "uncivil" } else { "civil" };
+            records.push(record("civility", CIVILITY_CLASSIFIER, label, Vec::new()));
+        }
+    }
+
+    let observations = records.iter().filter_map(core::EvidenceRecord::observation).collect();
+    let facet = |name: &str| -> core::OpinionRecord {
+        let items = records.iter().filter(|r| r.facet == name).map(|r| (r.classifier.as_str(), r.evidence()));
+        fusion.fuse_evidence(name, items).unwrap_or_default().into()
+    };
+    let (o_acc, o_civ) = (facet("accuracy"), facet("civility"));
+    observer.facets_computed(core::Facets::NAMES.len() as u32).await;
+    let scores = core::UserScores {
+        version: core::SCORES_VERSION,
+        did: did.to_string(),
+        handle: handle.to_string(),
+        updated_at: now,
+        facets: core::Facets { accuracy: o_acc, civility: o_civ },
+        bot_prob: BOT_PROB,
+        expertise: Vec::new(),
+        evidence,
+        observations,
+    };
+    Scored { scores, evidence: records, content }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atproto::{Author, PostRecord};
+    use std::sync::Mutex;
+
+    struct Fixed;
+
+    #[async_trait]
+    impl ClaimClassifier for Fixed {
+        async fn classify(&self, text: &str, _domain: &str) -> Result<GeminiResponse> {
+            if text.contains("fail") {
+                anyhow::bail!("quota");
+            }
+            let classification = if text.contains("false") { "inaccurate" } else { "accurate" };
+            Ok(GeminiResponse { classification: classification.into(), evidence_refs: vec![] })
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl Observer for Recorder {
+        async fn classified(&self, posts: u32) { self.0.lock().unwrap().push(format!("classified {posts}")); }
+        async fn facets_computed(&self, facets: u32) { self.0.lock().unwrap().push(format!("facets {facets}")); }
+        async fn error(&self, error: String) { self.0.lock().unwrap().push(error); }
+    }
+
+    fn post(cid: &str, text: &str) -> FeedPost {
+        FeedPost {
+            cid: cid.into(),
+            uri: format!("at://did:a/app.bsky.feed.post/{cid}"),
+            author: Author { did: "did:a".into() },
+            record: Some(PostRecord { text: Some(text.into()), created_at: Some("2024-01-01T00:00:00Z".into()) }),
+        }
+    }
+
+    #[tokio::test]
+    async fn t_posts_become_one_score_document() {
+        let posts = [post("1", "the sky is blue"), post("2", "this is false, idiot"), post("3", "fail"), post("4", "")];
+        let observer = Recorder::default();
+        let scored = score_posts("did:a", "a.test", &posts, &Fixed, &core::FacetFusion::default(), &observer, 0).await;
+
+        let acc = scored.scores.facets.accuracy;
+        let civ = scored.scores.facets.civility;
+        assert_eq!((acc.alpha, acc.beta), (Some(1.0), Some(1.0)));
+        assert_eq!((civ.alpha, civ.beta), (Some(1.0), Some(1.0)));
+        assert_eq!(scored.content.len(), 3);
+        assert_eq!(scored.evidence.iter().filter(|r| r.facet == "accuracy").count(), 3);
+        assert_eq!(scored.evidence[0].ts, 1_704_067_200_000);
+        assert!(scored.scores.validate().is_ok());
+        assert_eq!(
+            *observer.0.lock().unwrap(),
+            ["classified 1", "classified 2", "classify 3: quota", "classified 3", "facets 2"]
+        );
+    }
+}
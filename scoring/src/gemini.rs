@@ -1,22 +1,19 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::Value;
 use std::time::Duration;
 
-#[derive(Serialize)]
-pub struct GeminiRequest { pub text: String, pub domain: String }
-
 #[derive(Deserialize, Debug, Clone)]
 pub struct GeminiResponse {
     pub classification: String, // "accurate" | "inaccurate" | "contested" | "neutral"
-    #[serde(default)]
-    pub evidenceRefs: Vec<String>,
+    #[serde(default, rename = "evidenceRefs")]
+    pub evidence_refs: Vec<String>,
 }
 
 pub async fn analyze_claim(_text: &str, _domain: &str) -> Result<GeminiResponse> {
     // Call Gemini if API key exists; otherwise return neutral
     let api_key = match std::env::var("GEMINI_API_KEY") { Ok(k) if !k.is_empty() => k, _ => {
-        return Ok(GeminiResponse { classification: "neutral".into(), evidenceRefs: vec![] });
+        return Ok(GeminiResponse { classification: "neutral".into(), evidence_refs: vec![] });
     }};
 
     let endpoint = format!("https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent?key={}", api_key);
@@ -45,7 +42,7 @@ Post (domain={domain}):
     let resp = client.post(&endpoint).json(&body).send().await?;
     if !resp.status().is_success() {
         // fall back quietly
-        return Ok(GeminiResponse { classification: "neutral".into(), evidenceRefs: vec![] });
+        return Ok(GeminiResponse { classification: "neutral".into(), evidence_refs: vec![] });
     }
     let v: Value = resp.json().await?;
     // Try to parse the first candidate text as JSON
@@ -55,7 +52,7 @@ Post (domain={domain}):
             return Ok(parsed);
         }
     }
-    Ok(GeminiResponse { classification: "neutral".into(), evidenceRefs: vec![] })
+    Ok(GeminiResponse { classification: "neutral".into(), evidence_refs: vec![] })
 }
 
 
@@ -0,0 +1,9 @@
+//! Post fetching, classification and score computation shared by the API's
+//! inline mode and the workers binary, so a job scores the same whichever
+//! process runs it.
+
+pub mod atproto;
+pub mod engine;
+pub mod gemini;
+
+pub use engine::{facet_fusion_from_env, fetch_posts, score_posts, ClaimClassifier, GeminiClassifier, NoopObserver, Observer, Scored, ScoredPost, ScoringMode};
@@ -5,7 +5,7 @@ export default function LookupPage() {
   const [handle, setHandle] = useState("");
   const [resp, setResp] = useState<any>(null);
   const [loading, setLoading] = useState(false);
-  const pollRef = useRef<any>(null);
+  const eventsRef = useRef<EventSource | null>(null);
   const API_BASE = process.env.NEXT_PUBLIC_API_BASE || "http://localhost:8080";
 
   async function submit() {
@@ -19,25 +19,23 @@ export default function LookupPage() {
     const j = await r.json();
     setResp(j);
     setLoading(false);
-    // Begin polling job status until done
+    // Follow job progress until it finishes
     if (j?.jobId) {
-      if (pollRef.current) clearInterval(pollRef.current);
-      pollRef.current = setInterval(async () => {
-        try {
-          const s = await fetch(`${API_BASE}/internal/jobs/score/${j.jobId}`);
-          const sj = await s.json();
-          setResp((prev: any) => ({ ...prev, status: sj.status || prev?.status }));
-          if (sj.status === "done") {
-            clearInterval(pollRef.current);
-          }
-        } catch {}
-      }, 800);
+      if (eventsRef.current) eventsRef.current.close();
+      const es = new EventSource(`${API_BASE}/v1/jobs/${j.jobId}/events`);
+      eventsRef.current = es;
+      es.addEventListener("job", (ev) => {
+        const job = JSON.parse((ev as MessageEvent).data);
+        setResp((prev: any) => ({ ...prev, status: job.status, progress: job.progress, error: job.error }));
+        if (["done", "dead_letter", "superseded"].includes(job.status)) es.close();
+      });
+      es.onerror = () => es.close();
     }
   }
 
   useEffect(() => {
     return () => {
-      if (pollRef.current) clearInterval(pollRef.current);
+      if (eventsRef.current) eventsRef.current.close();
     };
   }, []);
 
@@ -68,6 +66,13 @@ export default function LookupPage() {
           <div>
             status: <b>{resp.status}</b>
           </div>
+          {resp.progress && (
+            <div className="text-sm text-gray-600">
+              posts fetched {resp.progress.postsFetched}, classified{" "}
+              {resp.progress.postsClassified}, facets {resp.progress.facetsComputed}
+            </div>
+          )}
+          {resp.error && <div className="text-sm text-red-700">{resp.error}</div>}
           <div className="mt-2">
             <a
               className="text-blue-600 underline"
@@ -0,0 +1,248 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use reqwest::Client;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use crate::pipeline;
+use crate::transport::{self, JobSource};
+
+/// Daemon settings, read from the environment with defaults.
+#[derive(Debug, Clone)]
+pub struct DaemonConfig {
+    /// Jobs processed at once (`WORKER_CONCURRENCY`), one per lane.
+    pub concurrency: usize,
+    /// First wait after an empty poll (`WORKER_IDLE_MIN_MS`); doubles on
+    /// each further empty poll up to `WORKER_IDLE_MAX_MS`.
+    pub idle_min: Duration,
+    pub idle_max: Duration,
+    /// How long in-flight jobs get to finish after SIGTERM before their
+    /// leases are released (`WORKER_DRAIN_MS`).
+    pub drain: Duration,
+    /// Where `/healthz` and `/readyz` are served (`WORKER_HEALTH_ADDR`).
+    pub health_addr: String,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            idle_min: Duration::from_millis(500),
+            idle_max: Duration::from_secs(10),
+            drain: Duration::from_secs(25),
+            health_addr: "0.0.0.0:8081".to_string(),
+        }
+    }
+}
+
+impl DaemonConfig {
+    pub fn from_env() -> Self {
+        fn ms(name: &str) -> Option<Duration> {
+            std::env::var(name).ok()?.parse().ok().map(Duration::from_millis)
+        }
+        let d = Self::default();
+        Self {
+            concurrency: std::env::var("WORKER_CONCURRENCY").ok().and_then(|v| v.parse().ok()).filter(|&n| n > 0).unwrap_or(d.concurrency),
+            idle_min: ms("WORKER_IDLE_MIN_MS").unwrap_or(d.idle_min),
+            idle_max: ms("WORKER_IDLE_MAX_MS").unwrap_or(d.idle_max),
+            drain: ms("WORKER_DRAIN_MS").unwrap_or(d.drain),
+            health_addr: std::env::var("WORKER_HEALTH_ADDR").unwrap_or(d.health_addr),
+        }
+    }
+
+    /// Wait before the next poll after one that found nothing.
+    pub fn next_idle(&self, current: Duration) -> Duration {
+        (current * 2).clamp(self.idle_min, self.idle_max.max(self.idle_min))
+    }
+}
+
+/// What the health endpoint reports.
+struct Health {
+    concurrency: usize,
+    in_flight: AtomicUsize,
+    /// Last attempt to get a job reached the API (and broker).
+    source_ok: AtomicBool,
+    draining: AtomicBool,
+}
+
+impl Health {
+    fn ready(&self) -> bool {
+        self.source_ok.load(Ordering::Relaxed) && !self.draining.load(Ordering::Relaxed)
+    }
+
+    fn report(&self) -> serde_json::Value {
+        serde_json::json!({
+            "ready": self.ready(),
+            "draining": self.draining.load(Ordering::Relaxed),
+            "sourceReachable": self.source_ok.load(Ordering::Relaxed),
+            "inFlight": self.in_flight.load(Ordering::Relaxed),
+            "concurrency": self.concurrency,
+        })
+    }
+}
+
+/// Liveness: up as long as the process serves requests.
+async fn healthz(State(health): State<Arc<Health>>) -> impl IntoResponse {
+    Json(health.report())
+}
+
+/// Readiness: jobs can be fetched and the worker isn't draining.
+async fn readyz(State(health): State<Arc<Health>>) -> impl IntoResponse {
+    let status = if health.ready() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(health.report()))
+}
+
+async fn serve_health(addr: String, health: Arc<Health>) {
+    let app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(health);
+    match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => {
+            info!(%addr, "health endpoint listening");
+            if let Err(e) = axum::serve(listener, app).await {
+                warn!(error = %e, "health endpoint stopped");
+            }
+        }
+        Err(e) => warn!(%addr, error = %e, "health endpoint unavailable"),
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!(error = %e, "ctrl-c handler unavailable");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let term = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut s) => { s.recv().await; }
+            Err(e) => {
+                warn!(error = %e, "SIGTERM handler unavailable");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let term = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = term => {}
+    }
+}
+
+/// Everything a lane needs besides its source.
+struct Lane {
+    client: Client,
+    api_base: String,
+    worker: String,
+    cfg: DaemonConfig,
+    health: Arc<Health>,
+}
+
+impl Lane {
+    /// Runs one job at a time until `shutdown` flips; a job still running
+    /// when `stop` flips is abandoned and its lease released.
+    async fn run(self, source: Arc<dyn JobSource>, mut shutdown: watch::Receiver<bool>, stop: watch::Receiver<bool>) {
+        let mut idle = self.cfg.idle_min;
+        loop {
+            let next = tokio::select! {
+                _ = shutdown.wait_for(|s| *s) => break,
+                next = source.next() => next,
+            };
+            let lease = match next {
+                Ok(lease) => {
+                    self.health.source_ok.store(true, Ordering::Relaxed);
+                    lease
+                }
+                Err(e) => {
+                    if self.health.source_ok.swap(false, Ordering::Relaxed) {
+                        warn!(error = %e, "fetching jobs failed");
+                    }
+                    None
+                }
+            };
+            let Some(lease) = lease else {
+                tokio::select! {
+                    _ = shutdown.wait_for(|s| *s) => break,
+                    _ = tokio::time::sleep(idle) => {}
+                }
+                idle = self.cfg.next_idle(idle);
+                continue;
+            };
+            idle = self.cfg.idle_min;
+            info!(job_id = %lease.job_id, did = %lease.did, "picked job");
+            self.health.in_flight.fetch_add(1, Ordering::Relaxed);
+            let mut stop = stop.clone();
+            let stopped = async move {
+                let _ = stop.wait_for(|s| *s).await;
+            };
+            let report = pipeline::run_leased(&self.client, &self.api_base, &self.worker, &lease, stopped).await;
+            if let Err(e) = source.finish(&lease, &report).await {
+                warn!(job_id = %lease.job_id, error = %e, "could not acknowledge job");
+            }
+            self.health.in_flight.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Runs `cfg.concurrency` lanes until `shutdown` resolves, then drains: no
+/// new jobs are taken, in-flight jobs get `cfg.drain` to finish and any
+/// still running release their lease so another worker picks them up
+/// straight away.
+pub async fn run(api_base: &str, cfg: DaemonConfig, shutdown: impl std::future::Future<Output = ()>) {
+    let client = Client::new();
+    let worker = pipeline::worker_id();
+    let health = Arc::new(Health {
+        concurrency: cfg.concurrency,
+        in_flight: AtomicUsize::new(0),
+        source_ok: AtomicBool::new(false),
+        draining: AtomicBool::new(false),
+    });
+    tokio::spawn(serve_health(cfg.health_addr.clone(), health.clone()));
+    info!(%worker, concurrency = cfg.concurrency, "worker daemon started");
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let mut lanes = JoinSet::new();
+    for source in transport::sources(&client, api_base, &worker, cfg.concurrency) {
+        let lane = Lane { client: client.clone(), api_base: api_base.to_string(), worker: worker.clone(), cfg: cfg.clone(), health: health.clone() };
+        lanes.spawn(lane.run(source, shutdown_rx.clone(), stop_rx.clone()));
+    }
+    shutdown.await;
+
+    health.draining.store(true, Ordering::Relaxed);
+    let _ = shutdown_tx.send(true);
+    let in_flight = health.in_flight.load(Ordering::Relaxed);
+    info!(in_flight, drain_ms = cfg.drain.as_millis() as u64, "shutting down; draining jobs");
+    let drain = async { while lanes.join_next().await.is_some() {} };
+    if tokio::time::timeout(cfg.drain, drain).await.is_err() {
+        warn!(in_flight = health.in_flight.load(Ordering::Relaxed), "drain timed out; releasing leases");
+        let _ = stop_tx.send(true);
+        while lanes.join_next().await.is_some() {}
+    }
+    info!("worker daemon stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_idle_backoff_doubles_up_to_the_cap() {
+        let cfg = DaemonConfig { idle_min: Duration::from_millis(500), idle_max: Duration::from_secs(3), ..DaemonConfig::default() };
+        let mut idle = cfg.idle_min;
+        let mut waits = Vec::new();
+        for _ in 0..5 {
+            waits.push(idle.as_millis());
+            idle = cfg.next_idle(idle);
+        }
+        assert_eq!(waits, [500, 1000, 2000, 3000, 3000]);
+    }
+}
@@ -1,5 +1,7 @@
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+mod daemon;
 mod pipeline;
+mod transport;
 
 #[tokio::main]
 async fn main() {
@@ -8,11 +10,11 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    tracing::info!("workers service started");
     let api_base = std::env::var("API_BASE").unwrap_or_else(|_| "http://localhost:8080".to_string());
-    // One-shot worker to avoid hanging long-running process during guided runs
-    let _ = pipeline::run_once(&api_base).await;
+    // `--once` takes a single job and exits, for guided runs and scripts
+    if std::env::args().any(|a| a == "--once") {
+        let _ = pipeline::run_once(&api_base).await;
+        return;
+    }
+    daemon::run(&api_base, daemon::DaemonConfig::from_env(), daemon::shutdown_signal()).await;
 }
-
-
-
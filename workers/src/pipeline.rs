@@ -1,83 +1,203 @@
-use trustsystem_core as core;
+use async_trait::async_trait;
+use trustsystem_scoring as scoring;
 use anyhow::Result;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
+use std::future::Future;
 use std::time::Duration;
 use tracing::{info, warn};
 
-#[derive(Deserialize)]
-struct LookupResp { did: String, jobId: String, status: String }
+/// Name sent with claims and heartbeats, which the API checks lease
+/// ownership against: `WORKER_ID`, or `<hostname>-<pid>` so replicas whose
+/// processes share a pid (PID 1 in containers) still differ.
+pub fn worker_id() -> String {
+    std::env::var("WORKER_ID")
+        .ok()
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("{}-{}", hostname(), std::process::id()))
+}
 
-pub async fn run_loop(api_base: &str) -> Result<()> {
-    let client = Client::new();
-    loop {
-        if let Some((job_id, did)) = try_pop_job(api_base, &client).await? {
-            info!(%job_id, %did, "picked job");
-            let _ = process_job(&client, api_base, &job_id, &did).await;
-        } else {
-            tokio::time::sleep(Duration::from_millis(500)).await;
-        }
-    }
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "worker".to_string())
+}
+
+/// A leased job as returned by `/internal/jobs/next`.
+pub struct Lease {
+    pub job_id: String,
+    pub did: String,
+    /// How long the lease lasts from the moment it was granted.
+    pub lease_ms: i64,
 }
 
 pub async fn run_once(api_base: &str) -> Result<bool> {
     let client = Client::new();
-    if let Some((job_id, did)) = try_pop_job(api_base, &client).await? {
-        info!(%job_id, %did, "picked job (oneshot)");
-        let _ = process_job(&client, api_base, &job_id, &did).await;
+    let worker = worker_id();
+    if let Some(lease) = try_pop_job(api_base, &client, &worker).await? {
+        info!(job_id = %lease.job_id, did = %lease.did, "picked job (oneshot)");
+        run_leased(&client, api_base, &worker, &lease, std::future::pending()).await;
         return Ok(true);
     }
     Ok(false)
 }
 
-async fn try_pop_job(api_base: &str, client: &Client) -> Result<Option<(String, String)>> {
-    let resp = client.get(format!("{}/internal/jobs/next", api_base)).send().await?;
+pub async fn try_pop_job(api_base: &str, client: &Client, worker: &str) -> Result<Option<Lease>> {
+    let resp = client.get(format!("{}/internal/jobs/next", api_base)).query(&[("worker", worker)]).send().await?;
     if resp.status().as_u16() == 204 { return Ok(None); }
     if resp.status().is_success() {
-        let v: serde_json::Value = resp.json().await?;
-        let job_id = v.get("jobId").and_then(|v| v.as_str()).unwrap_or_default().to_string();
-        let did = v.get("did").and_then(|v| v.as_str()).unwrap_or_default().to_string();
-        if !job_id.is_empty() && !did.is_empty() { return Ok(Some((job_id, did))); }
-    } else {
-        warn!(status=?resp.status(), "next job request failed");
+        return Ok(parse_lease(&resp.json().await?));
     }
+    warn!(status=?resp.status(), "next job request failed");
     Ok(None)
 }
 
-pub async fn process_job(client: &Client, api_base: &str, job_id: &str, did: &str) -> Result<()> {
-    // Simulate scoring: compute some fake counts and subjective logic
-    let alpha_acc = 5.0;
-    let beta_acc = 1.0;
-    let o_acc = core::evidence_to_opinion(alpha_acc, beta_acc, 2.0);
-    let alpha_civ = 8.0;
-    let beta_civ = 2.0;
-    let o_civ = core::evidence_to_opinion(alpha_civ, beta_civ, 2.0);
-
-    let scores = json!({
-        "did": did,
-        "handle": did,
-        "updatedAt": (chrono::Utc::now().timestamp_millis()),
-        "facets": {
-            "accuracy": {"alpha": alpha_acc as i64, "beta": beta_acc as i64, "b": o_acc.b, "d": o_acc.d, "u": o_acc.u},
-            "civility": {"alpha": alpha_civ as i64, "beta": beta_civ as i64, "b": o_civ.b, "d": o_civ.d, "u": o_civ.u}
-        },
-        "botProb": 0.12,
-        "expertise": [
-            {"domain":"politics","score":0.71},
-            {"domain":"technology","score":0.63},
-            {"domain":"medicine","score":0.12}
-        ],
-        "evidence": []
-    });
-
-    let _ = client.post(format!("{}/internal/upsert/scores", api_base))
-        .json(&scores)
+/// Reads a leased job as the API returns it.
+pub fn parse_lease(v: &serde_json::Value) -> Option<Lease> {
+    let job_id = v.get("jobId").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let did = v.get("did").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let expires = v.get("leaseExpiresAt").and_then(|v| v.as_i64()).unwrap_or_default();
+    let updated = v.get("updatedAt").and_then(|v| v.as_i64()).unwrap_or_default();
+    if job_id.is_empty() || did.is_empty() {
+        return None;
+    }
+    Some(Lease { job_id, did, lease_ms: (expires - updated).max(1_000) })
+}
+
+/// How a leased job ended, as reported to the API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Done,
+    Failed(String),
+    /// Handed back unfinished on shutdown.
+    Released,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Done => "done",
+            Outcome::Failed(_) => "fail",
+            Outcome::Released => "release",
+        }
+    }
+}
+
+/// What the API made of an outcome report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ack {
+    Accepted,
+    /// Refused with this status, e.g. 409 once the lease was lost.
+    Rejected(u16),
+    /// The report never got an answer.
+    Unreachable,
+}
+
+/// A job's outcome and whether the API recorded it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub outcome: Outcome,
+    pub ack: Ack,
+}
+
+/// Processes the job while heartbeating at a third of the lease, then
+/// reports success or failure so the API can retry or dead-letter it. If
+/// `stop` resolves first the job is abandoned and its lease released.
+pub async fn run_leased(client: &Client, api_base: &str, worker: &str, lease: &Lease, stop: impl Future<Output = ()>) -> Report {
+    let heartbeat = {
+        let (client, url, worker) = (client.clone(), format!("{}/internal/jobs/score/{}/heartbeat", api_base, lease.job_id), worker.to_string());
+        let every = Duration::from_millis((lease.lease_ms / 3) as u64);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(every).await;
+                match client.post(&url).json(&serde_json::json!({"worker": worker})).send().await {
+                    Ok(r) if r.status().is_success() => {}
+                    Ok(r) => { warn!(status = ?r.status(), "heartbeat rejected"); break; }
+                    Err(e) => warn!(error = %e, "heartbeat failed"),
+                }
+            }
+        })
+    };
+    let res = tokio::select! {
+        res = process_job(client, api_base, worker, &lease.job_id, &lease.did) => Some(res),
+        _ = stop => None,
+    };
+    heartbeat.abort();
+    let outcome = match res {
+        Some(Ok(())) => Outcome::Done,
+        Some(Err(e)) => Outcome::Failed(format!("{e:#}")),
+        None => Outcome::Released,
+    };
+    let body = match &outcome {
+        Outcome::Failed(error) => serde_json::json!({"worker": worker, "error": error}),
+        _ => serde_json::json!({"worker": worker}),
+    };
+    let path = outcome.as_str();
+    let ack = match client.post(format!("{}/internal/jobs/score/{}/{}", api_base, lease.job_id, path)).json(&body).send().await {
+        Ok(r) if r.status().is_success() => {
+            info!(job_id = %lease.job_id, outcome = path, "reported job");
+            Ack::Accepted
+        }
+        Ok(r) => {
+            warn!(job_id = %lease.job_id, status = ?r.status(), "job report rejected");
+            Ack::Rejected(r.status().as_u16())
+        }
+        Err(e) => {
+            warn!(job_id = %lease.job_id, error = %e, "job report failed");
+            Ack::Unreachable
+        }
+    };
+    Report { outcome, ack }
+}
+
+/// Sends engine progress to `/internal/jobs/score/:id/progress`.
+struct HttpObserver<'a> {
+    client: &'a Client,
+    url: String,
+    worker: &'a str,
+}
+
+impl HttpObserver<'_> {
+    async fn report(&self, mut body: serde_json::Value) {
+        body["worker"] = self.worker.into();
+        if let Err(e) = self.client.post(&self.url).json(&body).send().await {
+            warn!(error = %e, "progress report failed");
+        }
+    }
+}
+
+#[async_trait]
+impl scoring::Observer for HttpObserver<'_> {
+    async fn classified(&self, posts: u32) {
+        self.report(serde_json::json!({"postsClassified": posts})).await;
+    }
+
+    async fn facets_computed(&self, facets: u32) {
+        self.report(serde_json::json!({"facetsComputed": facets})).await;
+    }
+
+    async fn error(&self, error: String) {
+        self.report(serde_json::json!({"error": error})).await;
+    }
+}
+
+pub async fn process_job(client: &Client, api_base: &str, worker: &str, job_id: &str, did: &str) -> Result<()> {
+    let observer = HttpObserver { client, url: format!("{}/internal/jobs/score/{}/progress", api_base, job_id), worker };
+    let posts = scoring::fetch_posts(did, did).await?;
+    observer.report(serde_json::json!({"postsFetched": posts.len()})).await;
+    let now = chrono::Utc::now().timestamp_millis();
+    let fusion = scoring::facet_fusion_from_env();
+    let scored = scoring::score_posts(did, did, &posts, &scoring::GeminiClassifier, &fusion, &observer, now).await;
+
+    let resp = client.post(format!("{}/internal/upsert/scored", api_base))
+        .json(&scored)
         .send().await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        anyhow::bail!("scores rejected ({status}): {}", resp.text().await.unwrap_or_default());
+    }
     info!(%job_id, %did, "upserted scores");
-    let _ = client.post(format!("{}/internal/jobs/score/{}/done", api_base, job_id)).send().await?;
-    info!(%job_id, "marked done");
     Ok(())
 }
-
-
@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use rdkafka::{Message, Offset, TopicPartitionList};
+use reqwest::Client;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::{result_message, JobSource, Settle};
+use crate::pipeline::{self, Lease, Report};
+
+/// Message position, for committing once the job is reported.
+type Position = (String, i32, i64);
+
+/// Consumes `KAFKA_JOBS_TOPIC` (default `score.jobs`) as part of
+/// `KAFKA_GROUP` (default `trustsystem-workers`). A message's offset is
+/// committed only after the API has recorded its job done or failed, so a
+/// worker that dies mid-job leaves it to whoever takes over the partition
+/// and an unrecorded outcome gets the message read again; failed jobs are
+/// sent again by the API once their backoff passes. Recorded outcomes are
+/// produced to `KAFKA_RESULTS_TOPIC` (default `score.results`).
+///
+/// The consumer isn't polled while a job runs, so jobs must finish within
+/// `max.poll.interval.ms` (librdkafka's default is five minutes).
+pub struct KafkaSource {
+    consumer: StreamConsumer,
+    producer: FutureProducer,
+    results_topic: String,
+    client: Client,
+    api_base: String,
+    worker: String,
+    current: Mutex<Option<Position>>,
+}
+
+/// Where the consumers connect and what they read and write.
+pub struct KafkaConfig {
+    pub brokers: String,
+    pub jobs_topic: String,
+    pub results_topic: String,
+    pub group: String,
+}
+
+impl KafkaConfig {
+    /// `KAFKA_BROKERS`, plus the topics and group above.
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            brokers: std::env::var("KAFKA_BROKERS").ok().filter(|b| !b.is_empty()).ok_or_else(|| anyhow!("KAFKA_BROKERS is not set"))?,
+            jobs_topic: std::env::var("KAFKA_JOBS_TOPIC").unwrap_or_else(|_| "score.jobs".to_string()),
+            results_topic: std::env::var("KAFKA_RESULTS_TOPIC").unwrap_or_else(|_| "score.results".to_string()),
+            group: std::env::var("KAFKA_GROUP").unwrap_or_else(|_| "trustsystem-workers".to_string()),
+        })
+    }
+}
+
+pub fn sources(cfg: &KafkaConfig, client: &Client, api_base: &str, worker: &str, lanes: usize) -> Result<Vec<Arc<dyn JobSource>>> {
+    Ok(consumers(cfg, client, api_base, worker, lanes)?
+        .into_iter()
+        .map(|source| -> Arc<dyn JobSource> { source })
+        .collect())
+}
+
+/// One group member per lane, sharing a producer for results.
+fn consumers(cfg: &KafkaConfig, client: &Client, api_base: &str, worker: &str, lanes: usize) -> Result<Vec<Arc<KafkaSource>>> {
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &cfg.brokers)
+        .set("message.timeout.ms", "5000")
+        .create()?;
+    (0..lanes)
+        .map(|lane| {
+            let consumer: StreamConsumer = ClientConfig::new()
+                .set("bootstrap.servers", &cfg.brokers)
+                .set("group.id", &cfg.group)
+                .set("client.id", format!("{worker}-{lane}"))
+                .set("enable.auto.commit", "false")
+                .set("auto.offset.reset", "earliest")
+                .create()?;
+            consumer.subscribe(&[&cfg.jobs_topic])?;
+            Ok(Arc::new(KafkaSource {
+                consumer,
+                producer: producer.clone(),
+                results_topic: cfg.results_topic.clone(),
+                client: client.clone(),
+                api_base: api_base.to_string(),
+                worker: worker.to_string(),
+                current: Mutex::new(None),
+            }))
+        })
+        .collect()
+}
+
+impl KafkaSource {
+    /// Leases the delivered job, or says what to do with the message when
+    /// the API refuses.
+    async fn claim(&self, job_id: &str) -> Result<Result<Lease, Settle>> {
+        let url = format!("{}/internal/jobs/score/{}/claim", self.api_base, job_id);
+        let resp = self.client.post(url).json(&serde_json::json!({"worker": self.worker})).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            debug!(%job_id, status = status.as_u16(), "claim refused");
+            return Ok(Err(Settle::refused_claim(status.as_u16())));
+        }
+        Ok(pipeline::parse_lease(&resp.json().await?).ok_or(Settle::Commit))
+    }
+
+    fn settle(&self, (topic, partition, offset): &Position, settle: Settle) -> Result<()> {
+        match settle {
+            Settle::Commit => {
+                let mut tpl = TopicPartitionList::new();
+                tpl.add_partition_offset(topic, *partition, Offset::Offset(offset + 1))?;
+                self.consumer.commit(&tpl, CommitMode::Async)?;
+            }
+            Settle::Redeliver => self.consumer.seek(topic, *partition, Offset::Offset(*offset), Duration::from_secs(5))?,
+            Settle::Leave => {}
+        }
+        Ok(())
+    }
+
+    async fn publish_result(&self, result: &serde_json::Value, did: &str) -> Result<()> {
+        let payload = serde_json::to_vec(result)?;
+        let record = FutureRecord::to(&self.results_topic).key(did).payload(&payload);
+        self.producer
+            .send(record, Timeout::After(Duration::from_secs(5)))
+            .await
+            .map_err(|(e, _)| anyhow!("produce to {}: {e}", self.results_topic))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobSource for KafkaSource {
+    async fn next(&self) -> Result<Option<Lease>> {
+        loop {
+            let (position, job_id) = {
+                let msg = self.consumer.recv().await?;
+                let job_id = msg
+                    .payload()
+                    .and_then(|p| serde_json::from_slice::<serde_json::Value>(p).ok())
+                    .and_then(|v| v.get("jobId").and_then(|id| id.as_str()).map(str::to_string));
+                ((msg.topic().to_string(), msg.partition(), msg.offset()), job_id)
+            };
+            let Some(job_id) = job_id else {
+                warn!(partition = position.1, offset = position.2, "skipping malformed job message");
+                self.settle(&position, Settle::Commit)?;
+                continue;
+            };
+            match self.claim(&job_id).await {
+                Ok(Ok(lease)) => {
+                    *self.current.lock().map_err(|_| anyhow!("lane state poisoned"))? = Some(position);
+                    return Ok(Some(lease));
+                }
+                Ok(Err(settle)) => {
+                    self.settle(&position, settle)?;
+                    if settle == Settle::Redeliver {
+                        return Err(anyhow!("claim {job_id} failed"));
+                    }
+                }
+                Err(e) => {
+                    // read it again once the API is back
+                    self.settle(&position, Settle::Redeliver)?;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn finish(&self, lease: &Lease, report: &Report) -> Result<()> {
+        let Some(position) = self.current.lock().map_err(|_| anyhow!("lane state poisoned"))?.take() else { return Ok(()) };
+        if let Some(result) = result_message(lease, report, &self.worker, chrono::Utc::now().timestamp_millis()) {
+            if let Err(e) = self.publish_result(&result, &lease.did).await {
+                warn!(job_id = %lease.job_id, error = %e, "job result not published");
+            }
+        }
+        self.settle(&position, Settle::reported(report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Ack, Outcome};
+    use axum::{extract::Path, routing::post, Json, Router};
+    use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
+    use rdkafka::client::DefaultClientContext;
+
+    /// Stands in for the API: every claim is granted.
+    async fn fake_api() -> String {
+        async fn claim(Path(id): Path<String>) -> Json<serde_json::Value> {
+            Json(serde_json::json!({"jobId": id, "did": format!("did:test:{id}"), "leaseExpiresAt": 60_000, "updatedAt": 0}))
+        }
+        let app = Router::new().route("/internal/jobs/score/:id/claim", post(claim));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}")
+    }
+
+    /// Offset `source` has committed on `partition`, if any.
+    async fn committed(source: Arc<KafkaSource>, topic: &str, partition: i32) -> Option<i64> {
+        let topic = topic.to_string();
+        tokio::task::spawn_blocking(move || {
+            let tpl = source.consumer.committed(Duration::from_secs(5)).unwrap();
+            match tpl.find_partition(&topic, partition)?.offset() {
+                Offset::Offset(o) => Some(o),
+                _ => None,
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Kafka or Redpanda; set KAFKA_TEST_BROKERS"]
+    async fn t_kafka_lanes_share_the_group_and_commit_recorded_outcomes() {
+        let Ok(brokers) = std::env::var("KAFKA_TEST_BROKERS") else { return };
+        let run = uuid::Uuid::new_v4().simple().to_string();
+        let cfg = KafkaConfig {
+            brokers: brokers.clone(),
+            jobs_topic: format!("score.jobs.test.{run}"),
+            results_topic: format!("score.results.test.{run}"),
+            group: format!("test-{run}"),
+        };
+        let admin: AdminClient<DefaultClientContext> = ClientConfig::new().set("bootstrap.servers", &brokers).create().unwrap();
+        let topics = [NewTopic::new(&cfg.jobs_topic, 2, TopicReplication::Fixed(1)), NewTopic::new(&cfg.results_topic, 1, TopicReplication::Fixed(1))];
+        admin.create_topics(&topics, &AdminOptions::new()).await.unwrap();
+
+        let producer: FutureProducer = ClientConfig::new().set("bootstrap.servers", &brokers).create().unwrap();
+        for (partition, job) in [(0, "a"), (1, "b"), (0, "c")] {
+            let payload = serde_json::to_vec(&serde_json::json!({"jobId": job})).unwrap();
+            let record = FutureRecord::to(&cfg.jobs_topic).partition(partition).key(job).payload(&payload);
+            producer.send(record, Timeout::After(Duration::from_secs(5))).await.map_err(|(e, _)| e).unwrap();
+        }
+
+        let api = fake_api().await;
+        let lanes = consumers(&cfg, &Client::new(), &api, "w-test", 2).unwrap();
+        let next = |source: Arc<KafkaSource>| async move { tokio::time::timeout(Duration::from_secs(30), source.next()).await.unwrap().unwrap().unwrap() };
+        let (released, done) = tokio::join!(next(lanes[0].clone()), next(lanes[1].clone()));
+
+        // each lane owns one of the two partitions
+        let partition = |lease: &Lease| if lease.job_id == "b" { 1 } else { 0 };
+        assert_ne!(partition(&released), partition(&done), "lanes got {} and {}", released.job_id, done.job_id);
+
+        // a released job stays uncommitted; a recorded one is committed
+        lanes[0].finish(&released, &Report { outcome: Outcome::Released, ack: Ack::Accepted }).await.unwrap();
+        lanes[1].finish(&done, &Report { outcome: Outcome::Done, ack: Ack::Accepted }).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(committed(lanes[0].clone(), &cfg.jobs_topic, partition(&released)).await, None);
+        assert_eq!(committed(lanes[1].clone(), &cfg.jobs_topic, partition(&done)).await, Some(1));
+
+        // the recorded outcome is on the results topic
+        let reader: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &brokers)
+            .set("group.id", format!("test-results-{run}"))
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .unwrap();
+        reader.subscribe(&[&cfg.results_topic]).unwrap();
+        let msg = tokio::time::timeout(Duration::from_secs(30), reader.recv()).await.unwrap().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(msg.payload().unwrap()).unwrap();
+        assert_eq!(body["jobId"], done.job_id.as_str());
+        assert_eq!(body["status"], "done");
+        assert_eq!(body["worker"], "w-test");
+    }
+}
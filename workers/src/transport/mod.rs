@@ -0,0 +1,160 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::pipeline::{self, Lease, Report};
+#[cfg(any(feature = "kafka", test))]
+use crate::pipeline::{Ack, Outcome};
+
+#[cfg(feature = "kafka")]
+mod kafka;
+
+/// Where a lane gets its jobs. Every job handed out is already leased to
+/// this worker through the API, whichever way it was delivered.
+#[async_trait]
+pub trait JobSource: Send + Sync {
+    /// Next job, or `None` when there's nothing to do right now.
+    async fn next(&self) -> Result<Option<Lease>>;
+    /// Called once the job's outcome has been reported to the API.
+    async fn finish(&self, _lease: &Lease, _report: &Report) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Polls `/internal/jobs/next`.
+pub struct PollSource {
+    client: Client,
+    api_base: String,
+    worker: String,
+}
+
+#[async_trait]
+impl JobSource for PollSource {
+    async fn next(&self) -> Result<Option<Lease>> {
+        pipeline::try_pop_job(&self.api_base, &self.client, &self.worker).await
+    }
+}
+
+/// What a pushing source does with a delivery it has dealt with.
+#[cfg(any(feature = "kafka", test))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Settle {
+    /// Move past the message; nothing is left for this delivery to do.
+    Commit,
+    /// Read the message again, so the job is claimed afresh.
+    Redeliver,
+    /// Leave it uncommitted for whoever owns the partition next.
+    Leave,
+}
+
+#[cfg(any(feature = "kafka", test))]
+impl Settle {
+    /// After a claim was refused with `status`: 404 and 409 mean the job is
+    /// finished, running elsewhere or not due, and the API pushes it again
+    /// when it next becomes runnable; anything else is worth retrying.
+    pub fn refused_claim(status: u16) -> Self {
+        match status {
+            404 | 409 => Settle::Commit,
+            _ => Settle::Redeliver,
+        }
+    }
+
+    /// After running a job: only an outcome the API has recorded lets the
+    /// message go. An unrecorded one is read again, and the claim then
+    /// either reruns the job or finds it in other hands.
+    pub fn reported(report: &Report) -> Self {
+        match (&report.outcome, report.ack) {
+            (Outcome::Released, _) => Settle::Leave,
+            (_, Ack::Accepted) => Settle::Commit,
+            (_, Ack::Rejected(_) | Ack::Unreachable) => Settle::Redeliver,
+        }
+    }
+}
+
+/// What gets published to the results topic once `report` is recorded;
+/// `None` for anything else.
+#[cfg(any(feature = "kafka", test))]
+pub fn result_message(lease: &Lease, report: &Report, worker: &str, finished_at: i64) -> Option<serde_json::Value> {
+    if Settle::reported(report) != Settle::Commit {
+        return None;
+    }
+    let (status, error) = match &report.outcome {
+        Outcome::Failed(e) => ("failed", Some(e.as_str())),
+        _ => ("done", None),
+    };
+    Some(serde_json::json!({
+        "jobId": lease.job_id,
+        "did": lease.did,
+        "status": status,
+        "error": error,
+        "worker": worker,
+        "finishedAt": finished_at,
+    }))
+}
+
+/// One source per lane, selected by `JOB_TRANSPORT`: `kafka` gives each lane
+/// its own consumer in the workers' consumer group (needs the `kafka`
+/// feature); anything else has the lanes share one poller.
+pub fn sources(client: &Client, api_base: &str, worker: &str, lanes: usize) -> Vec<Arc<dyn JobSource>> {
+    let transport = std::env::var("JOB_TRANSPORT").unwrap_or_default();
+    if transport == "kafka" {
+        #[cfg(feature = "kafka")]
+        match kafka::KafkaConfig::from_env().and_then(|cfg| kafka::sources(&cfg, client, api_base, worker, lanes)) {
+            Ok(sources) => {
+                info!(lanes, "consuming jobs from kafka");
+                return sources;
+            }
+            Err(e) => error!(error = %e, "kafka transport unavailable; polling instead"),
+        }
+        #[cfg(not(feature = "kafka"))]
+        error!("built without the kafka feature; polling instead");
+    }
+    info!(lanes, "polling for jobs");
+    let poll: Arc<dyn JobSource> = Arc::new(PollSource { client: client.clone(), api_base: api_base.to_string(), worker: worker.to_string() });
+    vec![poll; lanes]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(outcome: Outcome, ack: Ack) -> Report {
+        Report { outcome, ack }
+    }
+
+    #[test]
+    fn t_messages_are_committed_only_once_the_outcome_is_recorded() {
+        assert_eq!(Settle::reported(&report(Outcome::Done, Ack::Accepted)), Settle::Commit);
+        assert_eq!(Settle::reported(&report(Outcome::Failed("boom".into()), Ack::Accepted)), Settle::Commit);
+        assert_eq!(Settle::reported(&report(Outcome::Done, Ack::Unreachable)), Settle::Redeliver);
+        assert_eq!(Settle::reported(&report(Outcome::Failed("boom".into()), Ack::Rejected(500))), Settle::Redeliver);
+        assert_eq!(Settle::reported(&report(Outcome::Done, Ack::Rejected(409))), Settle::Redeliver);
+        assert_eq!(Settle::reported(&report(Outcome::Released, Ack::Accepted)), Settle::Leave);
+        assert_eq!(Settle::reported(&report(Outcome::Released, Ack::Unreachable)), Settle::Leave);
+    }
+
+    #[test]
+    fn t_refused_claims_skip_only_settled_jobs() {
+        assert_eq!(Settle::refused_claim(404), Settle::Commit);
+        assert_eq!(Settle::refused_claim(409), Settle::Commit);
+        assert_eq!(Settle::refused_claim(500), Settle::Redeliver);
+        assert_eq!(Settle::refused_claim(503), Settle::Redeliver);
+    }
+
+    #[test]
+    fn t_recorded_outcomes_are_published() {
+        let lease = Lease { job_id: "j1".into(), did: "did:a".into(), lease_ms: 1_000 };
+        assert_eq!(
+            result_message(&lease, &report(Outcome::Done, Ack::Accepted), "w1", 42),
+            Some(serde_json::json!({"jobId": "j1", "did": "did:a", "status": "done", "error": null, "worker": "w1", "finishedAt": 42}))
+        );
+        assert_eq!(
+            result_message(&lease, &report(Outcome::Failed("boom".into()), Ack::Accepted), "w1", 42),
+            Some(serde_json::json!({"jobId": "j1", "did": "did:a", "status": "failed", "error": "boom", "worker": "w1", "finishedAt": 42}))
+        );
+        assert_eq!(result_message(&lease, &report(Outcome::Done, Ack::Unreachable), "w1", 42), None);
+        assert_eq!(result_message(&lease, &report(Outcome::Released, Ack::Accepted), "w1", 42), None);
+    }
+}